@@ -0,0 +1,46 @@
+// Copyright (c) 2021 Cognite AS
+//! Compares decoding large bodies with [`rocket_enumform::de::parse_pairs`]
+//! and with [`form_urlencoded::parse`].
+//!
+//! Run with `cargo bench --bench decode`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rocket_enumform::de::{parse_pairs, Pairs};
+
+/// A body of `fields` pairs whose values are `value`, encoded.
+fn body(fields: usize, value: &str) -> String {
+    let mut serializer = form_urlencoded::Serializer::new(String::new());
+    for i in 0..fields {
+        serializer.append_pair(&format!("field{}", i), value);
+    }
+    serializer.finish()
+}
+
+fn decode(c: &mut Criterion) {
+    let text = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. ".repeat(20);
+    let bodies = [
+        ("plain", body(1000, &"x".repeat(1000))),
+        ("text", body(1000, &text)),
+        (
+            "escaped",
+            body(1000, &"København, Øresund & æøå. ".repeat(40)),
+        ),
+    ];
+
+    let mut group = c.benchmark_group("decode");
+    for (name, body) in &bodies {
+        group.throughput(Throughput::Bytes(body.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("form_urlencoded", name),
+            body,
+            |b, body| b.iter(|| -> Pairs<'_> { form_urlencoded::parse(body.as_bytes()).collect() }),
+        );
+        group.bench_with_input(BenchmarkId::new("parse_pairs", name), body, |b, body| {
+            b.iter(|| parse_pairs(body.as_bytes()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
@@ -0,0 +1,69 @@
+// Copyright (c) 2021 Cognite AS
+//! `#[derive(EnumForm)]`: `FromForm` through serde for tagged enums.
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Data, DeriveInput, Error, GenericParam, Lifetime, LifetimeParam};
+
+use crate::serde_attr::Container;
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    if !matches!(input.data, Data::Enum(_)) {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "EnumForm can only be derived for enums",
+        ));
+    }
+    if !Container::from_attrs(&input.attrs)?.is_flat() {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "EnumForm requires an internally tagged enum: add `#[serde(tag = \"...\")]`",
+        ));
+    }
+
+    let ident = &input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let mut generics = input.generics.clone();
+    let lifetime = Lifetime::new("'__r", Span::call_site());
+    generics.params.insert(
+        0,
+        GenericParam::Lifetime(LifetimeParam::new(lifetime.clone())),
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let predicates = where_clause.map(|w| &w.predicates);
+
+    Ok(quote! {
+        const _: () = {
+            use ::rocket_enumform::derive::{async_trait, form, Context, Deserialize};
+
+            #[async_trait]
+            impl #impl_generics form::FromForm<#lifetime> for #ident #ty_generics
+            where
+                Self: Deserialize<#lifetime> + ::std::marker::Send,
+                #predicates
+            {
+                type Context = Context<#lifetime>;
+
+                fn init(opts: form::Options) -> Self::Context {
+                    Context::new(opts)
+                }
+
+                fn push_value(ctxt: &mut Self::Context, field: form::ValueField<#lifetime>) {
+                    ctxt.push_value(field)
+                }
+
+                async fn push_data(ctxt: &mut Self::Context, field: form::DataField<#lifetime, '_>) {
+                    ctxt.push_data(field).await
+                }
+
+                fn push_error(ctxt: &mut Self::Context, error: form::Error<#lifetime>) {
+                    ctxt.push_error(error)
+                }
+
+                fn finalize(ctxt: Self::Context) -> form::Result<#lifetime, Self> {
+                    ctxt.finalize()
+                }
+            }
+        };
+    })
+}
@@ -0,0 +1,82 @@
+// Copyright (c) 2021 Cognite AS
+//! `#[derive(FormExample)]`: a representative value per enum variant.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Error, Expr, Fields, Ident};
+
+use crate::serde_attr::{variant_name, Container, Variant};
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let examples = match &input.data {
+        Data::Struct(data) => {
+            let value = construct(quote!(Self), &data.fields)?;
+            let name = ident.to_string();
+            vec![quote!((#name, #value))]
+        }
+        Data::Enum(data) => {
+            let rename_all = Container::from_attrs(&input.attrs)?.rename_all.serialize;
+            let mut examples = Vec::new();
+            for variant in &data.variants {
+                let attrs = Variant::from_attrs(&variant.attrs)?;
+                let name = variant_name(
+                    &variant.ident.to_string(),
+                    &attrs.rename.serialize,
+                    &rename_all,
+                )?;
+                let variant_ident: &Ident = &variant.ident;
+                let value = construct(quote!(Self::#variant_ident), &variant.fields)?;
+                examples.push(quote!((#name, #value)));
+            }
+            examples
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                ident,
+                "FormExample can only be derived for structs and enums",
+            ))
+        }
+    };
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::rocket_enumform::FormExample for #ident #ty_generics #where_clause {
+            fn examples() -> ::std::vec::Vec<(&'static str, Self)> {
+                ::std::vec![#(#examples),*]
+            }
+        }
+    })
+}
+
+/// An expression constructing `path` with the example value of each field.
+fn construct(path: TokenStream, fields: &Fields) -> syn::Result<TokenStream> {
+    let values = fields
+        .iter()
+        .map(|field| example_value(&field.attrs))
+        .collect::<syn::Result<Vec<_>>>()?;
+    Ok(match fields {
+        Fields::Named(_) => {
+            let idents = fields.iter().map(|field| &field.ident);
+            quote!(#path { #(#idents: #values),* })
+        }
+        Fields::Unnamed(_) => quote!(#path(#(#values),*)),
+        Fields::Unit => path,
+    })
+}
+
+/// The value of a field: the expression of its `#[form_example(...)]`
+/// attribute, converted with `Into`, or its default.
+fn example_value(attrs: &[Attribute]) -> syn::Result<TokenStream> {
+    let mut value = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("form_example")) {
+        if value.is_some() {
+            return Err(Error::new_spanned(attr, "duplicate form_example attribute"));
+        }
+        value = Some(attr.parse_args::<Expr>()?);
+    }
+    Ok(match value {
+        Some(expr) => quote!(::std::convert::Into::into(#expr)),
+        None => quote!(::std::default::Default::default()),
+    })
+}
@@ -0,0 +1,94 @@
+// Copyright (c) 2021 Cognite AS
+//! `#[derive(FormFieldEnum)]`: `FromFormField` and `UriDisplay<Query>` for
+//! enums of unit variants.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields};
+
+use crate::serde_attr::{variant_name, Container, Variant};
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "FormFieldEnum can only be derived for enums",
+            ))
+        }
+    };
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "FormFieldEnum does not support generic enums",
+        ));
+    }
+
+    let container = Container::from_attrs(&input.attrs)?;
+    let mut choices = Vec::new();
+    let mut parse_arms = Vec::new();
+    let mut display_arms = Vec::new();
+    let mut other = None;
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                variant,
+                "FormFieldEnum only supports unit variants",
+            ));
+        }
+
+        let attrs = Variant::from_attrs(&variant.attrs)?;
+        let ident = &variant.ident;
+        let name = ident.to_string();
+        let rename_all = &container.rename_all;
+        let de_name = variant_name(&name, &attrs.rename.deserialize, &rename_all.deserialize)?;
+        let ser_name = variant_name(&name, &attrs.rename.serialize, &rename_all.serialize)?;
+
+        if attrs.other {
+            other = Some(ident);
+        } else {
+            let aliases = &attrs.aliases;
+            parse_arms.push(quote!(#de_name #(| #aliases)* => Ok(Self::#ident),));
+            choices.push(de_name);
+        }
+        display_arms.push(quote!(Self::#ident => #ser_name,));
+    }
+
+    let fallback = match other {
+        Some(ident) => quote!(Ok(Self::#ident)),
+        None => quote! {
+            Err(form::error::ErrorKind::InvalidChoice { choices: Cow::Borrowed(CHOICES) }.into())
+        },
+    };
+
+    let ident = &input.ident;
+    Ok(quote! {
+        const _: () = {
+            use ::rocket_enumform::derive::{form, uri, Cow};
+
+            const CHOICES: &[Cow<'static, str>] = &[#(Cow::Borrowed(#choices)),*];
+
+            impl<'__v> form::FromFormField<'__v> for #ident {
+                fn from_value(field: form::ValueField<'__v>) -> form::Result<'__v, Self> {
+                    match field.value {
+                        #(#parse_arms)*
+                        _ => #fallback,
+                    }
+                }
+            }
+
+            impl uri::UriDisplay<uri::Query> for #ident {
+                fn fmt(&self, f: &mut uri::Formatter<'_, uri::Query>) -> ::std::fmt::Result {
+                    f.write_value(match self {
+                        #(#display_arms)*
+                    })
+                }
+            }
+        };
+
+        ::rocket_enumform::derive::impl_from_uri_param_identity!(
+            [::rocket_enumform::derive::uri::Query] #ident
+        );
+    })
+}
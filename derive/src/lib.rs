@@ -0,0 +1,49 @@
+// Copyright (c) 2021 Cognite AS
+//! Derive macros for [`rocket-enumform`](https://docs.rs/rocket-enumform).
+//!
+//! The macros are re-exported by `rocket-enumform` with the `derive` feature
+//! enabled and should be used from there.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput, Error};
+
+mod enum_form;
+mod form_example;
+mod form_field_enum;
+mod serde_attr;
+
+/// Derives Rocket's `FromForm` for an internally tagged serde enum.
+///
+/// See `rocket_enumform::EnumForm` for documentation.
+#[proc_macro_derive(EnumForm)]
+pub fn derive_enum_form(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    enum_form::expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derives `rocket_enumform::FormExample`, with an example per enum variant
+/// built from the `#[form_example(...)]` attributes of its fields or their
+/// defaults.
+///
+/// See `rocket_enumform::FormExample` for documentation.
+#[proc_macro_derive(FormExample, attributes(form_example))]
+pub fn derive_form_example(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    form_example::expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derives Rocket's `FromFormField` and `UriDisplay<Query>` for an enum of
+/// unit variants, using serde's variant names.
+///
+/// See `rocket_enumform::FormFieldEnum` for documentation.
+#[proc_macro_derive(FormFieldEnum)]
+pub fn derive_form_field_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    form_field_enum::expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
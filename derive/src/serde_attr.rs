@@ -0,0 +1,161 @@
+// Copyright (c) 2021 Cognite AS
+//! The subset of `#[serde(...)]` attributes the derives need to agree with
+//! serde on.
+
+use syn::meta::ParseNestedMeta;
+use syn::{Attribute, Error, LitStr};
+
+/// Serde attributes of an enum.
+#[derive(Default)]
+pub struct Container {
+    pub tag: bool,
+    pub content: bool,
+    pub untagged: bool,
+    pub rename_all: Renamed,
+}
+
+/// Serde attributes of an enum variant.
+#[derive(Default)]
+pub struct Variant {
+    pub rename: Renamed,
+    pub aliases: Vec<String>,
+    pub other: bool,
+}
+
+/// A `rename` or `rename_all` value, which may differ by direction.
+#[derive(Default)]
+pub struct Renamed {
+    pub serialize: Option<LitStr>,
+    pub deserialize: Option<LitStr>,
+}
+
+impl Container {
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut container = Container::default();
+        parse_serde_attrs(attrs, |meta| {
+            if meta.path.is_ident("tag") {
+                container.tag = true;
+            } else if meta.path.is_ident("content") {
+                container.content = true;
+            } else if meta.path.is_ident("untagged") {
+                container.untagged = true;
+            } else if meta.path.is_ident("rename_all") {
+                container.rename_all = Renamed::parse(&meta)?;
+                return Ok(());
+            }
+            skip_value(&meta)
+        })?;
+        Ok(container)
+    }
+
+    /// Whether variants keep their fields at the top level: internally
+    /// tagged and untagged enums.
+    pub fn is_flat(&self) -> bool {
+        (self.tag && !self.content) || self.untagged
+    }
+}
+
+impl Variant {
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut variant = Variant::default();
+        parse_serde_attrs(attrs, |meta| {
+            if meta.path.is_ident("rename") {
+                variant.rename = Renamed::parse(&meta)?;
+                return Ok(());
+            } else if meta.path.is_ident("alias") {
+                variant
+                    .aliases
+                    .push(meta.value()?.parse::<LitStr>()?.value());
+                return Ok(());
+            } else if meta.path.is_ident("other") {
+                variant.other = true;
+            }
+            skip_value(&meta)
+        })?;
+        Ok(variant)
+    }
+}
+
+impl Renamed {
+    fn parse(meta: &ParseNestedMeta<'_>) -> syn::Result<Self> {
+        if meta.input.peek(syn::Token![=]) {
+            let lit: LitStr = meta.value()?.parse()?;
+            return Ok(Renamed {
+                serialize: Some(lit.clone()),
+                deserialize: Some(lit),
+            });
+        }
+
+        let mut renamed = Renamed::default();
+        meta.parse_nested_meta(|nested| {
+            let lit: LitStr = nested.value()?.parse()?;
+            if nested.path.is_ident("serialize") {
+                renamed.serialize = Some(lit);
+            } else if nested.path.is_ident("deserialize") {
+                renamed.deserialize = Some(lit);
+            }
+            Ok(())
+        })?;
+        Ok(renamed)
+    }
+}
+
+fn parse_serde_attrs(
+    attrs: &[Attribute],
+    mut f: impl FnMut(ParseNestedMeta<'_>) -> syn::Result<()>,
+) -> syn::Result<()> {
+    for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        attr.parse_nested_meta(&mut f)?;
+    }
+    Ok(())
+}
+
+/// Consumes the value of an attribute this crate does not interpret.
+fn skip_value(meta: &ParseNestedMeta<'_>) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|nested| skip_value(&nested))?;
+    }
+    Ok(())
+}
+
+/// The name serde uses for `variant`, given its `rename` and the enum's
+/// `rename_all` in the same direction.
+pub fn variant_name(
+    variant: &str,
+    rename: &Option<LitStr>,
+    rename_all: &Option<LitStr>,
+) -> syn::Result<String> {
+    match (rename, rename_all) {
+        (Some(lit), _) => Ok(lit.value()),
+        (None, Some(rule)) => rename_variant(rule, variant),
+        (None, None) => Ok(variant.to_string()),
+    }
+}
+
+/// Applies a serde `rename_all` rule to a variant name.
+fn rename_variant(rule: &LitStr, variant: &str) -> syn::Result<String> {
+    let snake = || {
+        let mut snake = String::new();
+        for (i, ch) in variant.char_indices() {
+            if i > 0 && ch.is_uppercase() {
+                snake.push('_');
+            }
+            snake.push(ch.to_ascii_lowercase());
+        }
+        snake
+    };
+
+    Ok(match rule.value().as_str() {
+        "lowercase" => variant.to_ascii_lowercase(),
+        "UPPERCASE" => variant.to_ascii_uppercase(),
+        "PascalCase" => variant.to_string(),
+        "camelCase" => variant[..1].to_ascii_lowercase() + &variant[1..],
+        "snake_case" => snake(),
+        "SCREAMING_SNAKE_CASE" => snake().to_ascii_uppercase(),
+        "kebab-case" => snake().replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => snake().replace('_', "-").to_ascii_uppercase(),
+        _ => return Err(Error::new_spanned(rule, "unknown rename rule")),
+    })
+}
@@ -0,0 +1,153 @@
+// Copyright (c) 2021 Cognite AS
+//! The request-local cache of the form body and its pairs, shared by the
+//! guards of a request.
+
+use std::borrow::Cow;
+use std::ops::Range;
+use std::ptr;
+use std::sync::OnceLock;
+
+use rocket::data::{Capped, N};
+use rocket::request::Request;
+
+use crate::{codec, de, EnumFormConfig};
+
+/// The body of a request, once read, and its pairs, once split.
+#[derive(Default)]
+struct FormCache {
+    body: OnceLock<(Vec<u8>, N)>,
+    pairs: OnceLock<(Split, Vec<(Text, Text)>)>,
+    /// The body and the query, with their values redacted.
+    redacted_body: OnceLock<String>,
+    redacted_query: OnceLock<String>,
+}
+
+/// A key or value of the cached pairs: where the body holds it as it is, the
+/// range of the body, so that large bodies are not copied, and the decoded
+/// text otherwise.
+enum Text {
+    Body(Range<usize>),
+    Decoded(String),
+}
+
+impl Text {
+    /// Caches `text`, a key or value of `body`.
+    fn new(body: &[u8], text: Cow<'_, str>) -> Self {
+        let start = (text.as_ptr() as usize).wrapping_sub(body.as_ptr() as usize);
+        match text {
+            Cow::Borrowed(text) if body.len().checked_sub(text.len()) >= Some(start) => {
+                Text::Body(start..start + text.len())
+            }
+            text => Text::Decoded(text.into_owned()),
+        }
+    }
+
+    fn get<'a>(&'a self, body: &'a [u8]) -> Cow<'a, str> {
+        match self {
+            // Valid UTF-8, as it was borrowed as text, so this is not a copy.
+            Text::Body(range) => String::from_utf8_lossy(&body[range.clone()]),
+            Text::Decoded(text) => Cow::Borrowed(text),
+        }
+    }
+}
+
+/// The options which affect how a body is split into pairs.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Split {
+    semicolon_separator: bool,
+    literal_plus: bool,
+}
+
+impl Split {
+    fn of(opts: &de::Options) -> Self {
+        Split {
+            semicolon_separator: opts.semicolon_separator,
+            literal_plus: opts.literal_plus,
+        }
+    }
+}
+
+fn cache<'r>(req: &'r Request<'_>) -> &'r FormCache {
+    req.local_cache(FormCache::default)
+}
+
+/// The body of `req`, if a guard has read it already.
+pub(crate) fn body<'r>(req: &'r Request<'_>) -> Option<Capped<&'r [u8]>> {
+    let (body, n) = cache(req).body.get()?;
+    Some(Capped::new(body.as_slice(), *n))
+}
+
+/// Caches `body` as the body of `req`, unless one was cached already, and
+/// returns the cached body.
+pub(crate) fn store_body<'r>(req: &'r Request<'_>, body: Capped<Vec<u8>>) -> Capped<&'r [u8]> {
+    let n = body.n;
+    let (body, n) = cache(req).body.get_or_init(|| (body.into_inner(), n));
+    Capped::new(body.as_slice(), *n)
+}
+
+/// Splits `body` into urlencoded pairs with `opts`, reusing the pairs of a
+/// previous guard if `body` is the cached body of `req` and was split the same
+/// way.
+pub(crate) fn pairs<'r>(req: &'r Request<'_>, body: &'r [u8], opts: &de::Options) -> de::Pairs<'r> {
+    let cache = cache(req);
+    let cached = cache.body.get().map(|(b, _)| b.as_slice());
+    if !cached.is_some_and(|cached| ptr::eq(cached, body)) {
+        return codec::decode_bytes(body, opts);
+    }
+
+    let split = Split::of(opts);
+    let (cached, pairs) = cache.pairs.get_or_init(|| {
+        let pairs = codec::decode_bytes(body, opts)
+            .into_iter()
+            .map(|(k, v)| (Text::new(body, k), Text::new(body, v)))
+            .collect();
+        (split, pairs)
+    });
+    match *cached == split {
+        true => pairs
+            .iter()
+            .map(|(k, v)| (k.get(body), v.get(body)))
+            .collect(),
+        false => codec::decode_bytes(body, opts),
+    }
+}
+
+/// `raw` with all values replaced by `[redacted]`, if it is the cached body or
+/// the query of `req`, where the redacted copy is cached, and nothing
+/// otherwise.
+pub(crate) fn redacted<'r>(req: &'r Request<'_>, raw: &'r str) -> &'r str {
+    let cache = cache(req);
+    let body = cache.body.get().map(|(b, _)| b.as_slice());
+    let query = req.uri().query().map(|q| q.as_str());
+    let redacted = if body.is_some_and(|body| ptr::eq(body, raw.as_bytes())) {
+        &cache.redacted_body
+    } else if query.is_some_and(|query| ptr::eq(query, raw)) {
+        &cache.redacted_query
+    } else {
+        return "";
+    };
+    let semicolon = EnumFormConfig::of(req).semicolon_separator;
+    redacted.get_or_init(|| redact_values(raw, semicolon))
+}
+
+/// `raw` with the values of its pairs, separated at `&` and, with
+/// `semicolon`, at `;`, replaced by `[redacted]`.
+fn redact_values(raw: &str, semicolon: bool) -> String {
+    let separator = |c| c == '&' || (semicolon && c == ';');
+    let mut redacted = String::with_capacity(raw.len());
+    for pair in raw.split_inclusive(separator) {
+        let (pair, end) = match pair.strip_suffix(separator) {
+            Some(stripped) => (stripped, &pair[stripped.len()..]),
+            None => (pair, ""),
+        };
+        match pair.split_once('=') {
+            Some((key, _)) => {
+                redacted.push_str(key);
+                redacted.push_str("=[redacted]");
+            }
+            None => redacted.push_str(pair),
+        }
+        redacted.push_str(end);
+    }
+    redacted
+}
@@ -0,0 +1,132 @@
+// Copyright (c) 2021 Cognite AS
+//! Translating the case of keys between the wire and Rust field names.
+
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+
+/// The case of keys on the wire, translated to and from the `snake_case` of
+/// Rust field names.
+///
+/// Incoming keys are translated to `snake_case` before deserialization, and
+/// serialized keys from `snake_case` to the wire case, so that a frontend
+/// submitting `contentOne` reaches a `content_one` field without
+/// `#[serde(rename)]`. Only keys are translated; the values of enum tags are
+/// left to serde.
+///
+/// ```rust
+/// use rocket_enumform::KeyCase;
+///
+/// assert_eq!(KeyCase::Camel.decode("contentOne"), "content_one");
+/// assert_eq!(KeyCase::Camel.encode("content_one"), "contentOne");
+/// assert_eq!(KeyCase::Kebab.encode("content_one"), "content-one");
+/// ```
+#[derive(Debug, Clone, Copy, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyCase {
+    /// `contentOne`. Incoming `kebab-case` keys are normalized as well.
+    Camel,
+
+    /// `content-one`.
+    Kebab,
+
+    /// `content_one`. Incoming `camelCase` and `kebab-case` keys are
+    /// normalized as well; serialized keys are left as they are.
+    Snake,
+
+    /// A custom translation: `decode` maps incoming keys to field names and
+    /// `encode` maps serialized field names to keys. It can only be set in
+    /// code, not in `Rocket.toml`.
+    #[serde(skip)]
+    Custom {
+        /// Translates a key on the wire to a field name.
+        decode: fn(&str) -> String,
+        /// Translates a field name to a key on the wire.
+        encode: fn(&str) -> String,
+    },
+}
+
+impl KeyCase {
+    /// Translates a key on the wire to a `snake_case` field name.
+    pub fn decode(&self, key: &str) -> String {
+        match self {
+            KeyCase::Camel | KeyCase::Snake => kebab_to_snake(&camel_to_snake(key)),
+            KeyCase::Kebab => kebab_to_snake(key),
+            KeyCase::Custom { decode, .. } => decode(key),
+        }
+    }
+
+    /// Translates a `snake_case` field name to a key on the wire.
+    pub fn encode(&self, name: &str) -> String {
+        match self {
+            KeyCase::Camel => snake_to_camel(name),
+            KeyCase::Kebab => name.replace('_', "-"),
+            KeyCase::Snake => name.to_string(),
+            KeyCase::Custom { encode, .. } => encode(name),
+        }
+    }
+}
+
+/// Custom translations are equal if they are the same functions.
+impl PartialEq for KeyCase {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                KeyCase::Custom { decode, encode },
+                KeyCase::Custom {
+                    decode: other_decode,
+                    encode: other_encode,
+                },
+            ) => {
+                *decode as usize == *other_decode as usize
+                    && *encode as usize == *other_encode as usize
+            }
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+impl Hash for KeyCase {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        if let KeyCase::Custom { decode, encode } = self {
+            (*decode as usize).hash(state);
+            (*encode as usize).hash(state);
+        }
+    }
+}
+
+fn camel_to_snake(key: &str) -> String {
+    let mut name = String::with_capacity(key.len() + 4);
+    for (i, c) in key.char_indices() {
+        if c.is_uppercase() {
+            if i > 0 {
+                name.push('_');
+            }
+            name.extend(c.to_lowercase());
+        } else {
+            name.push(c);
+        }
+    }
+    name
+}
+
+fn kebab_to_snake(key: &str) -> String {
+    key.replace('-', "_")
+}
+
+fn snake_to_camel(name: &str) -> String {
+    let mut key = String::with_capacity(name.len());
+    let mut upper = false;
+    for (i, c) in name.char_indices() {
+        if c == '_' && i > 0 {
+            upper = true;
+        } else if upper {
+            key.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            key.push(c);
+        }
+    }
+    key
+}
@@ -0,0 +1,76 @@
+// Copyright (c) 2021 Cognite AS
+//! Transcoding of form bodies in other charsets than UTF-8.
+
+use encoding_rs::{Encoding, UTF_8};
+use percent_encoding::percent_decode;
+use rocket::request::Request;
+
+use crate::{de, Error};
+
+/// Decodes a body in the `charset` of the request's Content-Type or, if there
+/// is none, of the `_charset_` field of the form, which default to UTF-8.
+///
+/// Percent-escapes encode bytes in the same charset, so a body in another
+/// charset is re-encoded pair by pair as an equivalent UTF-8 body. Unknown
+/// charsets, and charsets in which `&` and `=` are not ASCII, fail with
+/// [`Error::ContentType`].
+pub(crate) fn decode_body<'r>(req: &'r Request<'_>, bytes: Vec<u8>) -> Result<Vec<u8>, Error<'r>> {
+    let encoding = match req.content_type().and_then(|ct| ct.param("charset")) {
+        Some(label) => Encoding::for_label(label.as_bytes())
+            .filter(|encoding| encoding.is_ascii_compatible())
+            .ok_or_else(|| Error::ContentType(req.content_type()))?,
+        None => form_charset(&bytes).unwrap_or(UTF_8),
+    };
+    if encoding == UTF_8 {
+        return Ok(bytes);
+    }
+
+    // Separators are kept as they are, whichever are used.
+    let mut body = String::with_capacity(bytes.len());
+    for segment in bytes.split_inclusive(|&b| b == b'&' || b == b';') {
+        let (pair, separator) = match segment.split_last() {
+            Some((&b, pair)) if b == b'&' || b == b';' => (pair, Some(b as char)),
+            _ => (segment, None),
+        };
+        let mut split = pair.splitn(2, |&b| b == b'=');
+        transcode(&mut body, encoding, split.next().unwrap_or_default());
+        if let Some(value) = split.next() {
+            body.push('=');
+            transcode(&mut body, encoding, value);
+        }
+        body.extend(separator);
+    }
+
+    Ok(body.into_bytes())
+}
+
+/// Re-encodes a key or value in `encoding` as UTF-8, keeping each `+` as it
+/// is and escaping spaces as `%20`, so that the guard's options still decide
+/// whether `+` is a space.
+fn transcode(body: &mut String, encoding: &'static Encoding, input: &[u8]) {
+    for (i, part) in input.split(|&b| b == b'+').enumerate() {
+        if i > 0 {
+            body.push('+');
+        }
+        for chunk in form_urlencoded::byte_serialize(decode(encoding, part).as_bytes()) {
+            body.push_str(&chunk.replace('+', "%20"));
+        }
+    }
+}
+
+/// Unescapes percent-escapes in `encoding`.
+fn decode(encoding: &'static Encoding, input: &[u8]) -> String {
+    let bytes: Vec<u8> = percent_decode(input).collect();
+    let (text, _) = encoding.decode_without_bom_handling(&bytes);
+    text.into_owned()
+}
+
+/// The encoding named by the `_charset_` field of a form, if it is known.
+fn form_charset(bytes: &[u8]) -> Option<&'static Encoding> {
+    let prefix = format!("{}=", de::CHARSET_FIELD);
+    bytes
+        .split(|&b| b == b'&')
+        .find_map(|pair| pair.strip_prefix(prefix.as_bytes()))
+        .and_then(Encoding::for_label)
+        .filter(|encoding| encoding.is_ascii_compatible())
+}
@@ -0,0 +1,154 @@
+// Copyright (c) 2021 Cognite AS
+//! A form field for HTML checkboxes.
+
+use std::fmt;
+use std::ops::Deref;
+
+use rocket::form::{self, FromFormField, ValueField};
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+/// The Checkbox form field: a `bool` with the semantics of an HTML checkbox.
+///
+/// A checked checkbox submits `on`, or its `value` attribute, and an unchecked
+/// one submits nothing, which a plain `bool` field fails on. A Checkbox is
+/// `true` for `on`, `true`, `yes` and `1`, and `false` for `off`, `false`,
+/// `no`, `0`, an empty value or no value at all. Other values fail.
+///
+/// It serializes as a checkbox submits: `on` when checked and, like `None`,
+/// nothing when unchecked.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket_enumform::{Checkbox, UrlEncoded};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Signup {
+///     email: String,
+///     newsletter: Checkbox,
+/// }
+///
+/// #[post("/signup", format = "form", data = "<form>")]
+/// fn signup(form: UrlEncoded<Signup>) -> String {
+///     format!("{} {}", form.email, *form.newsletter)
+/// }
+///
+/// let signup: Signup = rocket_enumform::from_str("email=a@b.c").unwrap();
+/// assert!(!*signup.newsletter);
+/// let signup: Signup = rocket_enumform::from_str("email=a@b.c&newsletter=on").unwrap();
+/// assert!(*signup.newsletter);
+/// ```
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Checkbox(pub bool);
+
+impl Checkbox {
+    /// Consumes the Checkbox wrapper and returns whether it is checked.
+    #[inline(always)]
+    pub fn into_inner(self) -> bool {
+        self.0
+    }
+
+    /// Parses a submitted value.
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "on" | "true" | "yes" | "1" => Some(Checkbox(true)),
+            "off" | "false" | "no" | "0" | "" => Some(Checkbox(false)),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for Checkbox {
+    fn from(checked: bool) -> Self {
+        Checkbox(checked)
+    }
+}
+
+impl From<Checkbox> for bool {
+    fn from(checkbox: Checkbox) -> Self {
+        checkbox.0
+    }
+}
+
+impl Deref for Checkbox {
+    type Target = bool;
+
+    #[inline(always)]
+    fn deref(&self) -> &bool {
+        &self.0
+    }
+}
+
+/// Missing fields are unchecked: serde deserializes them as `None`.
+impl<'de> Deserialize<'de> for Checkbox {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_option(CheckboxVisitor)
+    }
+}
+
+struct CheckboxVisitor;
+
+impl<'de> Visitor<'de> for CheckboxVisitor {
+    type Value = Checkbox;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a checkbox value such as `on`")
+    }
+
+    fn visit_none<E>(self) -> Result<Checkbox, E> {
+        Ok(Checkbox(false))
+    }
+
+    fn visit_unit<E>(self) -> Result<Checkbox, E> {
+        Ok(Checkbox(false))
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Checkbox, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Checkbox, E> {
+        Ok(Checkbox(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Checkbox, E> {
+        match v {
+            0 | 1 => Ok(Checkbox(v == 1)),
+            _ => Err(E::invalid_value(de::Unexpected::Unsigned(v), &self)),
+        }
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Checkbox, E> {
+        match v {
+            0 | 1 => Ok(Checkbox(v == 1)),
+            _ => Err(E::invalid_value(de::Unexpected::Signed(v), &self)),
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Checkbox, E> {
+        Checkbox::parse(v).ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
+
+impl Serialize for Checkbox {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            true => serializer.serialize_str("on"),
+            false => serializer.serialize_none(),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'v> FromFormField<'v> for Checkbox {
+    fn from_value(field: ValueField<'v>) -> form::Result<'v, Self> {
+        Checkbox::parse(field.value)
+            .ok_or_else(|| form::Error::validation("expected a checkbox value such as `on`").into())
+    }
+
+    fn default() -> Option<Self> {
+        Some(Checkbox(false))
+    }
+}
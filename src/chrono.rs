@@ -0,0 +1,136 @@
+// Copyright (c) 2021 Cognite AS
+//! Form fields for [`chrono`] dates and times in the formats of HTML inputs.
+//!
+//! This module is only available with the `chrono` feature enabled.
+
+use ::chrono::{NaiveDate, NaiveDateTime};
+
+use crate::date::form_date;
+
+/// The format of a date or time field, in [`chrono`]'s
+/// [`strftime`](chrono::format::strftime) syntax.
+///
+/// ```rust
+/// use rocket_enumform::chrono::{FormDate, Format};
+/// use serde::Deserialize;
+///
+/// struct Dotted;
+///
+/// impl Format for Dotted {
+///     const FORMAT: &'static str = "%d.%m.%Y";
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Invoice {
+///     due: FormDate<Dotted>,
+/// }
+///
+/// let invoice: Invoice = rocket_enumform::from_str("due=01.03.2024").unwrap();
+/// assert_eq!(invoice.due.to_string(), "2024-03-01");
+/// ```
+pub trait Format {
+    /// The format values are parsed in and written in.
+    const FORMAT: &'static str;
+
+    /// Further formats values are parsed in.
+    const ALSO_ACCEPTS: &'static [&'static str] = &[];
+}
+
+/// `2024-03-01`, as submitted by `<input type="date">`.
+#[derive(Debug, Clone, Copy)]
+pub struct HtmlDate;
+
+impl Format for HtmlDate {
+    const FORMAT: &'static str = "%Y-%m-%d";
+}
+
+/// `2024-03-01T09:30`, as submitted by `<input type="datetime-local">`, with
+/// seconds and fractions of them where the input's `step` allows them.
+/// Values are written with seconds, which the input accepts as well.
+#[derive(Debug, Clone, Copy)]
+pub struct HtmlDateTime;
+
+impl Format for HtmlDateTime {
+    const FORMAT: &'static str = "%Y-%m-%dT%H:%M:%S%.f";
+    const ALSO_ACCEPTS: &'static [&'static str] = &["%Y-%m-%dT%H:%M"];
+}
+
+fn parse_date(text: &str, format: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text, format).ok()
+}
+
+fn parse_date_time(text: &str, format: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(text, format).ok()
+}
+
+fn format_date(date: &NaiveDate, format: &str) -> Option<String> {
+    let mut text = String::new();
+    std::fmt::write(&mut text, format_args!("{}", date.format(format))).ok()?;
+    Some(text)
+}
+
+fn format_date_time(date_time: &NaiveDateTime, format: &str) -> Option<String> {
+    let mut text = String::new();
+    std::fmt::write(&mut text, format_args!("{}", date_time.format(format))).ok()?;
+    Some(text)
+}
+
+form_date! {
+    /// The FormDate field: a [`NaiveDate`] in the format `F`, by default the
+    /// one of `<input type="date">`.
+    ///
+    /// FormDate deserializes with serde and is a
+    /// [`FromFormField`](rocket::form::FromFormField); values are
+    /// serialized in the same format.
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// # use serde::Deserialize;
+    /// use rocket_enumform::chrono::FormDate;
+    /// use rocket_enumform::UrlEncoded;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Booking {
+    ///     arrival: FormDate,
+    /// }
+    ///
+    /// #[post("/book", format = "form", data = "<form>")]
+    /// fn book(form: UrlEncoded<Booking>) -> String {
+    ///     form.arrival.to_string()
+    /// }
+    /// ```
+    FormDate<HtmlDate>(NaiveDate),
+    "a date",
+    parse_date,
+    format_date
+}
+
+form_date! {
+    /// The FormDateTime field: a [`NaiveDateTime`] in the format `F`, by
+    /// default the one of `<input type="datetime-local">`.
+    ///
+    /// FormDateTime deserializes with serde and is a
+    /// [`FromFormField`](rocket::form::FromFormField); values are
+    /// serialized in the same format.
+    ///
+    /// ```rust
+    /// use rocket_enumform::chrono::FormDateTime;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Debug, Deserialize, Serialize)]
+    /// struct Meeting {
+    ///     start: FormDateTime,
+    /// }
+    ///
+    /// let meeting: Meeting = rocket_enumform::from_str("start=2024-03-01T09%3A30").unwrap();
+    /// assert_eq!(meeting.start.to_string(), "2024-03-01 09:30:00");
+    /// assert_eq!(
+    ///     rocket_enumform::to_string(&meeting).unwrap(),
+    ///     "start=2024-03-01T09%3A30%3A00"
+    /// );
+    /// ```
+    FormDateTime<HtmlDateTime>(NaiveDateTime),
+    "a date and time",
+    parse_date_time,
+    format_date_time
+}
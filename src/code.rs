@@ -0,0 +1,141 @@
+// Copyright (c) 2021 Cognite AS
+//! Machine-readable codes of the errors of the guards.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// What went wrong, for clients to branch on instead of the English message
+/// of an error.
+///
+/// The code of an error is its [`Error::code()`](crate::Error::code), that
+/// of a parse error its [`de::Error::code()`](crate::de::Error::code). It is
+/// the `code` member of the problem details responses of
+/// [`Error`](crate::Error), and of [`ParseFailure`](crate::ParseFailure) for
+/// catchers. It serializes, and displays, in `snake_case`.
+///
+/// ```rust
+/// use rocket_enumform::ErrorCode;
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize)]
+/// #[serde(tag = "type", rename_all = "snake_case")]
+/// enum Body {
+///     VariantOne { content_one: String },
+///     VariantTwo { content_two: usize },
+/// }
+///
+/// let code = |form| rocket_enumform::from_str::<Body>(form).unwrap_err().code();
+/// assert_eq!(code("content_one=hello"), ErrorCode::MissingTag);
+/// assert_eq!(code("type=variant_three"), ErrorCode::UnknownVariant);
+/// assert_eq!(code("type=variant_one"), ErrorCode::MissingField);
+/// assert_eq!(code("type=variant_two&content_two=many"), ErrorCode::InvalidType);
+/// assert_eq!(ErrorCode::MissingTag.to_string(), "missing_tag");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// Reading the request data failed.
+    Io,
+
+    /// The request data exceeded the data limit.
+    BodyTooLarge,
+
+    /// The Content-Type of the request was missing or rejected.
+    UnsupportedContentType,
+
+    /// The Content-Encoding of the request was rejected.
+    UnsupportedContentEncoding,
+
+    /// The CSRF token of the form was missing or rejected.
+    InvalidCsrfToken,
+
+    /// The signature of a webhook was missing, invalid or expired.
+    InvalidSignature,
+
+    /// The tag of an internally tagged enum was missing.
+    MissingTag,
+
+    /// The variant named by a tag, or by the key of an externally tagged
+    /// enum, does not exist.
+    UnknownVariant,
+
+    /// A required field was missing.
+    MissingField,
+
+    /// A field which the target does not have was submitted.
+    UnknownField,
+
+    /// A field was submitted more than once.
+    DuplicateField,
+
+    /// A field was submitted both with a value and with nested keys.
+    ConflictingField,
+
+    /// A value was of the wrong type, such as a map for a string.
+    InvalidType,
+
+    /// A value was of the right type but invalid, such as text for a number.
+    InvalidValue,
+
+    /// A sequence or tuple had the wrong number of values.
+    InvalidLength,
+
+    /// More pairs were submitted than [`Options::max_pairs`] allows.
+    ///
+    /// [`Options::max_pairs`]: crate::de::Options::max_pairs
+    TooManyFields,
+
+    /// A key was longer than [`Options::max_key_len`] allows.
+    ///
+    /// [`Options::max_key_len`]: crate::de::Options::max_key_len
+    KeyTooLong,
+
+    /// A value was longer than [`Options::max_value_len`] allows.
+    ///
+    /// [`Options::max_value_len`]: crate::de::Options::max_value_len
+    ValueTooLong,
+
+    /// The data deserialized but failed validation, with the `validator`
+    /// feature.
+    ValidationFailed,
+
+    /// The data failed to deserialize otherwise, for instance as no variant
+    /// of an untagged enum.
+    Invalid,
+}
+
+impl ErrorCode {
+    /// The code in `snake_case`, as it serializes.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Io => "io",
+            Self::BodyTooLarge => "body_too_large",
+            Self::UnsupportedContentType => "unsupported_content_type",
+            Self::UnsupportedContentEncoding => "unsupported_content_encoding",
+            Self::InvalidCsrfToken => "invalid_csrf_token",
+            Self::InvalidSignature => "invalid_signature",
+            Self::MissingTag => "missing_tag",
+            Self::UnknownVariant => "unknown_variant",
+            Self::MissingField => "missing_field",
+            Self::UnknownField => "unknown_field",
+            Self::DuplicateField => "duplicate_field",
+            Self::ConflictingField => "conflicting_field",
+            Self::InvalidType => "invalid_type",
+            Self::InvalidValue => "invalid_value",
+            Self::InvalidLength => "invalid_length",
+            Self::TooManyFields => "too_many_fields",
+            Self::KeyTooLong => "key_too_long",
+            Self::ValueTooLong => "value_too_long",
+            Self::ValidationFailed => "validation_failed",
+            Self::Invalid => "invalid",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,275 @@
+// Copyright (c) 2021 Cognite AS
+//! Pluggable wire formats for form-like request and response bodies.
+
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use rocket::data::{Data, FromData, Outcome};
+use rocket::error_;
+use rocket::http::{ContentType, Status};
+use rocket::request::Request;
+use rocket::response::{self, Responder};
+use serde::{Deserialize, Serialize};
+
+use crate::{de, ser, EnumFormConfig, Error};
+
+/// A wire format for key/value pairs.
+///
+/// A codec only translates between the raw body and decoded key/value pairs;
+/// mapping pairs to and from `T` is shared by all codecs and done by the
+/// [`de`](crate::de) and [`ser`](crate::ser) modules. Codecs are selected with
+/// the second type parameter of [`Encoded`].
+///
+/// # Example
+///
+/// A codec for bodies which separate pairs with `;` instead of `&`:
+///
+/// ```rust
+/// use rocket_enumform::{de, ser, Encoded, FormCodec};
+///
+/// struct SemicolonCodec;
+///
+/// impl FormCodec for SemicolonCodec {
+///     fn decode(input: &str) -> Result<de::Pairs<'_>, de::Error> {
+///         Ok(input
+///             .split(';')
+///             .flat_map(|pair| form_urlencoded::parse(pair.as_bytes()))
+///             .collect())
+///     }
+///
+///     fn encode(pairs: &[(String, String)]) -> Result<String, ser::Error> {
+///         let pairs: Vec<_> = pairs
+///             .iter()
+///             .map(|pair| {
+///                 form_urlencoded::Serializer::new(String::new())
+///                     .extend_pairs(Some(pair))
+///                     .finish()
+///             })
+///             .collect();
+///         Ok(pairs.join(";"))
+///     }
+/// }
+///
+/// type SemicolonEncoded<T> = Encoded<T, SemicolonCodec>;
+/// ```
+pub trait FormCodec {
+    /// Splits `input` into decoded key/value pairs.
+    fn decode(input: &str) -> Result<de::Pairs<'_>, de::Error>;
+
+    /// Joins decoded key/value pairs into a response body.
+    fn encode(pairs: &[(String, String)]) -> Result<String, ser::Error>;
+
+    /// Joins decoded key/value pairs into a response body with `opts`.
+    /// Defaults to [`encode`](FormCodec::encode), ignoring the options.
+    fn encode_with(pairs: &[(String, String)], opts: ser::Options) -> Result<String, ser::Error> {
+        let _ = opts;
+        Self::encode(pairs)
+    }
+
+    /// The Content-Type of encoded response bodies.
+    fn content_type() -> ContentType {
+        ContentType::Form
+    }
+}
+
+/// The `application/x-www-form-urlencoded` wire format used by [`UrlEncoded`](crate::UrlEncoded).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UrlEncodedCodec;
+
+impl FormCodec for UrlEncodedCodec {
+    fn decode(input: &str) -> Result<de::Pairs<'_>, de::Error> {
+        Ok(de::parse_pairs(input.as_bytes()))
+    }
+
+    fn encode(pairs: &[(String, String)]) -> Result<String, ser::Error> {
+        Self::encode_with(pairs, ser::Options::default())
+    }
+
+    fn encode_with(pairs: &[(String, String)], opts: ser::Options) -> Result<String, ser::Error> {
+        Ok(ser::encode_pairs(pairs, &opts))
+    }
+}
+
+/// Deserializes an instance of `T` from `input` in the wire format of `C`
+/// with `opts`.
+pub(crate) fn decode_with<'de, C: FormCodec, T: Deserialize<'de>>(
+    input: &'de str,
+    opts: de::Options,
+) -> Result<T, de::Error> {
+    de::from_pairs_with(decode_pairs::<C>(input, &opts)?, opts)
+}
+
+/// Splits `input` into pairs with `C`, separating pairs at `;` as well if
+/// [`de::Options::semicolon_separator`] is set, and keeping `+` as it is if
+/// [`de::Options::literal_plus`] is set.
+pub(crate) fn decode_pairs<'a, C: FormCodec>(
+    input: &'a str,
+    opts: &de::Options,
+) -> Result<de::Pairs<'a>, de::Error> {
+    if opts.literal_plus && input.contains('+') {
+        let escaped = input.replace('+', "%2B");
+        let pairs = split_pairs::<C>(&escaped, opts)?;
+        return Ok(pairs
+            .into_iter()
+            .map(|(k, v)| (Cow::Owned(k.into_owned()), Cow::Owned(v.into_owned())))
+            .collect());
+    }
+
+    split_pairs::<C>(input, opts)
+}
+
+fn split_pairs<'a, C: FormCodec>(
+    input: &'a str,
+    opts: &de::Options,
+) -> Result<de::Pairs<'a>, de::Error> {
+    if !opts.semicolon_separator {
+        return C::decode(input);
+    }
+
+    let mut pairs = Vec::new();
+    for part in input.split(';') {
+        pairs.extend(C::decode(part)?);
+    }
+    Ok(pairs)
+}
+
+/// Splits the urlencoded `input` into pairs like
+/// [`decode_pairs::<UrlEncodedCodec>`](decode_pairs), but from bytes, so that
+/// a body need not be valid UTF-8 as a whole. Invalid UTF-8 in keys and values
+/// is replaced like [`String::from_utf8_lossy`] does.
+pub(crate) fn decode_bytes<'a>(input: &'a [u8], opts: &de::Options) -> de::Pairs<'a> {
+    if opts.literal_plus && memchr::memchr(b'+', input).is_some() {
+        let mut escaped = Vec::with_capacity(input.len());
+        for &b in input {
+            match b {
+                b'+' => escaped.extend_from_slice(b"%2B"),
+                b => escaped.push(b),
+            }
+        }
+        return split_bytes(&escaped, opts)
+            .into_iter()
+            .map(|(k, v)| (Cow::Owned(k.into_owned()), Cow::Owned(v.into_owned())))
+            .collect();
+    }
+
+    split_bytes(input, opts)
+}
+
+fn split_bytes<'a>(input: &'a [u8], opts: &de::Options) -> de::Pairs<'a> {
+    match opts.semicolon_separator {
+        true => input
+            .split(|&b| b == b';')
+            .flat_map(de::parse_pairs)
+            .collect(),
+        false => de::parse_pairs(input),
+    }
+}
+
+/// Serializes `value` into the wire format of `C` with `opts`.
+pub(crate) fn encode<C: FormCodec, T: Serialize + ?Sized>(
+    value: &T,
+    opts: ser::Options,
+) -> Result<String, ser::Error> {
+    C::encode_with(&ser::to_pairs_with(value, opts.clone())?, opts)
+}
+
+/// The Encoded guard: consume and produce form-like bodies in the wire format
+/// of any [`FormCodec`].
+///
+/// `Encoded<T, C>` behaves like [`UrlEncoded<T>`](crate::UrlEncoded), which is
+/// equivalent to `Encoded<T, UrlEncodedCodec>`, with the body parsed and
+/// written by `C`. The same `limits.enumform` data limit applies.
+///
+/// `UrlEncoded` remains a struct of its own, so that it can be built and
+/// matched as `UrlEncoded(value)`, but it decodes and encodes through
+/// [`UrlEncodedCodec`] like `Encoded` does.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Encoded<T, C = UrlEncodedCodec>(pub T, PhantomData<C>);
+
+impl<T, C> Encoded<T, C> {
+    /// Wraps `value` for encoding with the codec `C`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rocket_enumform::{Encoded, UrlEncodedCodec};
+    /// let outer: Encoded<_, UrlEncodedCodec> = Encoded::new("Hello".to_string());
+    /// assert_eq!(outer.into_inner(), "Hello".to_string());
+    /// ```
+    #[inline(always)]
+    pub fn new(value: T) -> Self {
+        Encoded(value, PhantomData)
+    }
+
+    /// Consumes the Encoded wrapper and returns the wrapped item.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<'r, T: Deserialize<'r>, C: FormCodec> Encoded<T, C> {
+    fn from_str(s: &'r str, opts: de::Options) -> Result<Self, Error<'r>> {
+        decode_with::<C, T>(s, opts)
+            .map(Encoded::new)
+            .map_err(|e| Error::Parse(s, e))
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>, C: FormCodec> FromData<'r> for Encoded<T, C> {
+    type Error = Error<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let opts = EnumFormConfig::of(req).options();
+        let limit = crate::form_limit(req);
+        let content_type = C::content_type();
+        crate::parse_body(req, data, &content_type, limit, |s| {
+            Self::from_str(crate::utf8(s)?, opts)
+        })
+        .await
+    }
+}
+
+/// Serializes the wrapped value with the codec `C`. Returns a response with
+/// the codec's Content-Type, with a `charset=utf-8` parameter as configured by
+/// [`EnumFormConfig::response_charset`], and a fixed-size body with the
+/// serialized value.
+/// If serialization fails, an `Err` of `Status::InternalServerError` is
+/// returned.
+///
+/// Like that of [`UrlEncoded`](crate::UrlEncoded), the size of the body is sent
+/// as its Content-Length by Rocket.
+impl<'r, T: Serialize, C: FormCodec> Responder<'r, 'static> for Encoded<T, C> {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let config = EnumFormConfig::of(req);
+        let string = encode::<C, T>(&self.0, config.ser_options()).map_err(|e| {
+            error_!("Encoding failed to serialize: {:?}", e);
+            Status::InternalServerError
+        })?;
+
+        (config.response_content_type(C::content_type()), string).respond_to(req)
+    }
+}
+
+impl<T, C> From<T> for Encoded<T, C> {
+    fn from(value: T) -> Self {
+        Encoded::new(value)
+    }
+}
+
+impl<T, C> Deref for Encoded<T, C> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T, C> DerefMut for Encoded<T, C> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright (c) 2021 Cognite AS
+//! Decompression of form bodies sent with a Content-Encoding.
+
+use std::convert::TryFrom;
+use std::io::{self, Read};
+
+use brotli_decompressor::Decompressor;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use rocket::data::{ByteUnit, Capped, Data, N};
+use rocket::request::Request;
+
+use crate::{read_bytes, Error};
+
+/// Reads a body compressed with `encoding`, `gzip`, `deflate` or `br`, and
+/// decompresses it.
+///
+/// `limit` applies to the compressed as well as the decompressed body, so that
+/// a small body cannot expand without bounds. Like reading an uncompressed
+/// body, the result is truncated and incomplete if the limit is exceeded.
+pub(crate) async fn decompress<'r>(
+    req: &'r Request<'_>,
+    encoding: &'r str,
+    data: Data<'r>,
+    limit: ByteUnit,
+) -> Result<Capped<Vec<u8>>, Error<'r>> {
+    let decoder: fn(&[u8]) -> Box<dyn Read + '_> = match encoding {
+        e if e.eq_ignore_ascii_case("gzip") || e.eq_ignore_ascii_case("x-gzip") => {
+            |bytes| Box::new(GzDecoder::new(bytes))
+        }
+        e if e.eq_ignore_ascii_case("deflate") => |bytes| Box::new(ZlibDecoder::new(bytes)),
+        e if e.eq_ignore_ascii_case("br") => |bytes| Box::new(Decompressor::new(bytes, 4096)),
+        _ => return Err(Error::ContentEncoding(encoding)),
+    };
+
+    let compressed = read_bytes(req, data, limit).await.map_err(Error::Io)?;
+    let max = limit.as_u64();
+    let mut bytes = Vec::new();
+    let mut decoder = decoder(&compressed).take(max.saturating_add(1));
+    // A truncated stream fails to decode, but is reported as incomplete.
+    if let Err(e) = decoder.read_to_end(&mut bytes) {
+        if compressed.is_complete() {
+            return Err(Error::Io(io::Error::new(io::ErrorKind::InvalidData, e)));
+        }
+    }
+
+    let complete = compressed.is_complete() && bytes.len() as u64 <= max;
+    bytes.truncate(usize::try_from(max).unwrap_or(usize::MAX));
+    let written = bytes.len() as u64;
+    Ok(Capped::new(bytes, N { written, complete }))
+}
@@ -0,0 +1,523 @@
+// Copyright (c) 2021 Cognite AS
+//! Application-wide configuration of the guards in this crate.
+
+use std::borrow::Cow;
+
+use rocket::fairing::{AdHoc, Fairing};
+use rocket::http::{ContentType, Status};
+use rocket::request::Request;
+use rocket::{error, info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::render::Renderer;
+use crate::{de, ser, Error, ErrorRenderer, KeyCase};
+
+/// The `enumform` configuration section.
+///
+/// Read from Rocket's figment by [`EnumForm::fairing()`], so that it can be
+/// set per profile in `Rocket.toml`:
+///
+/// ```toml
+/// [default.enumform]
+/// empty_as_none = true
+///
+/// [release.enumform]
+/// deny_unknown_fields = true
+/// ```
+///
+/// Without the fairing, or without the section, the defaults apply. The
+/// configuration applies to [`UrlEncoded`](crate::UrlEncoded),
+/// [`Encoded`](crate::Encoded), [`QueryEncoded`](crate::QueryEncoded),
+/// [`ContextualUrlEncoded`](crate::ContextualUrlEncoded) and
+/// [`MultipartEncoded`](crate::multipart::MultipartEncoded), while
+/// [`LenientUrlEncoded`](crate::LenientUrlEncoded) and
+/// [`StrictUrlEncoded`](crate::StrictUrlEncoded) always use their own
+/// `empty_as_none` and `deny_unknown_fields`.
+///
+/// Options are added in minor releases, so a configuration managed directly
+/// is built from [`EnumFormConfig::default()`] and then has its fields set.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct EnumFormConfig {
+    /// Deserialize empty values of optional fields as `None`, see
+    /// [`de::Options::empty_as_none`]. Defaults to `false`.
+    pub empty_as_none: bool,
+
+    /// Fail on keys which are not fields of the target struct, see
+    /// [`de::Options::deny_unknown_fields`]. Defaults to `false`.
+    pub deny_unknown_fields: bool,
+
+    /// Forward to the next matching route instead of failing when the data
+    /// does not parse, so that a lower-ranked route can handle it. Defaults
+    /// to `false`.
+    ///
+    /// Data guards can only forward a body which they did not consume: bodies
+    /// of less than 512 bytes, the size of Rocket's peek buffer, are
+    /// forwarded intact, while larger ones must be read to be parsed and
+    /// still fail, with a warning. [`QueryEncoded`](crate::QueryEncoded)
+    /// always forwards. The status of the forward is the status the guard
+    /// would have failed with. Errors reading the data are never forwarded.
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// # use serde::Deserialize;
+    /// use rocket::http::{ContentType, Status};
+    /// use rocket::local::blocking::Client;
+    /// use rocket_enumform::{EnumFormConfig, UrlEncoded};
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Order {
+    ///     quantity: u32,
+    /// }
+    ///
+    /// #[post("/order", data = "<order>")]
+    /// fn order(order: UrlEncoded<Order>) -> String {
+    ///     order.quantity.to_string()
+    /// }
+    ///
+    /// #[post("/order", data = "<body>", rank = 2)]
+    /// fn fallback(body: String) -> String {
+    ///     format!("{} bytes", body.len())
+    /// }
+    ///
+    /// let mut config = EnumFormConfig::default();
+    /// config.forward_on_error = true;
+    /// let rocket = rocket::build().manage(config).mount("/", routes![order, fallback]);
+    /// let client = Client::untracked(rocket).unwrap();
+    /// let post = |body: String| client.post("/order").header(ContentType::Form).body(body).dispatch();
+    ///
+    /// let response = post("quantity=many".into());
+    /// assert_eq!(response.into_string().unwrap(), "13 bytes");
+    ///
+    /// let response = post(format!("quantity=many&note={}", "x".repeat(512)));
+    /// assert_eq!(response.status(), Status::UnprocessableEntity);
+    /// ```
+    pub forward_on_error: bool,
+
+    /// Fail data guards with `Status::UnsupportedMediaType` unless the
+    /// request's Content-Type is the one they parse, such as
+    /// `application/x-www-form-urlencoded`. Parameters like `charset` are
+    /// allowed. Use this where routes don't set `format`. Defaults to `false`.
+    pub strict_content_type: bool,
+
+    /// How to handle keys which are submitted more than once, see
+    /// [`de::Options::duplicates`]: one of `"first_wins"`, `"last_wins"`,
+    /// `"error"` or `"collect"`. Defaults to leaving it to the target type.
+    pub duplicates: Option<de::Duplicates>,
+
+    /// The maximum number of pairs, see [`de::Options::max_pairs`]. Defaults
+    /// to no limit.
+    pub max_pairs: Option<usize>,
+
+    /// The maximum length of a key in bytes, see
+    /// [`de::Options::max_key_len`]. Defaults to no limit.
+    pub max_key_len: Option<usize>,
+
+    /// The maximum length of a value in bytes, see
+    /// [`de::Options::max_value_len`]. Defaults to no limit.
+    pub max_value_len: Option<usize>,
+
+    /// Also separate pairs at `;`, see
+    /// [`de::Options::semicolon_separator`]. Defaults to `false`.
+    pub semicolon_separator: bool,
+
+    /// Decode `+` as a literal plus sign instead of a space, see
+    /// [`de::Options::literal_plus`]. Defaults to `false`.
+    pub literal_plus: bool,
+
+    /// Encode spaces in responses as `%20` instead of `+`, see
+    /// [`ser::Options::percent_encode_space`]. Defaults to `false`.
+    pub percent_encode_space: bool,
+
+    /// Write `None` values in responses as `key=` instead of omitting them,
+    /// see [`ser::Options::none_as_empty`]. Defaults to `false`.
+    pub none_as_empty: bool,
+
+    /// Add `charset=utf-8` to the Content-Type of responses, unless it has a
+    /// `charset` already, so that clients decode non-ASCII percent-escapes as
+    /// UTF-8. Defaults to `true`.
+    pub response_charset: bool,
+
+    /// Log ignored keys which are not fields of the target at this level, see
+    /// [`de::Options::log_unknown_fields`]: one of `"error"`, `"warn"`,
+    /// `"info"`, `"debug"` or `"trace"`. Defaults to not logging them.
+    pub log_unknown_fields: Option<log::Level>,
+
+    /// The case of keys on the wire, see [`KeyCase`]: one of `"camel"`,
+    /// `"kebab"` or `"snake"`. Applies to parsing and to responses. Defaults
+    /// to using keys as they are.
+    pub key_case: Option<KeyCase>,
+
+    /// A prefix stripped from incoming keys and prepended to serialized keys,
+    /// see [`de::Options::key_prefix`] and [`ser::Options::key_prefix`].
+    /// Defaults to none.
+    pub key_prefix: Option<Cow<'static, str>>,
+
+    /// Nest keys at `.`, see [`de::Options::dot_notation`]. Defaults to
+    /// `false`.
+    pub dot_notation: bool,
+
+    /// What the guards keep of data which fails to parse, see [`RawData`]:
+    /// one of `"keep"`, `"redact_values"` or `"omit"`. Defaults to keeping it.
+    pub raw_data: RawData,
+
+    /// The level the guards log data which fails to parse at, or `"off"` to
+    /// not log it: one of `"off"`, `"error"`, `"warn"`, `"info"`, `"debug"`
+    /// or `"trace"`. Defaults to `"error"`. Rocket logs the failure of the
+    /// guard regardless, at its own level.
+    pub log_level: log::LevelFilter,
+
+    /// The target the guards log data which fails to parse with, such as
+    /// `"forms"`, to filter it in the logger. Defaults to this crate's, which
+    /// Rocket's logger indents below the request.
+    pub log_target: Option<String>,
+
+    /// Truncate the raw data the guards keep of data which fails to parse to
+    /// this many bytes, at a character boundary, after applying
+    /// [`raw_data`](EnumFormConfig::raw_data). Defaults to no limit.
+    pub max_raw_data_len: Option<usize>,
+
+    /// The names of the hidden fields which [`Honeypot`](crate::Honeypot)
+    /// requires to be submitted empty. Defaults to none, which leaves a
+    /// single field, `website`.
+    pub honeypot_fields: Vec<String>,
+
+    /// What [`Honeypot`](crate::Honeypot) does with submissions which fill
+    /// a honeypot field, see [`HoneypotAction`]: one of `"reject"` or
+    /// `"drop"`. Defaults to rejecting them.
+    pub honeypot_action: HoneypotAction,
+
+    /// The number of seconds the timestamp of a signed webhook may differ
+    /// from the current time, see
+    /// [`SignatureVerifier::timestamp`](crate::verify::SignatureVerifier::timestamp),
+    /// with the `verify` feature. Defaults to 300, five minutes.
+    pub max_clock_skew: u64,
+
+    /// Warn at launch if no catcher handles the statuses
+    /// [`UrlEncoded`](crate::UrlEncoded) fails with, see its
+    /// [`Sentinel`](rocket::Sentinel) implementation. Defaults to `false`.
+    pub warn_missing_catchers: bool,
+
+    /// Abort launch if no catcher handles the statuses
+    /// [`UrlEncoded`](crate::UrlEncoded) fails with, see its
+    /// [`Sentinel`](rocket::Sentinel) implementation. Defaults to `false`.
+    pub require_catchers: bool,
+
+    /// The statuses the guards fail with, see [`ErrorStatusPolicy`]: one of
+    /// `"split"` or `"bad_request"`. Defaults to splitting them.
+    pub error_status_policy: ErrorStatusPolicy,
+}
+
+/// What the guards keep of data which fails to parse, as the raw data of
+/// [`Error::Parse`](crate::Error::Parse), which Rocket logs, and what they log
+/// of the error.
+///
+/// Data the guards did not read themselves, such as the value of a
+/// [`FromFormField`](rocket::form::FromFormField), is kept as it is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RawData {
+    /// The data as received, and the error in full. The values of
+    /// [`Secret`](crate::Secret) fields are still redacted where the error
+    /// is shown.
+    #[default]
+    Keep,
+
+    /// The keys, with all values replaced by `[redacted]`, and the error
+    /// without its message, which may quote a value. The message is still
+    /// shown where the error is displayed.
+    RedactValues,
+
+    /// No data, and the error without its message.
+    Omit,
+}
+
+/// What [`Honeypot`](crate::Honeypot) does with submissions which fill a
+/// honeypot field, or leave it out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HoneypotAction {
+    /// Fail with `Status::UnprocessableEntity`.
+    #[default]
+    Reject,
+
+    /// Succeed without the value, so that the handler can respond as if it
+    /// had accepted it, giving bots no hint of the trap.
+    Drop,
+}
+
+/// Which statuses the guards fail with, for API guidelines which prescribe
+/// them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorStatusPolicy {
+    /// The status of each error, as [`Error::status()`] documents, such as
+    /// `Status::PayloadTooLarge` or `Status::UnprocessableEntity`.
+    #[default]
+    Split,
+
+    /// `Status::BadRequest` for every client error. Server errors are kept.
+    BadRequest,
+}
+
+impl ErrorStatusPolicy {
+    /// The status to fail with for an error of status `status`.
+    ///
+    /// ```rust
+    /// use rocket::http::Status;
+    /// use rocket_enumform::ErrorStatusPolicy;
+    ///
+    /// let policy = ErrorStatusPolicy::BadRequest;
+    /// assert_eq!(policy.status(Status::UnprocessableEntity), Status::BadRequest);
+    /// assert_eq!(policy.status(Status::InternalServerError), Status::InternalServerError);
+    /// ```
+    pub fn status(self, status: Status) -> Status {
+        match self {
+            Self::BadRequest if status.class().is_client_error() => Status::BadRequest,
+            Self::Split | Self::BadRequest => status,
+        }
+    }
+}
+
+impl EnumFormConfig {
+    /// The configuration used when none is managed.
+    pub const DEFAULT: EnumFormConfig = EnumFormConfig {
+        empty_as_none: false,
+        deny_unknown_fields: false,
+        forward_on_error: false,
+        strict_content_type: false,
+        duplicates: None,
+        max_pairs: None,
+        max_key_len: None,
+        max_value_len: None,
+        semicolon_separator: false,
+        literal_plus: false,
+        percent_encode_space: false,
+        none_as_empty: false,
+        response_charset: true,
+        log_unknown_fields: None,
+        key_case: None,
+        key_prefix: None,
+        dot_notation: false,
+        raw_data: RawData::Keep,
+        log_level: log::LevelFilter::Error,
+        log_target: None,
+        max_raw_data_len: None,
+        honeypot_fields: Vec::new(),
+        honeypot_action: HoneypotAction::Reject,
+        max_clock_skew: 300,
+        warn_missing_catchers: false,
+        require_catchers: false,
+        error_status_policy: ErrorStatusPolicy::Split,
+    };
+
+    /// The configuration managed by the application of `req`, or the default.
+    pub fn of<'r>(req: &'r Request<'_>) -> &'r EnumFormConfig {
+        // A static, as the default owns a `Vec` and is not promoted to one.
+        static DEFAULT: EnumFormConfig = EnumFormConfig::DEFAULT;
+        req.rocket().state::<EnumFormConfig>().unwrap_or(&DEFAULT)
+    }
+
+    /// The deserialization options selected by this configuration.
+    pub fn options(&self) -> de::Options {
+        de::Options {
+            empty_as_none: self.empty_as_none,
+            deny_unknown_fields: self.deny_unknown_fields,
+            duplicates: self.duplicates,
+            max_pairs: self.max_pairs,
+            max_key_len: self.max_key_len,
+            max_value_len: self.max_value_len,
+            semicolon_separator: self.semicolon_separator,
+            literal_plus: self.literal_plus,
+            log_unknown_fields: self.log_unknown_fields,
+            key_case: self.key_case,
+            key_prefix: self.key_prefix.clone(),
+            dot_notation: self.dot_notation,
+        }
+    }
+
+    /// The serialization options selected by this configuration.
+    pub fn ser_options(&self) -> ser::Options {
+        ser::Options {
+            percent_encode_space: self.percent_encode_space,
+            key_case: self.key_case,
+            key_prefix: self.key_prefix.clone(),
+            canonical: false,
+            none_as_empty: self.none_as_empty,
+            encode_set: ser::EncodeSet::Form,
+        }
+    }
+
+    /// `content_type`, with `charset=utf-8` added if
+    /// [`response_charset`](EnumFormConfig::response_charset) is set.
+    pub(crate) fn response_content_type(&self, content_type: ContentType) -> ContentType {
+        if !self.response_charset || content_type.param("charset").is_some() {
+            return content_type;
+        }
+        // Built anew, as known types like `ContentType::Form` are displayed
+        // without parameters added to them.
+        let params: Vec<_> = content_type
+            .params()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .chain(Some(("charset".to_string(), "utf-8".to_string())))
+            .collect();
+        ContentType::new(
+            content_type.top().to_string(),
+            content_type.sub().to_string(),
+        )
+        .with_params(params)
+    }
+}
+
+impl Default for EnumFormConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Attaches the `enumform` configuration to an application.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket_enumform::EnumForm;
+///
+/// #[launch]
+/// fn rocket() -> _ {
+///     rocket::build().attach(EnumForm::fairing())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct EnumForm;
+
+impl EnumForm {
+    /// Returns a fairing which reads [`EnumFormConfig`] from the `enumform`
+    /// key of the configuration and manages it. Ignition fails if the section
+    /// is present but invalid.
+    ///
+    /// A configuration the application already manages takes precedence, and
+    /// the section is then ignored.
+    ///
+    /// ```rust
+    /// use rocket::local::blocking::Client;
+    /// use rocket_enumform::{EnumForm, EnumFormConfig};
+    ///
+    /// let mut config = EnumFormConfig::default();
+    /// config.empty_as_none = true;
+    /// let rocket = rocket::build().manage(config).attach(EnumForm::fairing());
+    /// let client = Client::untracked(rocket).unwrap();
+    /// let config = client.rocket().state::<EnumFormConfig>().unwrap();
+    /// assert!(config.empty_as_none);
+    /// ```
+    pub fn fairing() -> impl Fairing {
+        AdHoc::try_on_ignite("EnumForm Config", |rocket| async move {
+            let figment = rocket.figment();
+            let present = figment.find_value("enumform").is_ok();
+            if rocket.state::<EnumFormConfig>().is_some() {
+                if present {
+                    warn!(
+                        "enumform: configuration already managed, ignoring the `enumform` section"
+                    );
+                }
+                return Ok(rocket);
+            }
+            if !present {
+                return Ok(rocket.manage(EnumFormConfig::default()));
+            }
+
+            match figment.extract_inner::<EnumFormConfig>("enumform") {
+                Ok(config) => {
+                    info!("enumform: {:?}", config);
+                    Ok(rocket.manage(config))
+                }
+                Err(e) => {
+                    error!("invalid enumform configuration: {}", e);
+                    Err(rocket)
+                }
+            }
+        })
+    }
+
+    /// Returns a fairing which has the guards of this crate call `hook` with
+    /// their errors, for custom handling such as metrics, after they apply
+    /// [`EnumFormConfig::raw_data`] and log them, including the errors of
+    /// guards which forward. Errors of
+    /// [`MultipartEncoded`](crate::multipart::MultipartEncoded) are passed
+    /// without raw data. Only one hook can be attached: ignition fails if
+    /// another one already is.
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket_enumform::{EnumForm, Error};
+    ///
+    /// #[launch]
+    /// fn rocket() -> _ {
+    ///     rocket::build().attach(EnumForm::on_error(|req, e| {
+    ///         if let Error::Parse(_, e) = e {
+    ///             println!("{} {}: {}", req.method(), req.uri(), e);
+    ///         }
+    ///     }))
+    /// }
+    /// ```
+    pub fn on_error<F>(hook: F) -> impl Fairing
+    where
+        F: Fn(&Request<'_>, &Error<'_>) + Send + Sync + 'static,
+    {
+        let hook = ErrorHook(Box::new(hook));
+        AdHoc::try_on_ignite("EnumForm Error Hook", |rocket| async move {
+            if rocket.state::<ErrorHook>().is_some() {
+                error!("an enumform error hook is already attached");
+                return Err(rocket);
+            }
+            Ok(rocket.manage(hook))
+        })
+    }
+
+    /// Returns a fairing which has [`Error`] respond with the response of
+    /// `renderer`, such as an HTML partial, instead of problem details. Only
+    /// one renderer can be attached: ignition fails if another one already
+    /// is.
+    ///
+    /// See [`ErrorRenderer`] for an example.
+    ///
+    /// ```rust
+    /// use rocket::http::Status;
+    /// use rocket::local::blocking::Client;
+    /// use rocket::response::Responder;
+    /// use rocket_enumform::{EnumForm, Error};
+    ///
+    /// let renderer = |req: &rocket::Request<'_>, _: &Error<'_>, status: Status| {
+    ///     status.respond_to(req)
+    /// };
+    /// let rocket = rocket::build()
+    ///     .attach(EnumForm::renderer(renderer))
+    ///     .attach(EnumForm::renderer(renderer));
+    /// let e = Client::untracked(rocket).unwrap_err();
+    /// assert!(matches!(e.kind(), rocket::error::ErrorKind::FailedFairings(_)));
+    /// ```
+    pub fn renderer<R: ErrorRenderer>(renderer: R) -> impl Fairing {
+        let renderer = Renderer(Box::new(renderer));
+        AdHoc::try_on_ignite("EnumForm Error Renderer", |rocket| async move {
+            if rocket.state::<Renderer>().is_some() {
+                error!("an enumform error renderer is already attached");
+                return Err(rocket);
+            }
+            Ok(rocket.manage(renderer))
+        })
+    }
+}
+
+/// A hook called with the errors of the guards of a request.
+type Hook = dyn Fn(&Request<'_>, &Error<'_>) + Send + Sync;
+
+/// The hook attached with [`EnumForm::on_error()`].
+pub(crate) struct ErrorHook(Box<Hook>);
+
+impl ErrorHook {
+    /// Calls the hook of the application of `req`, if any, with `e`.
+    pub(crate) fn call(req: &Request<'_>, e: &Error<'_>) {
+        if let Some(ErrorHook(hook)) = req.rocket().state::<ErrorHook>() {
+            hook(req, e);
+        }
+    }
+}
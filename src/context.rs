@@ -0,0 +1,266 @@
+// Copyright (c) 2021 Cognite AS
+//! A guard which keeps the submitted form around for re-rendering.
+
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+
+use rocket::data::{Data, FromData, Outcome};
+use rocket::http::{ContentType, Status};
+use rocket::request::Request;
+use serde::{Deserialize, Serialize};
+
+use crate::{de, EnumFormConfig, Error};
+
+/// The ContextualUrlEncoded guard: consume x-www-form-urlencoded requests
+/// without failing, keeping the submitted values and errors.
+///
+/// Where [`UrlEncoded`](crate::UrlEncoded) fails the request when the body
+/// does not deserialize as `T`, `ContextualUrlEncoded` always succeeds: `value`
+/// is `Some` if deserialization succeeded, and `context` holds the raw
+/// submitted values and any errors, keyed by field name, so that the form can
+/// be rendered again with the user's input.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket_enumform::ContextualUrlEncoded;
+///
+/// #[derive(Debug, Deserialize)]
+/// #[serde(tag = "type")]
+/// enum Body {
+///     #[serde(rename = "variant_one")]
+///     VariantOne { content_one: String },
+///     #[serde(rename = "variant_two")]
+///     VariantTwo { content_two: usize },
+/// }
+///
+/// #[post("/form", data = "<form>")]
+/// fn submit(form: ContextualUrlEncoded<Body>) -> (rocket::http::Status, String) {
+///     match form.value {
+///         Some(body) => (form.context.status(), format!("{:?}", body)),
+///         None => {
+///             let value = form.context.field_value("content_two").unwrap_or("");
+///             (form.context.status(), format!("please correct {:?}", value))
+///         }
+///     }
+/// }
+/// ```
+///
+/// The same `limits.enumform` data limit as for `UrlEncoded` applies; a body
+/// exceeding it results in an empty context with a form-level error and
+/// `Status::PayloadTooLarge`.
+///
+/// The values of [`Secret`](crate::Secret) fields, such as passwords, are
+/// left out of the context so that they are not rendered again, wherever
+/// they are submitted. Like for [`de::Error::redact()`], only the Secret
+/// fields deserialized are known, which excludes those of flattened structs
+/// and internally tagged enums, and those after a field which failed.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket::http::ContentType;
+/// use rocket::local::blocking::Client;
+/// use rocket_enumform::{ContextualUrlEncoded, Secret};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Login {
+///     user: String,
+///     password: Secret<String>,
+///     remember: bool,
+/// }
+///
+/// #[post("/login", data = "<login>")]
+/// fn login(login: ContextualUrlEncoded<Login>) -> String {
+///     let context = login.context;
+///     format!("{:?} {:?}", context.field_value("user"), context.field_value("password"))
+/// }
+///
+/// let client = Client::untracked(rocket::build().mount("/", routes![login])).unwrap();
+/// let body = "user=jane&password=hunter2&remember=maybe";
+/// let response = client.post("/login").header(ContentType::Form).body(body).dispatch();
+/// assert_eq!(response.into_string().unwrap(), r#"Some("jane") None"#);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextualUrlEncoded<T> {
+    /// The deserialized value, if deserialization succeeded.
+    pub value: Option<T>,
+
+    /// The submitted values and errors.
+    pub context: FormContext,
+}
+
+impl<T> ContextualUrlEncoded<T> {
+    /// Consumes the ContextualUrlEncoded wrapper and returns the value and the
+    /// context.
+    #[inline(always)]
+    pub fn into_inner(self) -> (Option<T>, FormContext) {
+        (self.value, self.context)
+    }
+}
+
+/// The submitted values of a form and the errors deserializing it.
+///
+/// `FormContext` serializes as `{ "values": { name: [value, ...] }, "errors":
+/// [{ "name": name, "message": message }, ...] }` for use in templates, and
+/// by field with the `templates` feature, see `templates::StickyForm`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FormContext {
+    values: BTreeMap<String, Vec<String>>,
+    errors: Vec<FieldError>,
+    #[serde(skip)]
+    status: Status,
+}
+
+/// An error deserializing a form.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FieldError {
+    /// The name of the field the error refers to, or `None` for errors
+    /// concerning the form as a whole.
+    pub name: Option<String>,
+
+    /// A human-readable description of the error.
+    pub message: String,
+}
+
+impl FieldError {
+    /// The errors for `e`, one for each field if it lists unknown fields.
+    fn from_de(e: &de::Error) -> Vec<Self> {
+        if e.unknown_fields().is_empty() {
+            return vec![FieldError {
+                name: e.path().map(String::from),
+                message: e.to_string(),
+            }];
+        }
+
+        e.unknown_fields()
+            .iter()
+            .map(|key| FieldError {
+                name: Some(key.clone()),
+                message: format!("unknown field `{}`", key),
+            })
+            .collect()
+    }
+
+    /// The errors for `e`, by field for parse and validation errors.
+    pub(crate) fn from_error(e: &Error<'_>) -> Vec<Self> {
+        match e {
+            Error::Parse(_, e) => FieldError::from_de(e),
+            #[cfg(feature = "validator")]
+            Error::Validation(errors) => crate::validated::field_errors(errors),
+            e => vec![FieldError {
+                name: None,
+                message: e.to_string(),
+            }],
+        }
+    }
+}
+
+impl FormContext {
+    fn from_error(req: &Request<'_>, e: Error<'_>) -> Self {
+        let status = crate::error_status(req, e.status());
+        FormContext {
+            values: BTreeMap::new(),
+            errors: FieldError::from_error(&e),
+            status,
+        }
+    }
+
+    /// The first value submitted for the field `name`, if any.
+    pub fn field_value(&self, name: &str) -> Option<&str> {
+        self.field_values(name).next()
+    }
+
+    /// All values submitted for the field `name`, in submission order.
+    pub fn field_values<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a str> + 'a {
+        self.values
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    /// All submitted values, by field name, in submission order.
+    pub fn values(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.values
+            .iter()
+            .map(|(name, values)| (name.as_str(), values.as_slice()))
+    }
+
+    /// The errors for the field `name`.
+    pub fn field_errors<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a FieldError> + 'a {
+        self.errors
+            .iter()
+            .filter(move |e| e.name.as_deref() == Some(name))
+    }
+
+    /// All errors, including those without a field name.
+    pub fn errors(&self) -> impl Iterator<Item = &FieldError> {
+        self.errors.iter()
+    }
+
+    /// `Status::Ok` if the form deserialized, otherwise the status
+    /// [`UrlEncoded`](crate::UrlEncoded) would have failed with.
+    pub fn status(&self) -> Status {
+        self.status
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>> FromData<'r> for ContextualUrlEncoded<T> {
+    type Error = Infallible;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let body = match crate::check_content_type(req, &ContentType::Form) {
+            Ok(()) => crate::read_body(req, data).await,
+            Err(e) => Err(e),
+        };
+        let opts = EnumFormConfig::of(req).options();
+        let pairs = match body {
+            Ok(s) => Ok(crate::cache::pairs(req, s, &opts)),
+            Err(e) => Err(e),
+        };
+        let pairs = match pairs {
+            Ok(pairs) => pairs,
+            Err(e) => {
+                let context = FormContext::from_error(req, e);
+                return Outcome::Success(ContextualUrlEncoded {
+                    value: None,
+                    context,
+                });
+            }
+        };
+
+        let mut values: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (key, value) in &pairs {
+            values
+                .entry(key.to_string())
+                .or_default()
+                .push(value.to_string());
+        }
+
+        let (value, secrets) = de::from_pairs_with_secrets(pairs, opts);
+        for values in values.values_mut() {
+            values.retain(|value| value.is_empty() || !secrets.contains(value));
+        }
+        values.retain(|_, values| !values.is_empty());
+
+        let (value, errors, status) = match value {
+            Ok(value) => (Some(value), Vec::new(), Status::Ok),
+            Err(e) => {
+                let errors = FieldError::from_de(&e);
+                (
+                    None,
+                    errors,
+                    crate::error_status(req, crate::parse_status(&e)),
+                )
+            }
+        };
+        let context = FormContext {
+            values,
+            errors,
+            status,
+        };
+        Outcome::Success(ContextualUrlEncoded { value, context })
+    }
+}
@@ -0,0 +1,241 @@
+// Copyright (c) 2021 Cognite AS
+//! Protection of forms against cross-site request forgery.
+//!
+//! This module is only available with the `csrf` feature enabled.
+
+use std::ops::{Deref, DerefMut};
+
+use rocket::data::{Data, FromData, Outcome};
+use rocket::error;
+use rocket::fairing::{AdHoc, Fairing};
+use rocket::http::{ContentType, Cookie, Status};
+use rocket::request::{self, FromRequest, Request};
+use serde::Deserialize;
+
+use crate::{codec, de, EnumFormConfig, Error, UrlEncoded};
+
+/// The CSRF token of the client, kept in the [`CsrfToken::COOKIE`] cookie by
+/// [`CsrfToken::fairing()`], to embed into forms as a hidden
+/// [`CsrfToken::FIELD`] for [`CsrfProtected`] to verify.
+///
+/// The fairing issues a random token to clients without one, which their
+/// first response sets in an `HttpOnly` cookie. As a request guard, the token
+/// is that of the cookie, or the one issued for the request, and the guard
+/// fails with `Status::InternalServerError` without the fairing.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket_enumform::CsrfToken;
+///
+/// #[get("/contact")]
+/// fn contact(token: CsrfToken) -> String {
+///     format!(r#"<form method="post">{}<textarea name="message"></textarea></form>"#, token.hidden_field())
+/// }
+///
+/// #[launch]
+/// fn rocket() -> _ {
+///     rocket::build()
+///         .attach(CsrfToken::fairing())
+///         .mount("/", routes![contact])
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsrfToken(String);
+
+impl CsrfToken {
+    /// The name of the cookie holding the token.
+    pub const COOKIE: &'static str = "csrf_token";
+
+    /// The name of the hidden field [`CsrfProtected`] reads the token from.
+    pub const FIELD: &'static str = "_csrf";
+
+    /// Returns a fairing which issues a token to requests without a valid
+    /// one.
+    pub fn fairing() -> impl Fairing {
+        AdHoc::on_request("CSRF Token", |req, _| {
+            Box::pin(async move {
+                let jar = req.cookies();
+                if !jar
+                    .get(CsrfToken::COOKIE)
+                    .is_some_and(|c| is_token(c.value()))
+                {
+                    let token: String = rand::random::<[u8; 32]>()
+                        .iter()
+                        .map(|byte| format!("{:02x}", byte))
+                        .collect();
+                    jar.add(
+                        Cookie::build((CsrfToken::COOKIE, token))
+                            .http_only(true)
+                            .build(),
+                    );
+                }
+            })
+        })
+    }
+
+    /// The token, in hexadecimal.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// A hidden `input` element carrying the token, to embed into forms.
+    pub fn hidden_field(&self) -> String {
+        format!(
+            r#"<input type="hidden" name="{}" value="{}">"#,
+            CsrfToken::FIELD,
+            self.0
+        )
+    }
+}
+
+/// Whether `value` is a token as issued by the fairing.
+fn is_token(value: &str) -> bool {
+    value.len() == 64 && value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CsrfToken {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, ()> {
+        match req.cookies().get_pending(CsrfToken::COOKIE) {
+            Some(cookie) if is_token(cookie.value()) => {
+                request::Outcome::Success(CsrfToken(cookie.value().to_string()))
+            }
+            _ => {
+                error!("CsrfToken requires attaching `CsrfToken::fairing()`.");
+                request::Outcome::Error((Status::InternalServerError, ()))
+            }
+        }
+    }
+}
+
+/// The CsrfProtected guard: wraps a [`UrlEncoded`] guard, handing over its
+/// value only if the [`CsrfToken::FIELD`] of the body matches the
+/// [`CsrfToken`] cookie of the request.
+///
+/// The field is removed before the other fields are deserialized, and the
+/// guard fails with [`Error::Csrf`], `Status::Forbidden`, if it is missing,
+/// submitted more than once, or differs from the cookie. Tokens are compared
+/// in constant time.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket::http::{ContentType, Status};
+/// use rocket::local::blocking::Client;
+/// use rocket_enumform::{CsrfProtected, CsrfToken, UrlEncoded};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Message {
+///     message: String,
+/// }
+///
+/// #[get("/contact")]
+/// fn form(token: CsrfToken) -> String {
+///     token.as_str().to_string()
+/// }
+///
+/// #[post("/contact", data = "<form>")]
+/// fn contact(form: CsrfProtected<UrlEncoded<Message>>) -> String {
+///     form.into_inner().into_inner().message
+/// }
+///
+/// let rocket = rocket::build()
+///     .attach(CsrfToken::fairing())
+///     .mount("/", routes![form, contact]);
+/// let client = Client::tracked(rocket).unwrap();
+/// let token = client.get("/contact").dispatch().into_string().unwrap();
+///
+/// let post = |body: String| {
+///     let request = client.post("/contact").header(ContentType::Form);
+///     request.body(body).dispatch()
+/// };
+/// let response = post(format!("_csrf={}&message=hello", token));
+/// assert_eq!(response.into_string().unwrap(), "hello");
+/// assert_eq!(post("message=hello".into()).status(), Status::Forbidden);
+/// assert_eq!(post(format!("_csrf={}&message=hello", "0".repeat(64))).status(), Status::Forbidden);
+/// ```
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CsrfProtected<G>(pub G);
+
+impl<G> CsrfProtected<G> {
+    /// Consumes the CsrfProtected wrapper and returns the wrapped item.
+    #[inline(always)]
+    pub fn into_inner(self) -> G {
+        self.0
+    }
+}
+
+impl<G> From<G> for CsrfProtected<G> {
+    fn from(value: G) -> Self {
+        CsrfProtected(value)
+    }
+}
+
+impl<G> Deref for CsrfProtected<G> {
+    type Target = G;
+
+    #[inline(always)]
+    fn deref(&self) -> &G {
+        &self.0
+    }
+}
+
+impl<G> DerefMut for CsrfProtected<G> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut G {
+        &mut self.0
+    }
+}
+
+/// Removes the token from `pairs`, returning whether it was submitted once
+/// and equals `expected`.
+fn take_token(pairs: &mut de::Pairs<'_>, expected: Option<&str>) -> bool {
+    let mut submitted = Vec::new();
+    pairs.retain(|(key, value)| match key == CsrfToken::FIELD {
+        true => {
+            submitted.push(value.clone());
+            false
+        }
+        false => true,
+    });
+    match (expected, &submitted[..]) {
+        (Some(expected), [submitted]) => {
+            // Compared in constant time, so as not to reveal the token.
+            submitted.len() == expected.len()
+                && submitted
+                    .bytes()
+                    .zip(expected.bytes())
+                    .fold(0, |diff, (a, b)| diff | (a ^ b))
+                    == 0
+        }
+        _ => false,
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>> FromData<'r> for CsrfProtected<UrlEncoded<T>> {
+    type Error = Error<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let expected = req
+            .cookies()
+            .get(CsrfToken::COOKIE)
+            .map(|cookie| cookie.value())
+            .filter(|value| is_token(value));
+        let opts = EnumFormConfig::of(req).options();
+        let limit = crate::form_limit(req);
+        crate::parse_body(req, data, &ContentType::Form, limit, |body| {
+            let mut pairs = codec::decode_bytes(body, &opts);
+            if !take_token(&mut pairs, expected) {
+                return Err(Error::Csrf);
+            }
+            de::from_pairs_with(pairs, opts)
+                .map(|value| CsrfProtected(UrlEncoded(value)))
+                .map_err(|e| Error::Parse(crate::raw_text(body), e))
+        })
+        .await
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright (c) 2021 Cognite AS
+//! The date and time fields shared by the `chrono` and `time` modules.
+
+/// Defines a form field wrapping the date or time type `$inner`, read and
+/// written in the format of a `Format` type defaulting to `$default`.
+///
+/// `$parse(text, format)` parses a value and `$format(value, format)` writes
+/// one, in the syntax of the `Format` trait in scope.
+macro_rules! form_date {
+    (
+        $(#[$attr:meta])*
+        $name:ident<$default:ident>($inner:ty),
+        $what:literal,
+        $parse:path,
+        $format:path
+    ) => {
+        $(#[$attr])*
+        #[repr(transparent)]
+        pub struct $name<F = $default>(pub $inner, std::marker::PhantomData<F>);
+
+        impl<F> $name<F> {
+            #[doc = concat!("Wraps ", $what, ".")]
+            #[inline(always)]
+            pub fn new(value: $inner) -> Self {
+                $name(value, std::marker::PhantomData)
+            }
+
+            #[doc = concat!("Consumes the ", stringify!($name), " wrapper and returns the wrapped item.")]
+            #[inline(always)]
+            pub fn into_inner(self) -> $inner {
+                self.0
+            }
+        }
+
+        impl<F: Format> $name<F> {
+            /// Parses `text` in the format, or one of the formats also
+            /// accepted.
+            fn parse(text: &str) -> Option<Self> {
+                std::iter::once(F::FORMAT)
+                    .chain(F::ALSO_ACCEPTS.iter().copied())
+                    .find_map(|format| $parse(text, format))
+                    .map(Self::new)
+            }
+
+            fn expected() -> String {
+                format!(concat!($what, " in the format `{}`"), F::FORMAT)
+            }
+        }
+
+        impl<F> From<$inner> for $name<F> {
+            fn from(value: $inner) -> Self {
+                Self::new(value)
+            }
+        }
+
+        impl<F> std::ops::Deref for $name<F> {
+            type Target = $inner;
+
+            #[inline(always)]
+            fn deref(&self) -> &$inner {
+                &self.0
+            }
+        }
+
+        impl<F> std::ops::DerefMut for $name<F> {
+            #[inline(always)]
+            fn deref_mut(&mut self) -> &mut $inner {
+                &mut self.0
+            }
+        }
+
+        // Implemented by hand, as derives would require them of `F`.
+        impl<F> Clone for $name<F> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<F> Copy for $name<F> {}
+
+        impl<F> std::fmt::Debug for $name<F> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_tuple(stringify!($name)).field(&self.0).finish()
+            }
+        }
+
+        impl<F> PartialEq for $name<F> {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+
+        impl<F> Eq for $name<F> {}
+
+        impl<F> PartialOrd for $name<F> {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl<F> Ord for $name<F> {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.0.cmp(&other.0)
+            }
+        }
+
+        impl<F> std::hash::Hash for $name<F> {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.0.hash(state)
+            }
+        }
+
+        impl<'de, F: Format> serde::Deserialize<'de> for $name<F> {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                use serde::de::{Error, Unexpected};
+
+                let text = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+                Self::parse(&text)
+                    .ok_or_else(|| D::Error::invalid_value(Unexpected::Str(&text), &&*Self::expected()))
+            }
+        }
+
+        impl<F: Format> serde::Serialize for $name<F> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match $format(&self.0, F::FORMAT) {
+                    Some(text) => serializer.serialize_str(&text),
+                    None => Err(serde::ser::Error::custom(Self::expected())),
+                }
+            }
+        }
+
+        #[rocket::async_trait]
+        impl<'v, F: Format + Send> rocket::form::FromFormField<'v> for $name<F> {
+            fn from_value(field: rocket::form::ValueField<'v>) -> rocket::form::Result<'v, Self> {
+                Self::parse(field.value).ok_or_else(|| {
+                    let expected = format!("expected {}", Self::expected());
+                    rocket::form::Error::validation(expected).into()
+                })
+            }
+        }
+    };
+}
+
+pub(crate) use form_date;
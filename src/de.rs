@@ -0,0 +1,2025 @@
+// Copyright (c) 2017 Anthony Ramine
+// Copyright (c) 2021 Cognite AS
+//! Deserialize x-www-form-urlencoded data.
+//!
+//! This is a reimplementation of the [`serde_urlencoded`] deserializer that
+//! additionally supports `#[serde(flatten)]` and internally tagged enums with
+//! non-string fields.
+//!
+//! Serde buffers flattened and internally tagged types into a self-describing
+//! representation before handing them to their fields, which means the fields
+//! only ever see what [`deserialize_any`](de::Deserializer::deserialize_any)
+//! produced. Values are therefore offered as strings, exactly like
+//! [`serde_urlencoded`], and where a field fails with a string when it
+//! expected a boolean or a number, the pairs are deserialized again with the
+//! value of that field alone inferred from its textual form. Strings which
+//! look like numbers stay strings.
+//!
+//! ```rust
+//! use serde::Deserialize;
+//!
+//! #[derive(Debug, PartialEq, Deserialize)]
+//! struct Page {
+//!     offset: usize,
+//!     limit: usize,
+//! }
+//!
+//! #[derive(Debug, PartialEq, Deserialize)]
+//! struct Listing {
+//!     sort: String,
+//!     #[serde(flatten)]
+//!     page: Page,
+//! }
+//!
+//! let listing: Listing = rocket_enumform::de::from_str("sort=name&offset=20&limit=10").unwrap();
+//! assert_eq!(listing.page, Page { offset: 20, limit: 10 });
+//! ```
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::{error, fmt};
+
+use serde::de::value::{MapAccessDeserializer, MapDeserializer, SeqDeserializer};
+use serde::de::{self, Error as _, IntoDeserializer};
+use serde::forward_to_deserialize_any;
+
+use crate::ErrorCode;
+
+/// Errors deserializing form data, also exported as
+/// [`ParseError`](crate::ParseError).
+///
+/// Besides the message, an error carries what is known of its cause: the
+/// [`path()`](Error::path) of the field being deserialized, the
+/// [`offset()`](Error::offset) of its pair in the form data and the type
+/// [`expected()`](Error::expected) there.
+///
+/// ```rust
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Item {
+///     name: String,
+///     count: u32,
+/// }
+///
+/// let e = rocket_enumform::from_str::<Item>("name=nail&count=many").unwrap_err();
+/// assert_eq!(e.path(), Some("count"));
+/// assert_eq!(e.offset(), Some(10));
+/// assert_eq!(e.expected(), Some("u32"));
+/// ```
+///
+/// The values of [`Secret`](crate::Secret) fields deserialized before the
+/// error are kept to redact them, see [`Error::redact()`], and are left out
+/// of the Debug output.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Error {
+    message: Box<str>,
+    path: Option<Box<str>>,
+    offset: Option<usize>,
+    expected: Option<Box<str>>,
+    limit: Option<Limit>,
+    code: ErrorCode,
+    unknown: Box<[String]>,
+    secrets: Box<[String]>,
+    /// Leave the message, which may quote a value, out of the Debug output.
+    quiet: bool,
+}
+
+impl Error {
+    /// The field which failed to deserialize, if known.
+    ///
+    /// By default this is inferred from serde's error message: missing,
+    /// unknown and duplicate fields are named in it, and invalid values are
+    /// looked up among the submitted pairs. With the `path` feature enabled,
+    /// the failing field is tracked with
+    /// [serde_path_to_error](https://docs.rs/serde_path_to_error) wherever serde
+    /// does not buffer the input, which also tells apart fields submitted with
+    /// the same invalid value.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// The byte offset in the form data of the first pair submitted for the
+    /// [`path()`](Error::path), if it was submitted under that exact key.
+    ///
+    /// Only known where the form data is: for errors of [`from_bytes`] and
+    /// [`from_str`], and of the guards, in the body of the request, which may
+    /// differ from the raw data of [`Error::Parse`](crate::Error::Parse) kept
+    /// by [`EnumFormConfig::raw_data`](crate::EnumFormConfig::raw_data).
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// What was expected instead of the value which failed to deserialize,
+    /// as serde describes it, such as a type like `u32` or the variants of
+    /// an enum, if known.
+    pub fn expected(&self) -> Option<&str> {
+        self.expected.as_deref()
+    }
+
+    /// Locates the [`path()`](Error::path) in `input`, the form data the
+    /// error was deserialized from, unless it already is.
+    pub(crate) fn locate(mut self, input: &[u8]) -> Self {
+        if self.offset.is_none() {
+            self.offset = self
+                .path
+                .as_deref()
+                .and_then(|path| key_offset(input, path));
+        }
+        self
+    }
+
+    /// What went wrong, see [`ErrorCode`].
+    ///
+    /// Missing tags are told apart from other missing fields where serde
+    /// deserializes an internally tagged enum from the form data, rather than
+    /// from the fields it buffered for a flattened struct.
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    /// The structural limit of [`Options`] which was exceeded, if any.
+    pub fn limit(&self) -> Option<Limit> {
+        self.limit
+    }
+
+    /// The keys which are not fields of the target, in submission order, if
+    /// the error is due to [`Options::deny_unknown_fields`].
+    pub fn unknown_fields(&self) -> &[String] {
+        &self.unknown
+    }
+
+    /// `input`, urlencoded form data such as the raw body of
+    /// [`Error::Parse`](crate::Error::Parse), with the values of the
+    /// [`Secret`](crate::Secret) fields known to the error replaced by
+    /// `[redacted]`.
+    ///
+    /// Only the Secret fields deserialized before the error are known, which
+    /// excludes the fields of flattened structs and internally tagged enums,
+    /// as serde buffers them first. Values equal to a secret are redacted
+    /// wherever they are submitted.
+    ///
+    /// ```rust
+    /// use rocket_enumform::Secret;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Login {
+    ///     password: Secret<String>,
+    ///     remember: bool,
+    /// }
+    ///
+    /// let body = "password=hunter2&remember=maybe";
+    /// let e = rocket_enumform::from_str::<Login>(body).unwrap_err();
+    /// assert_eq!(e.redact(body), "password=[redacted]&remember=maybe");
+    /// ```
+    pub fn redact<'a>(&self, input: &'a str) -> Cow<'a, str> {
+        if self.secrets.is_empty() {
+            return Cow::Borrowed(input);
+        }
+        let mut redacted = String::with_capacity(input.len());
+        self.redact_into(input, &['&', ';'], &mut redacted);
+        match redacted == input {
+            true => Cow::Borrowed(input),
+            false => Cow::Owned(redacted),
+        }
+    }
+
+    /// Appends `input` to `out`, with the secret values of its pairs redacted.
+    /// Pairs are separated at the first of `separators`, and those which are
+    /// not secret at the next ones, as the input may have been separated at
+    /// either.
+    fn redact_into(&self, input: &str, separators: &[char], out: &mut String) {
+        let (&separator, rest) = match separators.split_first() {
+            Some(split) => split,
+            None => return out.push_str(input),
+        };
+        for pair in input.split_inclusive(separator) {
+            let stripped = pair.strip_suffix(separator).unwrap_or(pair);
+            let value = parse_pairs(stripped.as_bytes()).into_iter().next();
+            match (stripped.split_once('='), value) {
+                (Some((key, _)), Some((_, value))) if self.is_secret(&value) => {
+                    out.push_str(key);
+                    out.push_str("=[redacted]");
+                }
+                _ => self.redact_into(stripped, rest, out),
+            }
+            out.push_str(&pair[stripped.len()..]);
+        }
+    }
+
+    /// The value of an invalid type, as serde describes it in the message,
+    /// such as ``integer `5` ``.
+    fn found(&self) -> Option<&str> {
+        let expected = self.expected.as_deref()?;
+        match self.code {
+            ErrorCode::InvalidType => self
+                .message
+                .strip_prefix("invalid type: ")?
+                .strip_suffix(expected)?
+                .strip_suffix(", expected "),
+            _ => None,
+        }
+    }
+
+    fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = code;
+        self
+    }
+
+    /// Leaves the message out of the Debug output.
+    pub(crate) fn quiet(mut self) -> Self {
+        self.quiet = true;
+        self
+    }
+
+    /// Whether `value` is the value of a [`Secret`](crate::Secret) field.
+    pub(crate) fn is_secret(&self, value: &str) -> bool {
+        !value.is_empty() && self.secrets.iter().any(|secret| secret == value)
+    }
+
+    fn unknown(keys: Vec<String>) -> Self {
+        let list = keys
+            .iter()
+            .map(|key| format!("`{}`", key))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let plural = if keys.len() == 1 { "" } else { "s" };
+        Error {
+            message: format!("unknown field{} {}", plural, list).into(),
+            path: keys.first().map(|key| key.as_str().into()),
+            offset: None,
+            expected: None,
+            limit: None,
+            code: ErrorCode::UnknownField,
+            unknown: keys.into(),
+            secrets: Box::default(),
+            quiet: false,
+        }
+    }
+
+    /// A key which is both a value and has nested keys.
+    pub(crate) fn conflict(path: &str) -> Self {
+        Error {
+            message: format!("conflicting field `{}`", path).into(),
+            path: Some(path.into()),
+            offset: None,
+            expected: None,
+            limit: None,
+            code: ErrorCode::ConflictingField,
+            unknown: Box::default(),
+            secrets: Box::default(),
+            quiet: false,
+        }
+    }
+
+    fn exceeded(limit: Limit, max: usize, path: Option<&str>) -> Self {
+        let code = match limit {
+            Limit::Pairs => ErrorCode::TooManyFields,
+            Limit::KeyLength => ErrorCode::KeyTooLong,
+            Limit::ValueLength => ErrorCode::ValueTooLong,
+        };
+        let message = match (limit, path) {
+            (Limit::Pairs, _) => format!("too many fields, the limit is {}", max),
+            (Limit::KeyLength, _) => format!("field name too long, the limit is {} bytes", max),
+            (Limit::ValueLength, Some(key)) => {
+                format!("value of `{}` too long, the limit is {} bytes", key, max)
+            }
+            (Limit::ValueLength, None) => format!("value too long, the limit is {} bytes", max),
+        };
+        Error {
+            message: message.into(),
+            path: path.map(Into::into),
+            offset: None,
+            expected: None,
+            limit: Some(limit),
+            code,
+            unknown: Box::default(),
+            secrets: Box::default(),
+            quiet: false,
+        }
+    }
+}
+
+/// A structural limit on form data, see [`Options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Limit {
+    /// [`Options::max_pairs`] was exceeded.
+    Pairs,
+
+    /// [`Options::max_key_len`] was exceeded.
+    KeyLength,
+
+    /// [`Options::max_value_len`] was exceeded.
+    ValueLength,
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message: &dyn fmt::Debug = match self.quiet {
+            true => &format_args!("[redacted]"),
+            false => &self.message,
+        };
+        f.debug_struct("Error")
+            .field("message", message)
+            .field("path", &self.path)
+            .field("offset", &self.offset)
+            .field("expected", &self.expected)
+            .field("limit", &self.limit)
+            .field("code", &self.code)
+            .field("unknown", &self.unknown)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error {
+            message: msg.to_string().into(),
+            path: None,
+            offset: None,
+            expected: None,
+            limit: None,
+            code: ErrorCode::Invalid,
+            unknown: Box::default(),
+            secrets: Box::default(),
+            quiet: false,
+        }
+    }
+
+    // Serde's messages, keeping what was expected and the code.
+
+    fn invalid_type(unexp: de::Unexpected<'_>, exp: &dyn de::Expected) -> Self {
+        let mut e = Error::custom(format_args!("invalid type: {}, expected {}", unexp, exp));
+        e.expected = Some(exp.to_string().into());
+        e.with_code(ErrorCode::InvalidType)
+    }
+
+    fn invalid_value(unexp: de::Unexpected<'_>, exp: &dyn de::Expected) -> Self {
+        let mut e = Error::custom(format_args!("invalid value: {}, expected {}", unexp, exp));
+        e.expected = Some(exp.to_string().into());
+        e.with_code(ErrorCode::InvalidValue)
+    }
+
+    fn invalid_length(len: usize, exp: &dyn de::Expected) -> Self {
+        let mut e = Error::custom(format_args!("invalid length {}, expected {}", len, exp));
+        e.expected = Some(exp.to_string().into());
+        e.with_code(ErrorCode::InvalidLength)
+    }
+
+    fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Self {
+        let e = match one_of(expected) {
+            Some(expected) => {
+                let message = format!("unknown variant `{}`, expected {}", variant, expected);
+                let mut e = Error::custom(message);
+                e.expected = Some(expected.into());
+                e
+            }
+            None => Error::custom(format_args!(
+                "unknown variant `{}`, there are no variants",
+                variant
+            )),
+        };
+        e.with_code(ErrorCode::UnknownVariant)
+    }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        let e = match one_of(expected) {
+            Some(expected) => Error::custom(format_args!(
+                "unknown field `{}`, expected {}",
+                field, expected
+            )),
+            None => Error::custom(format_args!(
+                "unknown field `{}`, there are no fields",
+                field
+            )),
+        };
+        e.with_code(ErrorCode::UnknownField)
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Error::custom(format_args!("missing field `{}`", field)).with_code(ErrorCode::MissingField)
+    }
+
+    fn duplicate_field(field: &'static str) -> Self {
+        Error::custom(format_args!("duplicate field `{}`", field))
+            .with_code(ErrorCode::DuplicateField)
+    }
+}
+
+/// `names` listed as serde does in its messages, if there are any.
+fn one_of(names: &[&str]) -> Option<String> {
+    match names {
+        [] => None,
+        [only] => Some(format!("`{}`", only)),
+        [first, second] => Some(format!("`{}` or `{}`", first, second)),
+        names => {
+            let names: Vec<_> = names.iter().map(|name| format!("`{}`", name)).collect();
+            Some(format!("one of {}", names.join(", ")))
+        }
+    }
+}
+
+/// The byte offset in `input` of the first pair whose key decodes to `key`,
+/// with pairs separated at `&` and `;` as
+/// [`Options::semicolon_separator`] allows.
+fn key_offset(input: &[u8], key: &str) -> Option<usize> {
+    let mut start = 0;
+    for end in memchr::memchr2_iter(b'&', b';', input).chain(Some(input.len())) {
+        let pair = &input[start..end];
+        let name = match memchr::memchr(b'=', pair) {
+            Some(i) => &pair[..i],
+            None => pair,
+        };
+        if !pair.is_empty() && percent_decode(name) == key {
+            return Some(start);
+        }
+        start = end + 1;
+    }
+    None
+}
+
+/// Decoded key/value pairs, borrowing from the input where no decoding was
+/// needed.
+pub type Pairs<'a> = Vec<(Cow<'a, str>, Cow<'a, str>)>;
+
+/// Options controlling how pairs are mapped to a value, in the spirit of
+/// Rocket's [`form::Options`](rocket::form::Options).
+///
+/// The default matches [`serde_urlencoded`]: keys which are not fields of the
+/// target type are ignored and empty values are kept as empty strings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// Deserialize empty values of optional fields as `None`.
+    pub empty_as_none: bool,
+
+    /// Fail on keys which are not fields of the target struct, listing all of
+    /// them in the error, see [`Error::unknown_fields()`]. Unknown keys take
+    /// precedence over other errors of the target, such as the missing field
+    /// a misnamed key leaves, unless the target fails before reaching them.
+    ///
+    /// Only keys ignored by the target itself are detected: the fields of
+    /// flattened structs and internally tagged enums are buffered by serde
+    /// first, and their unknown keys are dropped where this option cannot
+    /// see them. Internally tagged enums need `#[serde(deny_unknown_fields)]`
+    /// instead, while structs with flattened fields, which serde does not
+    /// allow it on, accept unknown keys.
+    pub deny_unknown_fields: bool,
+
+    /// How to handle keys which are submitted more than once, or `None` to
+    /// leave it to the target type: structs fail with a duplicate field error
+    /// and maps keep the last value.
+    pub duplicates: Option<Duplicates>,
+
+    /// The maximum number of pairs, or `None` for no limit.
+    pub max_pairs: Option<usize>,
+
+    /// The maximum length of a decoded key in bytes, or `None` for no limit.
+    pub max_key_len: Option<usize>,
+
+    /// The maximum length of a decoded value in bytes, or `None` for no limit.
+    pub max_value_len: Option<usize>,
+
+    /// Also separate pairs at `;`, as older HTML specifications and some
+    /// clients do, when the guards split form data into pairs. The functions
+    /// of this module which take pairs are not affected.
+    pub semicolon_separator: bool,
+
+    /// Decode `+` as a literal plus sign instead of a space, for clients
+    /// which only percent-encode spaces as `%20`. Applies wherever
+    /// [`semicolon_separator`](Options::semicolon_separator) does.
+    pub literal_plus: bool,
+
+    /// Log the keys which are not fields of the target struct at this level
+    /// when they are ignored, that is unless
+    /// [`deny_unknown_fields`](Options::deny_unknown_fields) is set, to detect
+    /// clients submitting fields the target no longer or never had. The same
+    /// caveats apply.
+    pub log_unknown_fields: Option<log::Level>,
+
+    /// Translate keys from this case to `snake_case` before deserializing,
+    /// see [`KeyCase`](crate::KeyCase). Limits apply to the keys as
+    /// submitted.
+    pub key_case: Option<crate::KeyCase>,
+
+    /// Strip this prefix, such as `payload.`, from the keys which start with
+    /// it before deserializing. Other keys are kept as they are. Applied
+    /// before [`key_case`](Options::key_case).
+    pub key_prefix: Option<Cow<'static, str>>,
+
+    /// Nest keys at `.`, so that `address.street=Foo&address.zip=123`
+    /// deserializes into an `address` field of a struct with `street` and
+    /// `zip` fields. Nested keys also fill internally tagged enums, with
+    /// `address.type=home`, and externally tagged ones, with
+    /// `address.home.street=Foo`. A key which is submitted both with and
+    /// without nested keys fails, while a key submitted more than once is
+    /// handled as without dot notation, such as by a
+    /// [`MultiSelect`](crate::MultiSelect).
+    ///
+    /// ```rust
+    /// use rocket_enumform::de;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Address {
+    ///     street: String,
+    ///     zip: u32,
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct User {
+    ///     address: Address,
+    /// }
+    ///
+    /// let opts = de::Options {
+    ///     dot_notation: true,
+    ///     ..Default::default()
+    /// };
+    /// let pairs = form_urlencoded::parse(b"address.street=Foo&address.zip=123").collect();
+    /// let user: User = de::from_pairs_with(pairs, opts).unwrap();
+    /// assert_eq!(user.address.zip, 123);
+    /// ```
+    pub dot_notation: bool,
+}
+
+impl Options {
+    /// Tolerates empty values for optional fields and ignores unknown keys.
+    pub const LENIENT: Options = Options {
+        empty_as_none: true,
+        deny_unknown_fields: false,
+        duplicates: None,
+        max_pairs: None,
+        max_key_len: None,
+        max_value_len: None,
+        semicolon_separator: false,
+        literal_plus: false,
+        log_unknown_fields: None,
+        key_case: None,
+        key_prefix: None,
+        dot_notation: false,
+    };
+
+    /// Fails on unknown keys.
+    pub const STRICT: Options = Options {
+        empty_as_none: false,
+        deny_unknown_fields: true,
+        duplicates: None,
+        max_pairs: None,
+        max_key_len: None,
+        max_value_len: None,
+        semicolon_separator: false,
+        literal_plus: false,
+        log_unknown_fields: None,
+        key_case: None,
+        key_prefix: None,
+        dot_notation: false,
+    };
+}
+
+/// A policy for keys which are submitted more than once, applied to the pairs
+/// before deserialization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Duplicates {
+    /// Keep the first value.
+    FirstWins,
+
+    /// Keep the last value.
+    LastWins,
+
+    /// Fail with a duplicate field error.
+    Error,
+
+    /// Keep all values, in submission order, as a sequence: `tags=a&tags=b`
+    /// deserializes into a `tags: Vec<String>` field. A key submitted once
+    /// deserializes into a sequence of one value, except for the fields of
+    /// flattened structs and internally tagged enums, which serde buffers
+    /// first.
+    Collect,
+}
+
+/// Pairs with all the values submitted for their key.
+type Grouped<'a> = Vec<(Cow<'a, str>, Vec<Cow<'a, str>>)>;
+
+/// Grouped pairs, with dotted keys nested if enabled.
+type Entries<'a> = Vec<(Cow<'a, str>, Entry<'a>)>;
+
+/// The values of a key, or the nested keys below it.
+#[derive(Clone)]
+enum Entry<'a> {
+    Values(Vec<Cow<'a, str>>),
+    Nested(Entries<'a>),
+}
+
+/// Nests `grouped` at the dots of their keys if `dot_notation` is set.
+fn nest(grouped: Grouped<'_>, dot_notation: bool) -> Result<Entries<'_>, Error> {
+    if !dot_notation {
+        return Ok(grouped
+            .into_iter()
+            .map(|(key, values)| (key, Entry::Values(values)))
+            .collect());
+    }
+
+    let mut tree = Tree::default();
+    for (key, values) in grouped {
+        let path = key.to_string();
+        tree.insert(key, values).map_err(|mut e| {
+            e.path = Some(path.into());
+            e
+        })?;
+    }
+    Ok(tree.into_entries())
+}
+
+/// Entries being nested, with the position of the first entry of each key.
+#[derive(Default)]
+struct Tree<'a> {
+    entries: Vec<(Cow<'a, str>, Node<'a>)>,
+    index: HashMap<Cow<'a, str>, usize>,
+}
+
+/// The values of a key, or the nested keys below it, while nesting.
+enum Node<'a> {
+    Values(Vec<Cow<'a, str>>),
+    Nested(Tree<'a>),
+}
+
+impl<'a> Tree<'a> {
+    /// Inserts the `values` of a dotted `key`. A key which is submitted more
+    /// than once keeps an entry per submission, as without dot notation, and
+    /// only a key which is both a value and has nested keys fails.
+    fn insert(&mut self, key: Cow<'a, str>, values: Vec<Cow<'a, str>>) -> Result<(), Error> {
+        let (head, tail) = match split_key(&key) {
+            Some(split) => split,
+            None => {
+                match self.index.get(&*key) {
+                    Some(&i) if matches!(self.entries[i].1, Node::Nested(_)) => {
+                        return Err(Error::conflict(&key));
+                    }
+                    Some(_) => {}
+                    None => {
+                        self.index.insert(key.clone(), self.entries.len());
+                    }
+                }
+                self.entries.push((key, Node::Values(values)));
+                return Ok(());
+            }
+        };
+
+        let i = match self.index.get(&*head) {
+            Some(&i) => i,
+            None => {
+                self.index.insert(head.clone(), self.entries.len());
+                self.entries
+                    .push((head.clone(), Node::Nested(Tree::default())));
+                self.entries.len() - 1
+            }
+        };
+        match &mut self.entries[i].1 {
+            Node::Nested(nested) => nested.insert(tail, values),
+            Node::Values(_) => Err(Error::conflict(&head)),
+        }
+    }
+
+    fn into_entries(self) -> Entries<'a> {
+        self.entries
+            .into_iter()
+            .map(|(key, node)| match node {
+                Node::Values(values) => (key, Entry::Values(values)),
+                Node::Nested(tree) => (key, Entry::Nested(tree.into_entries())),
+            })
+            .collect()
+    }
+}
+
+/// Splits `key` at its first dot, borrowing from the input where `key` does.
+fn split_key<'a>(key: &Cow<'a, str>) -> Option<(Cow<'a, str>, Cow<'a, str>)> {
+    match key {
+        Cow::Borrowed(key) => key
+            .split_once('.')
+            .map(|(head, tail)| (Cow::Borrowed(head), Cow::Borrowed(tail))),
+        Cow::Owned(key) => key
+            .split_once('.')
+            .map(|(head, tail)| (Cow::Owned(head.to_string()), Cow::Owned(tail.to_string()))),
+    }
+}
+
+/// Fails if `pairs` exceed the structural limits of `opts`.
+fn check_limits(pairs: &Pairs<'_>, opts: &Options) -> Result<(), Error> {
+    if let Some(max) = opts.max_pairs.filter(|&max| pairs.len() > max) {
+        return Err(Error::exceeded(Limit::Pairs, max, None));
+    }
+    for (key, value) in pairs {
+        if let Some(max) = opts.max_key_len.filter(|&max| key.len() > max) {
+            return Err(Error::exceeded(Limit::KeyLength, max, None));
+        }
+        if let Some(max) = opts.max_value_len.filter(|&max| value.len() > max) {
+            return Err(Error::exceeded(Limit::ValueLength, max, Some(key)));
+        }
+    }
+    Ok(())
+}
+
+/// Applies the `policy` for duplicate keys to `pairs`. Keys keep the position
+/// of their first occurrence.
+fn group<'a>(pairs: &Pairs<'a>, policy: Option<Duplicates>) -> Result<Grouped<'a>, Error> {
+    let policy = match policy {
+        Some(policy) => policy,
+        None => {
+            return Ok(pairs
+                .iter()
+                .map(|(k, v)| (k.clone(), vec![v.clone()]))
+                .collect())
+        }
+    };
+
+    let mut grouped: Grouped<'a> = Vec::with_capacity(pairs.len());
+    let mut index: HashMap<&str, usize> = HashMap::with_capacity(pairs.len());
+    for (key, value) in pairs {
+        let values = match index.get(&**key) {
+            Some(&i) => &mut grouped[i].1,
+            None => {
+                index.insert(key, grouped.len());
+                grouped.push((key.clone(), vec![value.clone()]));
+                continue;
+            }
+        };
+        match policy {
+            Duplicates::FirstWins => {}
+            Duplicates::LastWins => *values = vec![value.clone()],
+            Duplicates::Error => {
+                let e = Error::custom(format_args!("duplicate field `{}`", key));
+                return Err(e.with_code(ErrorCode::DuplicateField));
+            }
+            Duplicates::Collect => values.push(value.clone()),
+        }
+    }
+    Ok(grouped)
+}
+
+/// The name of the hidden field HTML forms submit their encoding in.
+pub(crate) const CHARSET_FIELD: &str = "_charset_";
+
+/// The newtype name [`MultiSelect`](crate::MultiSelect) deserializes with, to
+/// receive all the values of its key.
+pub(crate) const MULTI_SELECT: &str = "$rocket_enumform::MultiSelect";
+
+/// The newtype name [`Secret`](crate::Secret) deserializes with, to have its
+/// values redacted from errors.
+pub(crate) const SECRET: &str = "$rocket_enumform::Secret";
+
+/// Deserializes a `application/x-www-form-urlencoded` value from a `&[u8]`.
+pub fn from_bytes<'de, T>(input: &'de [u8]) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+{
+    from_pairs(parse_pairs(input)).map_err(|e| e.locate(input))
+}
+
+/// Splits and decodes `application/x-www-form-urlencoded` `input` into pairs
+/// exactly like [`form_urlencoded::parse`], borrowing the keys and values
+/// which contain no `+` or escapes from `input`.
+///
+/// Separators and escapes are searched for with [`memchr`], and the text
+/// between escapes is copied in bulk rather than byte by byte, which makes a
+/// difference for large bodies.
+///
+/// ```rust
+/// use rocket_enumform::de::parse_pairs;
+///
+/// let pairs = parse_pairs(b"name=Jane+Doe&city=K%C3%B8benhavn&&flag");
+/// assert_eq!(pairs[0], ("name".into(), "Jane Doe".into()));
+/// assert_eq!(pairs[1], ("city".into(), "København".into()));
+/// assert_eq!(pairs[2], ("flag".into(), "".into()));
+/// ```
+pub fn parse_pairs(input: &[u8]) -> Pairs<'_> {
+    let mut pairs = Vec::new();
+    let mut start = 0;
+    for end in memchr::memchr_iter(b'&', input).chain(Some(input.len())) {
+        let pair = &input[start..end];
+        start = end + 1;
+        if pair.is_empty() {
+            continue;
+        }
+        let (key, value) = match memchr::memchr(b'=', pair) {
+            Some(i) => (&pair[..i], &pair[i + 1..]),
+            None => (pair, &[][..]),
+        };
+        pairs.push((percent_decode(key), percent_decode(value)));
+    }
+    pairs
+}
+
+/// Decodes `+` and the percent escapes of `input`, keeping invalid escapes as
+/// they are and replacing invalid UTF-8 like [`String::from_utf8_lossy`].
+fn percent_decode(input: &[u8]) -> Cow<'_, str> {
+    let mut next = match memchr::memchr2(b'+', b'%', input) {
+        Some(next) => next,
+        None => return String::from_utf8_lossy(input),
+    };
+    let mut decoded = Vec::with_capacity(input.len());
+    let mut rest = input;
+    loop {
+        decoded.extend_from_slice(&rest[..next]);
+        rest = &rest[next..];
+        let escaped = match rest {
+            [b'%', high, low, ..] => hex_digit(*high).zip(hex_digit(*low)),
+            _ => None,
+        };
+        match escaped {
+            Some((high, low)) => {
+                decoded.push(high << 4 | low);
+                rest = &rest[3..];
+            }
+            None => {
+                decoded.push(if rest[0] == b'+' { b' ' } else { b'%' });
+                rest = &rest[1..];
+            }
+        }
+        // Escapes tend to come in runs, such as those of non-ASCII text.
+        next = match rest.first() {
+            Some(b'+' | b'%') => 0,
+            _ => match memchr::memchr2(b'+', b'%', rest) {
+                Some(next) => next,
+                None => break,
+            },
+        };
+    }
+    decoded.extend_from_slice(rest);
+    match String::from_utf8(decoded) {
+        Ok(decoded) => Cow::Owned(decoded),
+        Err(e) => Cow::Owned(String::from_utf8_lossy(e.as_bytes()).into_owned()),
+    }
+}
+
+/// The value of the hexadecimal digit `byte`.
+fn hex_digit(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|digit| digit as u8)
+}
+
+/// Deserializes a value from already decoded key/value pairs.
+pub fn from_pairs<'de, T>(pairs: Pairs<'de>) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+{
+    from_pairs_with(pairs, Options::default())
+}
+
+/// Deserializes a value from already decoded key/value pairs with `opts`.
+///
+/// The `_charset_` field, which HTML forms may submit to declare their
+/// encoding, and the `_method` field, which overrides the request method, are
+/// removed from `pairs` first.
+pub fn from_pairs_with<'de, T>(pairs: Pairs<'de>, opts: Options) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+{
+    from_pairs_with_secrets(pairs, opts).0
+}
+
+/// Like [`from_pairs_with`], also returning the values which
+/// [`Secret`](crate::Secret) fields received, whether it fails or not.
+pub(crate) fn from_pairs_with_secrets<'de, T>(
+    mut pairs: Pairs<'de>,
+    opts: Options,
+) -> (Result<T, Error>, Vec<String>)
+where
+    T: de::Deserialize<'de>,
+{
+    if let Err(e) = check_limits(&pairs, &opts) {
+        return (Err(e), Vec::new());
+    }
+    pairs.retain(|(key, _)| key != CHARSET_FIELD && key != crate::method::METHOD_FIELD);
+    if let Some(prefix) = &opts.key_prefix {
+        for (key, _) in &mut pairs {
+            if let Some(stripped) = strip_prefix(key, prefix) {
+                *key = stripped;
+            }
+        }
+    }
+    if let Some(case) = opts.key_case {
+        for (key, _) in &mut pairs {
+            *key = Cow::Owned(case.decode(key));
+        }
+    }
+    let entries =
+        group(&pairs, opts.duplicates).and_then(|grouped| nest(grouped, opts.dot_notation));
+    let (result, secrets) = match entries {
+        Ok(entries) => {
+            let mut inference = Inference::default();
+            loop {
+                match deserialize_pass(entries.clone(), &inference.inferred, &opts) {
+                    (Err(e), _) if inference.retry(&e, &entries, opts.empty_as_none) => continue,
+                    done => break done,
+                }
+            }
+        }
+        Err(e) => (Err(e), Vec::new()),
+    };
+    let result = result.map_err(|mut e| {
+        if e.path.is_none() {
+            e.path = error_key(&e, &pairs).map(Into::into);
+        }
+        e
+    });
+    (result, secrets)
+}
+
+/// `key` without `prefix`, if it starts with it, borrowing from the input
+/// where `key` does.
+fn strip_prefix<'a>(key: &Cow<'a, str>, prefix: &str) -> Option<Cow<'a, str>> {
+    match key {
+        Cow::Borrowed(key) => key.strip_prefix(prefix).map(Cow::Borrowed),
+        Cow::Owned(key) => key
+            .strip_prefix(prefix)
+            .map(|key| Cow::Owned(key.to_string())),
+    }
+}
+
+/// The keys whose values are inferred in `deserialize_any`.
+type InferredKeys = Rc<HashSet<String>>;
+
+/// Which buffered values are inferred as booleans and numbers, decided one
+/// value at a time from the invalid types serde fails with.
+#[derive(Default)]
+struct Inference {
+    inferred: InferredKeys,
+    /// The keys whose values were inferred but are strings after all.
+    strings: HashSet<String>,
+}
+
+impl Inference {
+    /// Infers or stops inferring the value of the first key `e` may be due
+    /// to, returning whether deserializing again may succeed. Each key is
+    /// inferred at most once, which bounds the passes.
+    fn retry(&mut self, e: &Error, entries: &Entries<'_>, empty_as_none: bool) -> bool {
+        let found = match e.found() {
+            Some(found) => found,
+            None => return false,
+        };
+        let mut values = Vec::new();
+        dotted_values(entries, None, &mut values);
+        let (inferred, strings) = (Rc::make_mut(&mut self.inferred), &mut self.strings);
+
+        let text = values.iter().find(|(key, values)| {
+            !inferred.contains(key)
+                && !strings.contains(key)
+                && values.iter().any(|value| {
+                    de::Unexpected::Str(value).to_string() == found
+                        && infer(value, empty_as_none).is_some()
+                })
+        });
+        if let Some((key, _)) = text {
+            return inferred.insert(key.clone());
+        }
+
+        let value = values.iter().find(|(key, values)| {
+            inferred.contains(key)
+                && values.iter().any(|value| {
+                    infer(value, empty_as_none).map(|value| value.unexpected().to_string())
+                        == Some(found.to_string())
+                })
+        });
+        match value {
+            Some((key, _)) => {
+                inferred.remove(key);
+                strings.insert(key.clone())
+            }
+            None => false,
+        }
+    }
+}
+
+/// Appends the dotted keys of `entries` and their values to `out`.
+fn dotted_values<'a, 'de>(
+    entries: &'a Entries<'de>,
+    parent: Option<&str>,
+    out: &mut Vec<(String, &'a [Cow<'de, str>])>,
+) {
+    for (key, entry) in entries {
+        let path = match parent {
+            Some(parent) => format!("{}.{}", parent, key),
+            None => key.to_string(),
+        };
+        match entry {
+            Entry::Values(values) => out.push((path, values)),
+            Entry::Nested(entries) => dotted_values(entries, Some(&path), out),
+        }
+    }
+}
+
+/// A value inferred from its textual form in `deserialize_any`.
+enum Inferred {
+    None,
+    Bool(bool),
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+}
+
+impl Inferred {
+    fn unexpected(&self) -> de::Unexpected<'static> {
+        match *self {
+            Inferred::None => de::Unexpected::Option,
+            Inferred::Bool(value) => de::Unexpected::Bool(value),
+            Inferred::Unsigned(value) => de::Unexpected::Unsigned(value),
+            Inferred::Signed(value) => de::Unexpected::Signed(value),
+            Inferred::Float(value) => de::Unexpected::Float(value),
+        }
+    }
+}
+
+/// The boolean or number `text` is inferred as, or `None` if it is a string
+/// after all. Empty values are inferred as `None` with `empty_as_none`.
+fn infer(text: &str, empty_as_none: bool) -> Option<Inferred> {
+    if empty_as_none && text.is_empty() {
+        Some(Inferred::None)
+    } else if let Ok(value) = text.parse() {
+        Some(Inferred::Bool(value))
+    } else if let Ok(value) = text.parse() {
+        Some(Inferred::Unsigned(value))
+    } else if let Ok(value) = text.parse() {
+        Some(Inferred::Signed(value))
+    } else {
+        text.parse()
+            .ok()
+            .filter(|value: &f64| value.is_finite())
+            .map(Inferred::Float)
+    }
+}
+
+/// The keys ignored by the target while deserializing, if they are denied.
+type Unknown = Rc<RefCell<Vec<String>>>;
+
+/// The repeated keys whose values a [`MultiSelect`](crate::MultiSelect)
+/// received, which are skipped where they repeat.
+type Selected = Rc<RefCell<Vec<String>>>;
+
+/// The values [`Secret`](crate::Secret) fields received, which are redacted.
+type Secrets = Rc<RefCell<Vec<String>>>;
+
+/// Deserializes `pairs`, failing with all the unknown keys instead of the
+/// error of the target, if any, as they likely caused it, or logging them.
+/// Also returns the values of the [`Secret`](crate::Secret) fields.
+fn deserialize_pass<'de, T>(
+    pairs: Entries<'de>,
+    infer: &InferredKeys,
+    opts: &Options,
+) -> (Result<T, Error>, Vec<String>)
+where
+    T: de::Deserialize<'de>,
+{
+    let (unknown, secrets) = (Unknown::default(), Secrets::default());
+    let deserializer = Deserializer::new(
+        pairs,
+        infer.clone(),
+        opts.clone(),
+        unknown.clone(),
+        secrets.clone(),
+        None,
+    );
+    let result = deserialize(deserializer);
+    let unknown = unknown.take();
+    let result = match opts.log_unknown_fields {
+        _ if unknown.is_empty() => result,
+        Some(level) if !opts.deny_unknown_fields => {
+            if result.is_ok() {
+                log::log!(level, "ignored unknown fields: {}", unknown.join(", "));
+            }
+            result
+        }
+        _ => Err(Error::unknown(unknown)),
+    };
+    let secrets = secrets.take();
+    let result = result.map_err(|mut e| {
+        e.secrets = secrets.clone().into();
+        e
+    });
+    (result, secrets)
+}
+
+#[cfg(not(feature = "path"))]
+fn deserialize<'de, T: de::Deserialize<'de>>(deserializer: Deserializer<'de>) -> Result<T, Error> {
+    T::deserialize(deserializer)
+}
+
+#[cfg(feature = "path")]
+fn deserialize<'de, T: de::Deserialize<'de>>(deserializer: Deserializer<'de>) -> Result<T, Error> {
+    serde_path_to_error::deserialize(deserializer).map_err(|e| {
+        let path = e.path();
+        let known = path.iter().next().is_some()
+            && path
+                .iter()
+                .all(|s| !matches!(s, serde_path_to_error::Segment::Unknown));
+        let path = known.then(|| path.to_string().into());
+        let mut e = e.into_inner();
+        e.path = e.path.or(path);
+        e
+    })
+}
+
+/// Deserializes a `application/x-www-form-urlencoded` value from a `&str`.
+pub fn from_str<'de, T>(input: &'de str) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+{
+    from_bytes(input.as_bytes())
+}
+
+/// The key of `pairs` an error deserializing them refers to, as far as the
+/// error message tells.
+///
+/// Serde errors only carry a message: missing, unknown and duplicate fields
+/// are named in it, while invalid values are identified by looking up the
+/// quoted value among `pairs`.
+fn error_key(error: &Error, pairs: &[(Cow<'_, str>, Cow<'_, str>)]) -> Option<String> {
+    let message = error.to_string();
+    let quoted = |prefix: &str| {
+        message
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.split('`').next())
+    };
+
+    if let Some(field) = quoted("missing field `")
+        .or_else(|| quoted("unknown field `"))
+        .or_else(|| quoted("duplicate field `"))
+    {
+        return Some(field.to_string());
+    }
+
+    let key = match quoted("unknown variant `") {
+        Some(variant) => pairs.iter().find(|(_, v)| v == variant),
+        None => pairs
+            .iter()
+            .find(|(_, v)| message.contains(&format!("string {:?}", v))),
+    };
+    key.map(|(k, _)| k.to_string())
+}
+
+/// A deserializer for the `application/x-www-form-urlencoded` format.
+///
+/// * Supported top-level outputs are structs, maps and sequences of pairs,
+///   with or without a given length.
+///
+/// * Main `deserialize` methods defers to `deserialize_map`.
+///
+/// * Everything else but `deserialize_seq` and `deserialize_seq_fixed_size`
+///   defers to `deserialize`.
+pub struct Deserializer<'de> {
+    inner: MapDeserializer<'de, PartIterator<'de>, Error>,
+}
+
+impl<'de> Deserializer<'de> {
+    fn new(
+        pairs: Entries<'de>,
+        infer: InferredKeys,
+        opts: Options,
+        unknown: Unknown,
+        secrets: Secrets,
+        parent: Option<String>,
+    ) -> Self {
+        let mut counts = HashMap::new();
+        for (key, _) in pairs.iter().filter(|(_, e)| matches!(e, Entry::Values(_))) {
+            *counts.entry(&**key).or_insert(0) += 1;
+        }
+        let mut repeated: HashMap<String, Vec<Cow<'de, str>>> = HashMap::new();
+        for (key, entry) in &pairs {
+            if let (Entry::Values(values), Some(2..)) = (entry, counts.get(&**key)) {
+                let all = repeated.entry(key.to_string()).or_default();
+                all.extend(values.iter().cloned());
+            }
+        }
+        Deserializer {
+            inner: MapDeserializer::new(PartIterator {
+                pairs: pairs.into_iter(),
+                infer,
+                opts,
+                unknown,
+                secrets,
+                parent,
+                repeated,
+                selected: Selected::default(),
+            }),
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    /// Internally tagged enums, whose tag is the only field which can be
+    /// missing here, as structs are deserialized as such.
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_map(visitor).map_err(|e| match e.code {
+            ErrorCode::MissingField => e.with_code(ErrorCode::MissingTag),
+            _ => e,
+        })
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_map(self.inner)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_seq(self.inner)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.inner.end()?;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    /// Externally tagged enums, with the variant as the only key.
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_enum(MapAccessDeserializer::new(self.inner))
+    }
+
+    forward_to_deserialize_any! {
+        bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char str string
+        bytes byte_buf unit_struct newtype_struct tuple_struct
+        identifier tuple ignored_any
+    }
+}
+
+struct PartIterator<'de> {
+    pairs: std::vec::IntoIter<(Cow<'de, str>, Entry<'de>)>,
+    /// The dotted keys whose values are inferred.
+    infer: InferredKeys,
+    opts: Options,
+    unknown: Unknown,
+    secrets: Secrets,
+    /// The dotted key of the nested keys iterated.
+    parent: Option<String>,
+    /// All the values of keys which are submitted more than once.
+    repeated: HashMap<String, Vec<Cow<'de, str>>>,
+    selected: Selected,
+}
+
+impl<'de> Iterator for PartIterator<'de> {
+    type Item = (Part<'de>, Value<'de>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (k, entry) = loop {
+            let (k, entry) = self.pairs.next()?;
+            if !self.selected.borrow().iter().any(|s| *s == k) {
+                break (k, entry);
+            }
+        };
+        let path = match &self.parent {
+            Some(parent) => format!("{}.{}", parent, k),
+            None => k.to_string(),
+        };
+        let mut values = match entry {
+            Entry::Values(values) => values,
+            Entry::Nested(entries) => {
+                let (infer, opts) = (self.infer.clone(), self.opts.clone());
+                let (unknown, secrets) = (self.unknown.clone(), self.secrets.clone());
+                let map = Deserializer::new(entries, infer, opts, unknown, secrets, Some(path));
+                return Some((Part::key(k), Value::Map(Box::new(map))));
+            }
+        };
+        let tag = self.parent.is_none();
+        let infer = self.infer.contains(&path);
+        let secret = Some((path.clone(), self.secrets.clone()));
+        let key = (self.opts.deny_unknown_fields || self.opts.log_unknown_fields.is_some())
+            .then(|| (path, self.unknown.clone()));
+        let multi = self.repeated.get(&*k).map(|all| Multi {
+            key: k.to_string(),
+            values: all.clone(),
+            selected: self.selected.clone(),
+        });
+        let text = values.remove(0);
+        let value = Part {
+            text,
+            rest: values,
+            infer,
+            empty_as_none: self.opts.empty_as_none,
+            collect: self.opts.duplicates == Some(Duplicates::Collect),
+            key,
+            multi,
+            secret,
+            tag,
+        };
+        Some((Part::key(k), Value::Part(value)))
+    }
+}
+
+/// A value, or nested keys with [`Options::dot_notation`].
+enum Value<'de> {
+    Part(Part<'de>),
+    Map(Box<Deserializer<'de>>),
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Value<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! forward_to_value {
+    ($($method:ident($($arg:ident: $ty:ty),*),)*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+                where V: de::Visitor<'de>
+            {
+                match self {
+                    Value::Part(part) => part.$method($($arg,)* visitor),
+                    Value::Map(map) => (*map).$method($($arg,)* visitor),
+                }
+            }
+        )*
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Value<'de> {
+    type Error = Error;
+
+    forward_to_value! {
+        deserialize_any(),
+        deserialize_bool(),
+        deserialize_u8(),
+        deserialize_u16(),
+        deserialize_u32(),
+        deserialize_u64(),
+        deserialize_i8(),
+        deserialize_i16(),
+        deserialize_i32(),
+        deserialize_i64(),
+        deserialize_f32(),
+        deserialize_f64(),
+        deserialize_char(),
+        deserialize_str(),
+        deserialize_string(),
+        deserialize_bytes(),
+        deserialize_byte_buf(),
+        deserialize_option(),
+        deserialize_unit(),
+        deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str),
+        deserialize_seq(),
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_map(),
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+        deserialize_identifier(),
+        deserialize_ignored_any(),
+    }
+}
+
+/// A single key or value.
+struct Part<'de> {
+    text: Cow<'de, str>,
+    /// Further values collected for the same key.
+    rest: Vec<Cow<'de, str>>,
+    /// Infer booleans and numbers in `deserialize_any`.
+    infer: bool,
+    /// Deserialize an empty value as `None`.
+    empty_as_none: bool,
+    /// Deserialize a single value as a sequence where one is expected.
+    collect: bool,
+    /// The key of a value which must not be ignored silently, and where to
+    /// report it.
+    key: Option<(String, Unknown)>,
+    /// All the values of a key which is submitted more than once.
+    multi: Option<Multi<'de>>,
+    /// The key of a value, and where to report it if it is a
+    /// [`Secret`](crate::Secret).
+    secret: Option<(String, Secrets)>,
+    /// Whether a value deserialized as an identifier is the tag of the
+    /// variant of the form data, as of an internally tagged enum.
+    #[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+    tag: bool,
+}
+
+/// All the values of a repeated key, for a [`MultiSelect`](crate::MultiSelect).
+struct Multi<'de> {
+    key: String,
+    values: Vec<Cow<'de, str>>,
+    selected: Selected,
+}
+
+impl<'de> Part<'de> {
+    fn key(text: Cow<'de, str>) -> Self {
+        Part {
+            text,
+            rest: Vec::new(),
+            infer: false,
+            empty_as_none: false,
+            collect: false,
+            key: None,
+            multi: None,
+            secret: None,
+            tag: false,
+        }
+    }
+
+    /// Visits all values as a sequence.
+    fn visit_values<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let (infer, empty_as_none) = (self.infer, self.empty_as_none);
+        let secret = self.secret;
+        let parts = Some(self.text)
+            .into_iter()
+            .chain(self.rest)
+            .map(|text| Part {
+                text,
+                rest: Vec::new(),
+                infer,
+                empty_as_none,
+                collect: false,
+                key: None,
+                multi: None,
+                secret: secret.clone(),
+                tag: false,
+            });
+        let mut seq = SeqDeserializer::new(parts);
+        let value = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+        Ok(value)
+    }
+}
+
+/// A deserializer for a single value, which is parsed as the type it is
+/// deserialized into, and deserializes into a sequence of one value where one
+/// is expected. With `infer`, booleans and numbers are inferred in
+/// `deserialize_any`.
+pub(crate) fn text(text: &str, infer: bool) -> impl de::Deserializer<'_, Error = Error> {
+    Part {
+        text: Cow::Borrowed(text),
+        rest: Vec::new(),
+        infer,
+        empty_as_none: false,
+        collect: true,
+        key: None,
+        multi: None,
+        secret: None,
+        tag: false,
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Part<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! forward_parsed_value {
+    ($($ty:ident => $method:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+                where V: de::Visitor<'de>
+            {
+                if !self.rest.is_empty() {
+                    return self.visit_values(visitor);
+                }
+                match self.text.parse::<$ty>() {
+                    Ok(val) => val.into_deserializer().$method(visitor),
+                    Err(_) => Err(de::Error::invalid_value(
+                        de::Unexpected::Str(&self.text),
+                        &stringify!($ty),
+                    )),
+                }
+            }
+        )*
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Part<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if !self.rest.is_empty() {
+            return self.visit_values(visitor);
+        }
+        if self.infer {
+            match infer(&self.text, self.empty_as_none) {
+                Some(Inferred::None) => return visitor.visit_none(),
+                Some(Inferred::Bool(value)) => return visitor.visit_bool(value),
+                Some(Inferred::Unsigned(value)) => return visitor.visit_u64(value),
+                Some(Inferred::Signed(value)) => return visitor.visit_i64(value),
+                Some(Inferred::Float(value)) => return visitor.visit_f64(value),
+                None => {}
+            }
+        }
+        match self.text {
+            Cow::Borrowed(value) => visitor.visit_borrowed_str(value),
+            Cow::Owned(value) => visitor.visit_string(value),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.empty_as_none && self.text.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if let Some((key, unknown)) = self.key {
+            unknown.borrow_mut().push(key);
+        }
+        visitor.visit_unit()
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if !self.rest.is_empty() {
+            return self.visit_values(visitor);
+        }
+        visitor.visit_enum(ValueEnumAccess(self.text))
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.collect {
+            self.visit_values(visitor)
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    /// A [`MultiSelect`](crate::MultiSelect) receives all the values of its
+    /// key, wherever they are, and the values of a [`Secret`](crate::Secret)
+    /// are reported for redaction.
+    fn deserialize_newtype_struct<V>(
+        mut self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if name == MULTI_SELECT {
+            if let Some(multi) = self.multi.take() {
+                multi.selected.borrow_mut().push(multi.key);
+                let mut values = multi.values.into_iter();
+                self.text = values.next().unwrap_or_default();
+                self.rest = values.collect();
+            }
+            return self.visit_values(visitor);
+        }
+        if let (SECRET, Some((path, secrets))) = (name, self.secret.clone()) {
+            let values = Some(&self.text).into_iter().chain(&self.rest);
+            secrets.borrow_mut().extend(values.map(|v| v.to_string()));
+            return visitor.visit_newtype_struct(self).map_err(|mut e| {
+                e.path = e.path.or(Some(path.into()));
+                e
+            });
+        }
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Records the tag of an internally tagged enum, which serde deserializes
+    /// as an identifier, with the `tracing` feature.
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        #[cfg(feature = "tracing")]
+        if self.tag {
+            crate::trace::record_variant(&self.text);
+        }
+        self.deserialize_any(visitor)
+    }
+
+    /// An empty value, as written for a unit or a unit variant of an
+    /// externally tagged enum, `Unit=`.
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.rest.is_empty() && self.text.is_empty() {
+            true => visitor.visit_unit(),
+            false => self.deserialize_any(visitor),
+        }
+    }
+
+    /// Strings are never inferred as other types, as the fields of a
+    /// flattened struct may share a key with the fields next to it.
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if !self.rest.is_empty() {
+            return self.visit_values(visitor);
+        }
+        match self.text {
+            Cow::Borrowed(value) => visitor.visit_borrowed_str(value),
+            Cow::Owned(value) => visitor.visit_string(value),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        char bytes byte_buf unit_struct tuple_struct struct tuple map
+    }
+
+    forward_parsed_value! {
+        bool => deserialize_bool,
+        u8 => deserialize_u8,
+        u16 => deserialize_u16,
+        u32 => deserialize_u32,
+        u64 => deserialize_u64,
+        i8 => deserialize_i8,
+        i16 => deserialize_i16,
+        i32 => deserialize_i32,
+        i64 => deserialize_i64,
+        f32 => deserialize_f32,
+        f64 => deserialize_f64,
+    }
+}
+
+struct ValueEnumAccess<'de>(Cow<'de, str>);
+
+impl<'de> de::EnumAccess<'de> for ValueEnumAccess<'de> {
+    type Error = Error;
+    type Variant = UnitOnlyVariantAccess;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.0.into_deserializer())?;
+        Ok((variant, UnitOnlyVariantAccess))
+    }
+}
+
+struct UnitOnlyVariantAccess;
+
+impl<'de> de::VariantAccess<'de> for UnitOnlyVariantAccess {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, _seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        Err(Error::custom("expected unit variant"))
+    }
+
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::custom("expected unit variant"))
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::custom("expected unit variant"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(tag = "type")]
+    enum Tagged {
+        A { x: u32, y: u32 },
+    }
+
+    #[test]
+    fn tagged_enum_reports_missing_field() {
+        let e = from_str::<Tagged>("type=A&x=1").unwrap_err();
+        assert_eq!(e.code(), ErrorCode::MissingField);
+        assert_eq!(e.to_string(), "missing field `y`");
+        assert_eq!(e.path(), Some("y"));
+    }
+
+    /// Deserializes `input` with `opts`.
+    fn parse<'de, T: Deserialize<'de>>(input: &'de str, opts: Options) -> Result<T, Error> {
+        from_pairs_with(parse_pairs(input.as_bytes()), opts)
+    }
+
+    #[test]
+    fn flatten_infers_numbers_and_booleans() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Page {
+            offset: usize,
+            desc: bool,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Listing {
+            sort: String,
+            #[serde(flatten)]
+            page: Page,
+        }
+
+        let listing: Listing = from_str("sort=10&offset=20&desc=true").unwrap();
+        assert_eq!(listing.sort, "10");
+        assert_eq!(
+            listing.page,
+            Page {
+                offset: 20,
+                desc: true
+            }
+        );
+    }
+
+    #[test]
+    fn buffered_strings_stay_strings() {
+        #[derive(Debug, PartialEq, Deserialize, serde::Serialize)]
+        #[serde(tag = "type", rename_all = "snake_case")]
+        enum Message {
+            VariantOne { content_one: String, n: u32 },
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Inner {
+            code: String,
+            n: u32,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Outer {
+            #[serde(flatten)]
+            inner: Inner,
+        }
+
+        let message: Message = from_str("type=variant_one&content_one=5&n=3").unwrap();
+        let expected = Message::VariantOne {
+            content_one: "5".into(),
+            n: 3,
+        };
+        assert_eq!(message, expected);
+        let serialized = crate::ser::to_string(&message).unwrap();
+        assert_eq!(from_str::<Message>(&serialized).unwrap(), message);
+
+        let outer: Outer = from_str("code=true&n=3").unwrap();
+        assert_eq!(outer.inner.code, "true");
+        assert_eq!(outer.inner.n, 3);
+        // The same text as both a string and a number.
+        let outer: Outer = from_str("code=3&n=3").unwrap();
+        assert_eq!((&*outer.inner.code, outer.inner.n), ("3", 3));
+        let e = from_str::<Outer>("code=3&n=x").unwrap_err();
+        assert_eq!(e.code(), ErrorCode::InvalidType);
+        assert_eq!(e.path(), Some("n"));
+    }
+
+    #[test]
+    fn tagged_enum_deserializes_its_variant() {
+        assert_eq!(
+            from_str::<Tagged>("y=2&type=A&x=1").unwrap(),
+            Tagged::A { x: 1, y: 2 }
+        );
+        let e = from_str::<Tagged>("x=1&y=2").unwrap_err();
+        assert_eq!(e.code(), ErrorCode::MissingTag);
+        let e = from_str::<Tagged>("type=B").unwrap_err();
+        assert_eq!(e.code(), ErrorCode::UnknownVariant);
+        let e = from_str::<Tagged>("type=A&x=1&y=many").unwrap_err();
+        assert_eq!(e.code(), ErrorCode::InvalidType);
+    }
+
+    #[test]
+    fn unknown_fields_of_buffered_content_are_not_detected() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Page {
+            offset: usize,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Listing {
+            sort: String,
+            #[serde(flatten)]
+            page: Page,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Plain {
+            sort: String,
+        }
+
+        let e = parse::<Plain>("sort=a&extra=1", Options::STRICT).unwrap_err();
+        assert_eq!(e.unknown_fields(), ["extra"]);
+        // Serde buffers these, and drops the unknown keys itself.
+        assert!(parse::<Tagged>("type=A&x=1&y=2&extra=1", Options::STRICT).is_ok());
+        assert!(parse::<Listing>("sort=a&offset=1&extra=1", Options::STRICT).is_ok());
+    }
+
+    #[test]
+    fn duplicates_follow_the_policy() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct One {
+            a: String,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Many {
+            a: Vec<String>,
+            b: Vec<u32>,
+        }
+
+        let with = |duplicates| Options {
+            duplicates: Some(duplicates),
+            ..Default::default()
+        };
+        let one: One = parse("a=1&a=2", with(Duplicates::FirstWins)).unwrap();
+        assert_eq!(one.a, "1");
+        let one: One = parse("a=1&a=2", with(Duplicates::LastWins)).unwrap();
+        assert_eq!(one.a, "2");
+        let e = parse::<One>("a=1&a=2", with(Duplicates::Error)).unwrap_err();
+        assert_eq!(e.code(), ErrorCode::DuplicateField);
+        let many: Many = parse("a=1&b=2&a=3", with(Duplicates::Collect)).unwrap();
+        assert_eq!(many.a, ["1", "3"]);
+        assert_eq!(many.b, [2]);
+
+        let e = from_str::<One>("a=1&a=2").unwrap_err();
+        assert_eq!(e.code(), ErrorCode::DuplicateField);
+    }
+
+    #[test]
+    fn dot_notation_nests_structs_and_enums() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        #[serde(tag = "type")]
+        enum Kind {
+            Home { zip: u32 },
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct User {
+            name: String,
+            address: Kind,
+        }
+
+        let opts = Options {
+            dot_notation: true,
+            ..Default::default()
+        };
+        let user: User = parse("name=a&address.type=Home&address.zip=123", opts.clone()).unwrap();
+        assert_eq!(user.address, Kind::Home { zip: 123 });
+        let e = parse::<User>("name=a&address.type=Home", opts).unwrap_err();
+        assert_eq!(e.code(), ErrorCode::MissingField);
+    }
+
+    #[test]
+    fn limits_are_checked_before_deserializing() {
+        let opts = Options {
+            max_pairs: Some(2),
+            max_key_len: Some(3),
+            max_value_len: Some(4),
+            ..Default::default()
+        };
+        let parse = |input| parse::<HashMap<String, String>>(input, opts.clone());
+        assert_eq!(parse("a=1&b=2").unwrap().len(), 2);
+        let e = parse("a=1&b=2&c=3").unwrap_err();
+        assert_eq!(
+            (e.code(), e.limit()),
+            (ErrorCode::TooManyFields, Some(Limit::Pairs))
+        );
+        let e = parse("abcd=1").unwrap_err();
+        assert_eq!(e.limit(), Some(Limit::KeyLength));
+        let e = parse("a=12345").unwrap_err();
+        assert_eq!((e.limit(), e.path()), (Some(Limit::ValueLength), Some("a")));
+    }
+
+    #[test]
+    fn offsets_follow_both_separators() {
+        assert_eq!(key_offset(b"name=nail&count=many", "count"), Some(10));
+        assert_eq!(key_offset(b"name=nail;count=many", "count"), Some(10));
+        assert_eq!(key_offset(b"a=1;b=2&c=3", "c"), Some(8));
+        assert_eq!(key_offset(b"a=1;b=2", "d"), None);
+
+        let opts = Options {
+            semicolon_separator: true,
+            ..Default::default()
+        };
+        let input = b"count=1;size=many";
+        let pairs = crate::codec::decode_bytes(input, &opts);
+        let e = from_pairs_with::<HashMap<String, u32>>(pairs, opts);
+        let e = e.unwrap_err().locate(input);
+        assert_eq!((e.path(), e.offset()), (Some("size"), Some(8)));
+    }
+
+    #[test]
+    fn duplicates_keep_first_occurrence_order() {
+        let pairs = parse_pairs(b"a=1&b=2&a=3&c=4&b=5");
+        let grouped = group(&pairs, Some(Duplicates::LastWins)).unwrap();
+        let keys: Vec<_> = grouped.iter().map(|(k, v)| (&**k, &*v[0])).collect();
+        assert_eq!(keys, [("a", "3"), ("b", "5"), ("c", "4")]);
+    }
+
+    #[test]
+    fn dot_notation_keeps_repeated_keys() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Address {
+            street: String,
+            tags: crate::MultiSelect<String>,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct User {
+            tags: crate::MultiSelect<String>,
+            address: Address,
+        }
+
+        let opts = Options {
+            dot_notation: true,
+            ..Default::default()
+        };
+        let input = b"tags=a&address.street=Foo&tags=b&address.tags=c&address.tags=d";
+        let user: User = from_pairs_with(parse_pairs(input), opts.clone()).unwrap();
+        assert_eq!(*user.tags, ["a", "b"]);
+        assert_eq!(user.address.street, "Foo");
+        assert_eq!(*user.address.tags, ["c", "d"]);
+
+        let e = from_pairs_with::<User>(parse_pairs(b"address=x&address.street=Foo"), opts.clone());
+        assert_eq!(e.unwrap_err().code(), ErrorCode::ConflictingField);
+        let e = from_pairs_with::<User>(parse_pairs(b"address.street=Foo&address=x"), opts);
+        assert_eq!(e.unwrap_err().code(), ErrorCode::ConflictingField);
+    }
+}
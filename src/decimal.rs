@@ -0,0 +1,134 @@
+// Copyright (c) 2021 Cognite AS
+//! A form field for exact decimal numbers, such as amounts of money.
+//!
+//! This module is only available with the `decimal` feature enabled.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use rocket::form::{self, FromFormField, ValueField};
+use rust_decimal::Decimal;
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+/// The `SCALE` of a [`FormDecimal`] which accepts any number of decimal
+/// places.
+pub const ANY_SCALE: u32 = u32::MAX;
+
+/// The FormDecimal form field: a [`Decimal`] parsed from its text, such as
+/// `19.99`, without the rounding of going through a float.
+///
+/// With a `SCALE`, values with more than `SCALE` decimal places fail rather
+/// than being rounded, and values are scaled to exactly `SCALE` places, so
+/// that `FormDecimal<2>` reads `5` and `5.5` as `5.00` and `5.50` and rejects
+/// `5.555`. Trailing zeros beyond the scale are allowed. Without one, any
+/// value a [`Decimal`] holds exactly is accepted as submitted. Values which
+/// do not fit a [`Decimal`] fail in either case, as do exponents such as
+/// `1e3`.
+///
+/// FormDecimal deserializes with serde and is a [`FromFormField`]. It
+/// serializes as its text.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket_enumform::decimal::FormDecimal;
+/// use rocket_enumform::UrlEncoded;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Payment {
+///     amount: FormDecimal<2>,
+/// }
+///
+/// #[post("/pay", format = "form", data = "<form>")]
+/// fn pay(form: UrlEncoded<Payment>) -> String {
+///     form.amount.to_string()
+/// }
+///
+/// let payment: Payment = rocket_enumform::from_str("amount=19.9").unwrap();
+/// assert_eq!(payment.amount.to_string(), "19.90");
+/// assert!(rocket_enumform::from_str::<Payment>("amount=19.999").is_err());
+/// ```
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FormDecimal<const SCALE: u32 = ANY_SCALE>(pub Decimal);
+
+impl<const SCALE: u32> FormDecimal<SCALE> {
+    /// Consumes the FormDecimal wrapper and returns the wrapped item.
+    #[inline(always)]
+    pub fn into_inner(self) -> Decimal {
+        self.0
+    }
+
+    /// Parses `text` exactly, within `SCALE`.
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut value = Decimal::from_str_exact(text).map_err(|e| e.to_string())?;
+        if SCALE != ANY_SCALE {
+            if value.normalize().scale() > SCALE {
+                return Err(format!("more than {} decimal places", SCALE));
+            }
+            value.rescale(SCALE);
+        }
+        Ok(FormDecimal(value))
+    }
+
+    fn expected() -> String {
+        match SCALE {
+            ANY_SCALE => "a decimal number".into(),
+            _ => format!("a decimal number with at most {} decimal places", SCALE),
+        }
+    }
+}
+
+impl<'de, const SCALE: u32> Deserialize<'de> for FormDecimal<SCALE> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = <Cow<'de, str>>::deserialize(deserializer)?;
+        FormDecimal::parse(&text)
+            .map_err(|_| de::Error::invalid_value(de::Unexpected::Str(&text), &&*Self::expected()))
+    }
+}
+
+impl<const SCALE: u32> Serialize for FormDecimal<SCALE> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+#[rocket::async_trait]
+impl<'v, const SCALE: u32> FromFormField<'v> for FormDecimal<SCALE> {
+    fn from_value(field: ValueField<'v>) -> form::Result<'v, Self> {
+        FormDecimal::parse(field.value).map_err(|e| {
+            let msg = format!("expected {}: {}", Self::expected(), e);
+            form::Error::validation(msg).into()
+        })
+    }
+}
+
+impl<const SCALE: u32> fmt::Display for FormDecimal<SCALE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<const SCALE: u32> From<Decimal> for FormDecimal<SCALE> {
+    fn from(value: Decimal) -> Self {
+        FormDecimal(value)
+    }
+}
+
+impl<const SCALE: u32> Deref for FormDecimal<SCALE> {
+    type Target = Decimal;
+
+    #[inline(always)]
+    fn deref(&self) -> &Decimal {
+        &self.0
+    }
+}
+
+impl<const SCALE: u32> DerefMut for FormDecimal<SCALE> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Decimal {
+        &mut self.0
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright (c) 2021 Cognite AS
+//! A form field for lists joined into a single value.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use rocket::form::{self, FromFormField, ValueField};
+use serde::de::{self, Deserialize, DeserializeOwned, Deserializer, SeqAccess, Visitor};
+use serde::ser::{self, Serialize, Serializer};
+
+/// The Delimited field: a list submitted as a single value, its items joined
+/// by `SEP`, such as `ids=1,2,3`.
+///
+/// The value is split at every `SEP` and each item is parsed as a `T`, like a
+/// single value of [`UrlEncoded`](crate::UrlEncoded) is; an empty value is an
+/// empty list. Values of a key submitted more than once, as collected by
+/// [`Duplicates::Collect`](crate::de::Duplicates::Collect), are split and
+/// concatenated. Items are serialized as text and joined by `SEP`, failing if
+/// an item contains it.
+///
+/// Delimited deserializes with serde in [`UrlEncoded`](crate::UrlEncoded)
+/// and other bodies, and is a [`FromFormField`] for Rocket's forms and
+/// queries.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::{Deserialize, Serialize};
+/// use rocket_enumform::Delimited;
+///
+/// #[derive(Debug, Deserialize, Serialize)]
+/// struct Batch {
+///     ids: Delimited<Vec<u32>>,
+///     fields: Delimited<Vec<String>, '|'>,
+/// }
+///
+/// let batch: Batch = rocket_enumform::from_str("ids=1,2,3&fields=a|b").unwrap();
+/// assert_eq!(*batch.ids, [1, 2, 3]);
+/// assert_eq!(*batch.fields, ["a", "b"]);
+/// assert_eq!(
+///     rocket_enumform::to_string(&batch).unwrap(),
+///     "ids=1%2C2%2C3&fields=a%7Cb"
+/// );
+///
+/// #[get("/items?<ids>")]
+/// fn items(ids: Delimited<Vec<u32>>) -> String {
+///     format!("{:?}", ids.into_inner())
+/// }
+/// ```
+#[repr(transparent)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Delimited<T, const SEP: char = ','>(pub T);
+
+impl<T, const SEP: char> Delimited<T, SEP> {
+    /// Consumes the Delimited wrapper and returns the wrapped list.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T, const SEP: char> From<T> for Delimited<T, SEP> {
+    fn from(list: T) -> Self {
+        Delimited(list)
+    }
+}
+
+impl<T, const SEP: char> Deref for Delimited<T, SEP> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T, const SEP: char> DerefMut for Delimited<T, SEP> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+/// Splits `value` at `sep` and parses the items into `items`.
+fn split<T, E>(items: &mut Vec<T>, value: &str, sep: char) -> Result<(), E>
+where
+    T: DeserializeOwned,
+    E: de::Error,
+{
+    if value.is_empty() {
+        return Ok(());
+    }
+    for item in value.split(sep) {
+        let item = T::deserialize(crate::de::text(item, false)).map_err(E::custom)?;
+        items.push(item);
+    }
+    Ok(())
+}
+
+impl<'de, T: DeserializeOwned, const SEP: char> Deserialize<'de> for Delimited<Vec<T>, SEP> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(DelimitedVisitor(PhantomData))
+    }
+}
+
+struct DelimitedVisitor<T, const SEP: char>(PhantomData<T>);
+
+impl<'de, T: DeserializeOwned, const SEP: char> Visitor<'de> for DelimitedVisitor<T, SEP> {
+    type Value = Delimited<Vec<T>, SEP>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a list separated by `{}`", SEP)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        let mut items = Vec::new();
+        split(&mut items, v, SEP)?;
+        Ok(Delimited(items))
+    }
+
+    /// Single items, as buffered by serde with inferred types.
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    /// The values of a repeated key.
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut items = Vec::new();
+        while let Some(value) = seq.next_element::<String>()? {
+            split(&mut items, &value, SEP)?;
+        }
+        Ok(Delimited(items))
+    }
+}
+
+impl<T: Serialize, const SEP: char> Serialize for Delimited<Vec<T>, SEP> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut joined = String::new();
+        for (i, item) in self.0.iter().enumerate() {
+            let item = crate::ser::to_text(item).map_err(ser::Error::custom)?;
+            if item.contains(SEP) {
+                let msg = format!("item `{}` contains the separator `{}`", item, SEP);
+                return Err(ser::Error::custom(msg));
+            }
+            if i > 0 {
+                joined.push(SEP);
+            }
+            joined.push_str(&item);
+        }
+        serializer.serialize_str(&joined)
+    }
+}
+
+#[rocket::async_trait]
+impl<'v, T, const SEP: char> FromFormField<'v> for Delimited<Vec<T>, SEP>
+where
+    T: DeserializeOwned + Send,
+{
+    fn from_value(field: ValueField<'v>) -> form::Result<'v, Self> {
+        let mut items = Vec::new();
+        split::<_, crate::de::Error>(&mut items, field.value, SEP)
+            .map_err(|e| form::Error::validation(e.to_string()))?;
+        Ok(Delimited(items))
+    }
+}
@@ -0,0 +1,82 @@
+// Copyright (c) 2021 Cognite AS
+//! Runtime support for the code generated by `#[derive(EnumForm)]` and
+//! `#[derive(FormFieldEnum)]`.
+//!
+//! Not public API.
+
+pub use std::borrow::Cow;
+
+use rocket::form::name::{Name, NameView};
+use rocket::form::{DataField, FromFormField, Options, ValueField};
+
+pub use rocket::async_trait;
+pub use rocket::form;
+pub use rocket::http::impl_from_uri_param_identity;
+pub use rocket::http::uri::fmt as uri;
+pub use serde::Deserialize;
+
+use crate::de;
+
+/// The `FromForm` context of a derived enum: the fields pushed so far, as
+/// key/value pairs for [`de::from_pairs`].
+pub struct Context<'r> {
+    parent: Option<&'r Name>,
+    pairs: de::Pairs<'r>,
+    errors: form::Errors<'r>,
+}
+
+/// The name of a field relative to the enum it was pushed to, with the keys
+/// of any deeper nesting joined by `.`.
+fn relative_name(mut name: NameView<'_>) -> Cow<'_, str> {
+    let first = name.key_lossy().as_str();
+    name.shift();
+    if name.key().is_none() {
+        return Cow::Borrowed(first);
+    }
+
+    let mut joined = first.to_string();
+    while let Some(key) = name.key() {
+        joined.push('.');
+        joined.push_str(key.as_str());
+        name.shift();
+    }
+    Cow::Owned(joined)
+}
+
+impl<'r> Context<'r> {
+    pub fn new(_opts: Options) -> Self {
+        Context {
+            parent: None,
+            pairs: Vec::new(),
+            errors: form::Errors::new(),
+        }
+    }
+
+    pub fn push_value(&mut self, field: ValueField<'r>) {
+        self.parent = field.name.parent();
+        let name = relative_name(field.name);
+        self.pairs.push((name, Cow::Borrowed(field.value)));
+    }
+
+    pub async fn push_data(&mut self, field: DataField<'r, '_>) {
+        let name = field.name;
+        self.parent = name.parent();
+        match <String as FromFormField<'r>>::from_data(field).await {
+            Ok(value) => self.pairs.push((relative_name(name), Cow::Owned(value))),
+            Err(e) => self.errors.extend(e.with_name(name)),
+        }
+    }
+
+    pub fn push_error(&mut self, error: form::Error<'r>) {
+        self.errors.push(error);
+    }
+
+    pub fn finalize<T: Deserialize<'r>>(self) -> form::Result<'r, T> {
+        if !self.errors.is_empty() {
+            return Err(self.errors);
+        }
+
+        de::from_pairs(self.pairs.clone())
+            .map_err(|e| crate::form_error(e, &self.pairs, self.parent).into())
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright (c) 2021 Cognite AS
+//! Representative values of forms, to document what clients send.
+
+use serde::Serialize;
+
+use crate::ser;
+
+/// Types with representative values: one per variant of an enum, or a single
+/// one of a struct, named after it.
+///
+/// Their urlencoded forms, from [`form_examples()`](FormExample::form_examples),
+/// document exactly what clients send, for instance as the examples of
+/// request bodies in OpenAPI documents, and seed tests with valid bodies.
+///
+/// With the `derive` feature enabled, `#[derive(FormExample)]` builds each
+/// example from the `#[form_example(...)]` attributes of the fields, whose
+/// expression is converted with [`Into`], or from their [`Default`], naming
+/// the variants as serde serializes them.
+///
+/// ```rust
+/// # #[cfg(feature = "derive")] {
+/// use rocket_enumform::FormExample;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize, FormExample)]
+/// #[serde(tag = "type", rename_all = "snake_case")]
+/// enum Body {
+///     VariantOne {
+///         #[form_example("hello")]
+///         content_one: String,
+///     },
+///     VariantTwo {
+///         #[form_example(42u32)]
+///         content_two: u64,
+///         flag: bool,
+///     },
+/// }
+///
+/// assert_eq!(
+///     Body::form_examples().unwrap(),
+///     [
+///         ("variant_one", "type=variant_one&content_one=hello".to_string()),
+///         ("variant_two", "type=variant_two&content_two=42&flag=false".to_string()),
+///     ]
+/// );
+/// # }
+/// ```
+pub trait FormExample: Sized {
+    /// The representative values, with the names of their variants.
+    fn examples() -> Vec<(&'static str, Self)>;
+
+    /// The [`examples()`](FormExample::examples), urlencoded.
+    ///
+    /// # Errors
+    ///
+    /// Fails as [`to_string`](crate::to_string) does, if an example cannot be
+    /// serialized as a form.
+    fn form_examples() -> Result<Vec<(&'static str, String)>, ser::Error>
+    where
+        Self: Serialize,
+    {
+        Self::examples()
+            .into_iter()
+            .map(|(name, value)| Ok((name, crate::to_string(&value)?)))
+            .collect()
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright (c) 2021 Cognite AS
+//! Access to the failure of a guard from catchers.
+
+use std::fmt;
+
+use rocket::http::Status;
+use rocket::request::{self, FromRequest, Request};
+
+use crate::{Error, ErrorCode};
+
+/// An owned summary of the error a guard of this crate failed with.
+///
+/// When a guard fails, the summary is stored in the request-local cache so
+/// that the error catcher for the resulting status can render the actual
+/// deserialization message. Catchers only receive the request: retrieve the
+/// summary with [`Error::from_request()`], or with `&ParseFailure` as a request
+/// guard.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::Request;
+/// use rocket_enumform::{Error, ParseFailure};
+///
+/// #[catch(422)]
+/// fn unprocessable(req: &Request<'_>) -> String {
+///     match Error::from_request(req) {
+///         Some(failure) => format!("invalid form: {}", failure),
+///         None => "invalid request".into(),
+///     }
+/// }
+///
+/// #[catch(413)]
+/// async fn too_large(req: &Request<'_>) -> String {
+///     let failure = req.guard::<&ParseFailure>().await;
+///     format!("{:?}", failure.succeeded().map(|f| f.message()))
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFailure {
+    status: Status,
+    code: ErrorCode,
+    message: String,
+    field: Option<String>,
+}
+
+impl ParseFailure {
+    /// The status the guard failed with.
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// What went wrong, see [`ErrorCode`].
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    /// A human-readable description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The field which failed to deserialize, if known.
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+}
+
+impl fmt::Display for ParseFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "{}: {}", field, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Keeps the first failure of the request.
+pub(crate) fn store(
+    req: &Request<'_>,
+    status: Status,
+    code: ErrorCode,
+    message: String,
+    field: Option<String>,
+) {
+    req.local_cache(|| {
+        Some(ParseFailure {
+            status,
+            code,
+            message,
+            field,
+        })
+    });
+}
+
+/// Keeps `e` as the failure of the request, unless there already is one.
+pub(crate) fn store_error(req: &Request<'_>, e: &Error<'_>) {
+    let status = crate::error_status(req, e.status());
+    match e {
+        Error::Parse(_, de) => store(
+            req,
+            status,
+            e.code(),
+            de.to_string(),
+            de.path().map(String::from),
+        ),
+        Error::Io(io) => store(req, status, e.code(), io.to_string(), None),
+        Error::TooLarge { .. }
+        | Error::ContentType(_)
+        | Error::ContentEncoding(_)
+        | Error::Csrf
+        | Error::Signature => store(req, status, e.code(), e.to_string(), None),
+        #[cfg(feature = "validator")]
+        Error::Validation(errors) => {
+            let errors = crate::validated::field_errors(errors);
+            let field = errors.into_iter().find_map(|e| e.name);
+            store(req, status, e.code(), e.to_string(), field)
+        }
+    }
+}
+
+impl<'a> Error<'a> {
+    /// The failure of a guard of this crate during `req`, if any.
+    pub fn from_request<'r>(req: &'r Request<'_>) -> Option<&'r ParseFailure> {
+        req.local_cache(|| None::<ParseFailure>).as_ref()
+    }
+}
+
+/// Succeeds with the failure of an earlier guard of this crate, if any, and
+/// forwards with `Status::NotFound` otherwise.
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for &'r ParseFailure {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        match Error::from_request(req) {
+            Some(failure) => request::Outcome::Success(failure),
+            None => request::Outcome::Forward(Status::NotFound),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright (c) 2021 Cognite AS
+//! Flashing the errors of a form across a redirect.
+
+use std::collections::BTreeMap;
+use std::convert::{Infallible, TryInto};
+
+use rocket::http::uri::Reference;
+use rocket::request::{self, FlashMessage, FromRequest, Request};
+use rocket::response::{Flash, Redirect};
+use rocket::warn_;
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, FieldError, FormContext};
+
+/// The kind of the flash messages of [`FlashedForm`].
+const KIND: &str = "enumform";
+
+/// The FlashedForm request guard and flash message: the errors of a failed
+/// form, and optionally its submitted values, carried to the page redirected
+/// to in a [`Flash`] cookie, for post/redirect/get flows.
+///
+/// A POST handler packs the errors of a [`FormContext`] or an [`Error`] with
+/// `From`, adds the submitted values with [`with_values`](Self::with_values)
+/// and responds with [`redirect`](Self::redirect). The GET handler redirected
+/// to takes a `FlashedForm` guard, which never fails: without a flashed form
+/// it is empty, and the page renders blank.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket::http::{ContentType, Status};
+/// use rocket::local::blocking::Client;
+/// use rocket::response::{Flash, Redirect};
+/// use rocket_enumform::{ContextualUrlEncoded, FlashedForm};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Signup {
+///     email: String,
+///     age: u8,
+/// }
+///
+/// #[get("/signup")]
+/// fn form(flashed: FlashedForm) -> String {
+///     let errors: Vec<_> = flashed.field_errors("age").map(|e| &*e.message).collect();
+///     format!("age={:?} {:?}", flashed.field_value("age").unwrap_or(""), errors)
+/// }
+///
+/// #[post("/signup", data = "<signup>")]
+/// fn signup(signup: ContextualUrlEncoded<Signup>) -> Result<String, Flash<Redirect>> {
+///     match signup.value {
+///         Some(signup) => Ok(signup.email),
+///         None => Err(FlashedForm::from(&signup.context)
+///             .with_values(&signup.context)
+///             .redirect(uri!(form))),
+///     }
+/// }
+///
+/// let client = Client::tracked(rocket::build().mount("/", routes![form, signup])).unwrap();
+/// let request = client.post("/signup").header(ContentType::Form);
+/// let response = request.body("email=a%40b.c&age=old").dispatch();
+/// assert_eq!(response.status(), Status::SeeOther);
+/// assert_eq!(response.headers().get_one("Location"), Some("/signup"));
+///
+/// let page = client.get("/signup").dispatch().into_string().unwrap();
+/// assert!(page.starts_with("age=\"old\" [\"invalid value"), "{}", page);
+/// // The flash is gone once read.
+/// assert_eq!(client.get("/signup").dispatch().into_string().unwrap(), "age=\"\" []");
+/// ```
+///
+/// The flash travels in a cookie which is neither signed nor encrypted, and
+/// browsers drop cookies over about 4 KiB: values of sensitive or long
+/// fields are best left out with [`without_value`](Self::without_value). It
+/// serializes like [`FormContext`], for templates.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FlashedForm {
+    values: BTreeMap<String, Vec<String>>,
+    errors: Vec<FieldError>,
+}
+
+impl FlashedForm {
+    /// Adds the values submitted with `context`.
+    pub fn with_values(mut self, context: &FormContext) -> Self {
+        for (name, values) in context.values() {
+            self.values.insert(name.to_string(), values.to_vec());
+        }
+        self
+    }
+
+    /// Leaves out the values of the field `name`, such as a password.
+    pub fn without_value(mut self, name: &str) -> Self {
+        self.values.remove(name);
+        self
+    }
+
+    /// A `303 See Other` redirect to `uri`, flashing the form.
+    ///
+    /// Like with [`Redirect::to`], an invalid `uri` is only reported when
+    /// responding.
+    pub fn redirect<U: TryInto<Reference<'static>>>(self, uri: U) -> Flash<Redirect> {
+        let message = serde_json::to_string(&self).expect("form serializes to JSON");
+        Flash::new(Redirect::to(uri), KIND, message)
+    }
+
+    /// Whether no form was flashed, or it had neither errors nor values.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty() && self.errors.is_empty()
+    }
+
+    /// The first value flashed for the field `name`, if any.
+    pub fn field_value(&self, name: &str) -> Option<&str> {
+        self.field_values(name).next()
+    }
+
+    /// All values flashed for the field `name`, in submission order.
+    pub fn field_values<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a str> + 'a {
+        self.values
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    /// The errors for the field `name`.
+    pub fn field_errors<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a FieldError> + 'a {
+        self.errors
+            .iter()
+            .filter(move |e| e.name.as_deref() == Some(name))
+    }
+
+    /// All errors, including those without a field name.
+    pub fn errors(&self) -> impl Iterator<Item = &FieldError> {
+        self.errors.iter()
+    }
+}
+
+impl From<&FormContext> for FlashedForm {
+    /// The errors of `context`, without its values.
+    fn from(context: &FormContext) -> Self {
+        FlashedForm {
+            values: BTreeMap::new(),
+            errors: context.errors().cloned().collect(),
+        }
+    }
+}
+
+impl From<&Error<'_>> for FlashedForm {
+    /// The errors of `e`, by field where known.
+    fn from(e: &Error<'_>) -> Self {
+        FlashedForm {
+            values: BTreeMap::new(),
+            errors: FieldError::from_error(e),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for FlashedForm {
+    type Error = Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let flash = match req.guard::<Option<FlashMessage<'_>>>().await {
+            request::Outcome::Success(Some(flash)) if flash.kind() == KIND => flash,
+            _ => return request::Outcome::Success(FlashedForm::default()),
+        };
+        let form = serde_json::from_str(flash.message()).unwrap_or_else(|e| {
+            warn_!("Ignoring a malformed flashed form: {}", e);
+            FlashedForm::default()
+        });
+        request::Outcome::Success(form)
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright (c) 2021 Cognite AS
+//! Error messages in the languages of clients, with
+//! [Fluent](https://projectfluent.org).
+//!
+//! A [`Catalog`] holds a message per [`ErrorCode`], in English and in the
+//! languages added to it, and picks the language of a request from its
+//! `Accept-Language` header. Once managed, it localizes the `detail` of the
+//! problem details responses of [`Error`]:
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! # use serde::Deserialize;
+//! use rocket::http::{ContentType, Header};
+//! use rocket::local::blocking::Client;
+//! use rocket_enumform::fluent::Catalog;
+//! use rocket_enumform::{Error, UrlEncoded};
+//!
+//! #[derive(Debug, Deserialize)]
+//! struct User {
+//!     name: String,
+//! }
+//!
+//! #[post("/user", data = "<user>")]
+//! fn new_user(user: Result<UrlEncoded<User>, Error<'_>>) -> Result<String, Error<'_>> {
+//!     Ok(user?.into_inner().name)
+//! }
+//!
+//! let french = "missing_field = Un champ obligatoire manque.
+//!     .field = Le champ { $field } est obligatoire.
+//! ";
+//! let catalog = Catalog::new().with_messages("fr".parse().unwrap(), french).unwrap();
+//! let rocket = rocket::build().manage(catalog).mount("/", routes![new_user]);
+//! let client = Client::untracked(rocket).unwrap();
+//! let request = client.post("/user").header(ContentType::Form);
+//! let request = request.header(Header::new("Accept-Language", "fr-CH, fr;q=0.9, en;q=0.8"));
+//! let problem: serde_json::Value = request.body("").dispatch().into_json().unwrap();
+//! assert_eq!(problem["detail"], "Le champ name est obligatoire.");
+//! assert_eq!(problem["code"], "missing_field");
+//! ```
+//!
+//! This module is only available with the `fluent` feature enabled.
+
+use std::fmt;
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentError, FluentResource};
+use rocket::request::Request;
+use unic_langid::LanguageIdentifier;
+
+use crate::{Error, ErrorCode, ParseFailure};
+
+/// The English messages of a new [`Catalog`], in Fluent syntax, as a
+/// template for translations.
+///
+/// The identifier of the message of an [`ErrorCode`] is the code in
+/// `snake_case`. Where the failing field is known, its `field` attribute is
+/// used instead of its value, if it has one. Messages receive the variables
+/// `$field`, the field, and `$expected`, what serde expected instead of the
+/// value, where they are known.
+pub const ENGLISH: &str = "\
+io = The form could not be read.
+body_too_large = The form is too large.
+unsupported_content_type = The form was sent in an unsupported format.
+unsupported_content_encoding = The form was sent with an unsupported compression.
+invalid_csrf_token = The form has expired, please submit it again.
+invalid_signature = The signature of the request is missing, invalid or expired.
+missing_tag = The kind of form is missing.
+    .field = The kind of form, { $field }, is missing.
+unknown_variant = The kind of form is unknown.
+    .field = The kind of form, { $field }, is unknown.
+missing_field = A required field is missing.
+    .field = The field { $field } is required.
+unknown_field = The form has an unknown field.
+    .field = The field { $field } is unknown.
+duplicate_field = A field was submitted more than once.
+    .field = The field { $field } was submitted more than once.
+conflicting_field = A field conflicts with the fields nested in it.
+    .field = The field { $field } conflicts with the fields nested in it.
+invalid_type = A field has a value of the wrong type.
+    .field = The field { $field } has a value of the wrong type.
+invalid_value = A field has an invalid value.
+    .field = The field { $field } has an invalid value.
+invalid_length = A field has the wrong number of values.
+    .field = The field { $field } has the wrong number of values.
+too_many_fields = The form has too many fields.
+key_too_long = The name of a field is too long.
+value_too_long = The value of a field is too long.
+    .field = The value of { $field } is too long.
+validation_failed = Some fields have invalid values.
+    .field = The field { $field } has an invalid value.
+invalid = The form is invalid.
+";
+
+/// Messages for the [`ErrorCode`]s, in English and in the languages added
+/// with [`with_messages`](Catalog::with_messages).
+///
+/// A message is looked up in the languages a request accepts, in order of
+/// preference, matching either the language and region or the language
+/// alone, then in English.
+pub struct Catalog {
+    /// The bundles of the languages, English first.
+    bundles: Vec<FluentBundle<FluentResource>>,
+}
+
+impl Catalog {
+    /// A catalog of the [`ENGLISH`] messages.
+    pub fn new() -> Self {
+        let english = "en".parse().expect("valid language identifier");
+        Catalog {
+            bundles: Vec::new(),
+        }
+        .with_messages(english, ENGLISH)
+        .expect("valid English messages")
+    }
+
+    /// Adds the messages of `ftl`, in Fluent syntax, for `locale`, replacing
+    /// those with the same identifiers, such as English ones.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `ftl` is not valid Fluent syntax.
+    pub fn with_messages(
+        mut self,
+        locale: LanguageIdentifier,
+        ftl: &str,
+    ) -> Result<Self, Vec<FluentError>> {
+        let resource = FluentResource::try_new(ftl.to_string()).map_err(|(_, errors)| {
+            errors
+                .into_iter()
+                .map(FluentError::from)
+                .collect::<Vec<_>>()
+        })?;
+        let bundle = match self.bundles.iter_mut().find(|b| b.locales[0] == locale) {
+            Some(bundle) => bundle,
+            None => {
+                let mut bundle = FluentBundle::new_concurrent(vec![locale]);
+                bundle.set_use_isolating(false);
+                self.bundles.push(bundle);
+                self.bundles.last_mut().expect("just pushed")
+            }
+        };
+        bundle.add_resource_overriding(resource);
+        Ok(self)
+    }
+
+    /// The message of `code` in the first of `locales` which has one, or in
+    /// English, with the failing `field` and what was `expected`, if known.
+    pub fn message(
+        &self,
+        locales: &[LanguageIdentifier],
+        code: ErrorCode,
+        field: Option<&str>,
+        expected: Option<&str>,
+    ) -> String {
+        let mut args = FluentArgs::new();
+        if let Some(field) = field {
+            args.set("field", field);
+        }
+        if let Some(expected) = expected {
+            args.set("expected", expected);
+        }
+
+        let mut errors = Vec::new();
+        for bundle in self.negotiate(locales) {
+            let message = match bundle.get_message(code.as_str()) {
+                Some(message) => message,
+                None => continue,
+            };
+            let attribute = field.and_then(|_| message.get_attribute("field"));
+            let pattern = attribute.map(|a| a.value()).or_else(|| message.value());
+            if let Some(pattern) = pattern {
+                return bundle
+                    .format_pattern(pattern, Some(&args), &mut errors)
+                    .into_owned();
+            }
+        }
+        code.to_string()
+    }
+
+    /// The message of `e` in the language `req` accepts.
+    pub fn localize(&self, req: &Request<'_>, e: &Error<'_>) -> String {
+        let (field, expected) = match e {
+            Error::Parse(_, e) => (e.path(), e.expected()),
+            _ => (None, None),
+        };
+        self.message(&accepted_languages(req), e.code(), field, expected)
+    }
+
+    /// The message of the failure of a guard, for catchers, in the language
+    /// `req` accepts.
+    pub fn localize_failure(&self, req: &Request<'_>, failure: &ParseFailure) -> String {
+        let locales = accepted_languages(req);
+        self.message(&locales, failure.code(), failure.field(), None)
+    }
+
+    /// The bundles for `locales`, in order of preference, then English.
+    fn negotiate<'a>(
+        &'a self,
+        locales: &'a [LanguageIdentifier],
+    ) -> impl Iterator<Item = &'a FluentBundle<FluentResource>> + 'a {
+        let exact = move |locale: &'a LanguageIdentifier| {
+            self.bundles
+                .iter()
+                .find(move |b| b.locales[0].matches(locale, true, true))
+        };
+        let language = move |locale: &'a LanguageIdentifier| {
+            self.bundles
+                .iter()
+                .find(move |b| b.locales[0].language == locale.language)
+        };
+        locales
+            .iter()
+            .flat_map(move |locale| exact(locale).or_else(|| language(locale)))
+            .chain(self.bundles.first())
+    }
+}
+
+impl Default for Catalog {
+    fn default() -> Self {
+        Catalog::new()
+    }
+}
+
+impl fmt::Debug for Catalog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let locales: Vec<_> = self.bundles.iter().map(|b| &b.locales[0]).collect();
+        f.debug_struct("Catalog")
+            .field("locales", &locales)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The languages of the `Accept-Language` header of `req`, in order of
+/// preference. Malformed and wildcard ranges are left out.
+///
+/// ```rust
+/// use rocket::http::Header;
+/// use rocket::local::blocking::Client;
+/// use rocket_enumform::fluent::accepted_languages;
+///
+/// let client = Client::untracked(rocket::build()).unwrap();
+/// let request = client.get("/").header(Header::new("Accept-Language", "de;q=0.5, fr-CH, *"));
+/// let languages = accepted_languages(request.inner());
+/// let languages: Vec<_> = languages.iter().map(ToString::to_string).collect();
+/// assert_eq!(languages, ["fr-CH", "de"]);
+/// ```
+pub fn accepted_languages(req: &Request<'_>) -> Vec<LanguageIdentifier> {
+    let mut ranges: Vec<(f32, LanguageIdentifier)> = req
+        .headers()
+        .get("Accept-Language")
+        .flat_map(|header| header.split(','))
+        .filter_map(|range| {
+            let mut params = range.split(';');
+            let locale = params.next()?.trim().parse().ok()?;
+            let quality = params
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse().ok())
+                .unwrap_or(1.0);
+            (quality > 0.0).then_some((quality, locale))
+        })
+        .collect();
+    // Stable, keeping the order of ranges of equal quality.
+    ranges.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+    ranges.into_iter().map(|(_, locale)| locale).collect()
+}
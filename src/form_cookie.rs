@@ -0,0 +1,220 @@
+// Copyright (c) 2021 Cognite AS
+//! Typed values kept form-encoded in cookies.
+
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use rocket::http::{Cookie, CookieJar, Status};
+use rocket::request::{self, FromRequest, Request};
+use serde::{Deserialize, Serialize};
+
+use crate::{de, guard_failure, ser, Error};
+
+/// The cookie a [`FormCookie`] is kept in.
+///
+/// ```rust
+/// use rocket::http::{Cookie, SameSite};
+/// use rocket_enumform::CookieSpec;
+///
+/// struct Checkout;
+///
+/// impl CookieSpec for Checkout {
+///     const NAME: &'static str = "checkout";
+///
+///     fn cookie(value: String) -> Cookie<'static> {
+///         Cookie::build((Self::NAME, value))
+///             .path("/checkout")
+///             .same_site(SameSite::Lax)
+///             .build()
+///     }
+/// }
+/// ```
+pub trait CookieSpec {
+    /// The name of the cookie.
+    const NAME: &'static str;
+
+    /// The maximum length of the cookie in bytes, as sent in its `Set-Cookie`
+    /// header with its attributes. Defaults to 4096, the least browsers
+    /// accept.
+    const MAX_LEN: usize = 4096;
+
+    /// The cookie holding the encoded `value`. Defaults to a cookie with
+    /// Rocket's defaults, `Path=/` and `SameSite=Strict`.
+    fn cookie(value: String) -> Cookie<'static> {
+        Cookie::new(Self::NAME, value)
+    }
+}
+
+/// The FormCookie guard: a value kept form-encoded in the cookie of `S`, such
+/// as the state of a multi-step form.
+///
+/// [`add_to`](FormCookie::add_to) serializes the value into the cookie with
+/// [`ser::to_string`], failing instead of setting a cookie longer than
+/// [`CookieSpec::MAX_LEN`]. As a request guard, FormCookie deserializes the
+/// cookie with [`de::from_str`], and forwards with `Status::NotFound` if the
+/// request has no such cookie. The options of
+/// [`EnumFormConfig`](crate::EnumFormConfig), which are those of the wire
+/// format, do not apply.
+///
+/// The cookie is not encrypted or signed: clients can read and change it,
+/// and its value must be validated like any other input.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::{Deserialize, Serialize};
+/// use rocket::http::CookieJar;
+/// use rocket_enumform::{CookieSpec, FormCookie, UrlEncoded};
+///
+/// struct Wizard;
+///
+/// impl CookieSpec for Wizard {
+///     const NAME: &'static str = "wizard";
+/// }
+///
+/// #[derive(Debug, Deserialize, Serialize)]
+/// struct Step1 {
+///     name: String,
+/// }
+///
+/// #[post("/step1", data = "<step>")]
+/// fn step1(step: UrlEncoded<Step1>, jar: &CookieJar<'_>) -> Result<(), String> {
+///     FormCookie::<_, Wizard>::new(step.into_inner())
+///         .add_to(jar)
+///         .map_err(|e| e.to_string())
+/// }
+///
+/// #[post("/step2")]
+/// fn step2(step1: FormCookie<Step1, Wizard>, jar: &CookieJar<'_>) -> String {
+///     FormCookie::<Step1, Wizard>::remove(jar);
+///     format!("done, {}", step1.name)
+/// }
+/// ```
+pub struct FormCookie<T, S>(pub T, PhantomData<S>);
+
+impl<T, S> FormCookie<T, S> {
+    /// Wraps `value`, to keep it in the cookie of `S`.
+    #[inline(always)]
+    pub fn new(value: T) -> Self {
+        FormCookie(value, PhantomData)
+    }
+
+    /// Consumes the FormCookie wrapper and returns the wrapped item.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T, S: CookieSpec> FormCookie<T, S> {
+    /// Removes the cookie of `S` from `jar`.
+    pub fn remove(jar: &CookieJar<'_>) {
+        jar.remove(S::cookie(String::new()));
+    }
+}
+
+impl<T: Serialize, S: CookieSpec> FormCookie<T, S> {
+    /// Serializes the value into the cookie of `S` and adds it to `jar`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the value cannot be serialized, or if the cookie would be
+    /// longer than [`CookieSpec::MAX_LEN`], without adding it.
+    pub fn add_to(&self, jar: &CookieJar<'_>) -> Result<(), ser::Error> {
+        jar.add(checked_cookie::<S>(ser::to_string(&self.0)?, false)?);
+        Ok(())
+    }
+}
+
+/// The cookie of `S` holding `value`, unless it is longer than
+/// [`CookieSpec::MAX_LEN`], with `value` encrypted if `private`.
+pub(crate) fn checked_cookie<S: CookieSpec>(
+    value: String,
+    private: bool,
+) -> Result<Cookie<'static>, ser::Error> {
+    let mut cookie = S::cookie(value);
+    let len = match private {
+        false => cookie.encoded().to_string().len(),
+        true => {
+            // Private cookies hold the base64 of a 12 byte nonce, the
+            // encrypted value and a 16 byte tag, padded with `=`.
+            let value = cookie.value().to_string();
+            let encrypted = value.len() + 28;
+            let padding = (3 - encrypted % 3) % 3;
+            let base64 = "A".repeat(encrypted.div_ceil(3) * 4 - padding) + &"=".repeat(padding);
+            cookie.set_value(base64);
+            let len = cookie.encoded().to_string().len();
+            cookie.set_value(value);
+            len
+        }
+    };
+    if len > S::MAX_LEN {
+        return Err(ser::Error::Custom(
+            format!(
+                "cookie `{}` of {} bytes exceeds the limit of {} bytes",
+                S::NAME,
+                len,
+                S::MAX_LEN
+            )
+            .into(),
+        ));
+    }
+    Ok(cookie)
+}
+
+#[rocket::async_trait]
+impl<'r, T, S> FromRequest<'r> for FormCookie<T, S>
+where
+    T: Deserialize<'r>,
+    S: CookieSpec,
+{
+    type Error = Error<'r>;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let value = match req.cookies().get(S::NAME) {
+            Some(cookie) => cookie.value(),
+            None => return request::Outcome::Forward(Status::NotFound),
+        };
+        match de::from_str(value) {
+            Ok(value) => request::Outcome::Success(FormCookie::new(value)),
+            Err(e) => {
+                let e = guard_failure(req, Error::Parse(value, e));
+                request::Outcome::Error((crate::error_status(req, e.status()), e))
+            }
+        }
+    }
+}
+
+impl<T, S> From<T> for FormCookie<T, S> {
+    fn from(value: T) -> Self {
+        FormCookie::new(value)
+    }
+}
+
+impl<T, S> Deref for FormCookie<T, S> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T, S> DerefMut for FormCookie<T, S> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+// Implemented by hand, as derives would require them of `S`.
+impl<T: Clone, S> Clone for FormCookie<T, S> {
+    fn clone(&self) -> Self {
+        FormCookie::new(self.0.clone())
+    }
+}
+
+impl<T: std::fmt::Debug, S> std::fmt::Debug for FormCookie<T, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("FormCookie").field(&self.0).finish()
+    }
+}
@@ -0,0 +1,152 @@
+// Copyright (c) 2021 Cognite AS
+//! Hidden fields which trap bots filling in every field of a form.
+
+use std::ops::{Deref, DerefMut};
+
+use rocket::data::{Data, FromData, Outcome};
+use rocket::http::ContentType;
+use rocket::info_;
+use rocket::request::Request;
+use serde::de::Error as _;
+use serde::Deserialize;
+
+use crate::{codec, de, EnumFormConfig, Error, HoneypotAction};
+
+/// The field checked when [`EnumFormConfig::honeypot_fields`] names none.
+const DEFAULT_FIELD: &str = "website";
+
+/// The Honeypot guard: [`UrlEncoded`](crate::UrlEncoded) for public forms
+/// with hidden fields which people leave empty and bots fill in, such as a
+/// contact form with a `website` input hidden by CSS.
+///
+/// Each field of [`EnumFormConfig::honeypot_fields`], or `website` if it
+/// names none, must be submitted once and empty. The honeypot fields are
+/// removed before the others are deserialized. A submission which fills or
+/// leaves out a honeypot field is handled as set by
+/// [`EnumFormConfig::honeypot_action`]: rejected with
+/// `Status::UnprocessableEntity` by default, or accepted without its value,
+/// which is then `None`, with [`HoneypotAction::Drop`].
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket::http::ContentType;
+/// use rocket::local::blocking::Client;
+/// use rocket_enumform::{EnumFormConfig, Honeypot, HoneypotAction};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Contact {
+///     message: String,
+/// }
+///
+/// #[post("/contact", data = "<contact>")]
+/// fn contact(contact: Honeypot<Contact>) -> &'static str {
+///     if let Some(contact) = contact.into_inner() {
+///         println!("{}", contact.message);
+///     }
+///     "Thank you!"
+/// }
+///
+/// let mut config = EnumFormConfig::default();
+/// config.honeypot_action = HoneypotAction::Drop;
+/// let rocket = rocket::build().manage(config).mount("/", routes![contact]);
+/// let client = Client::untracked(rocket).unwrap();
+/// let request = client.post("/contact").header(ContentType::Form);
+/// let response = request.body("message=hi&website=http://spam.example").dispatch();
+/// assert_eq!(response.into_string().unwrap(), "Thank you!");
+/// ```
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Honeypot<T>(pub Option<T>);
+
+impl<T> Honeypot<T> {
+    /// Consumes the Honeypot wrapper and returns the wrapped item, `None` if
+    /// the submission was dropped.
+    #[inline(always)]
+    pub fn into_inner(self) -> Option<T> {
+        self.0
+    }
+}
+
+impl<T> From<T> for Honeypot<T> {
+    fn from(value: T) -> Self {
+        Honeypot(Some(value))
+    }
+}
+
+impl<T> Deref for Honeypot<T> {
+    type Target = Option<T>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Option<T> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Honeypot<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Option<T> {
+        &mut self.0
+    }
+}
+
+/// Removes the honeypot `fields` from `pairs`, failing unless each of them
+/// is submitted once and empty.
+fn take_honeypots(pairs: &mut de::Pairs<'_>, fields: &[&str]) -> Result<(), de::Error> {
+    let mut seen = vec![false; fields.len()];
+    let mut filled = None;
+    pairs.retain(
+        |(key, value)| match fields.iter().position(|field| key == field) {
+            Some(i) => {
+                if seen[i] || !value.is_empty() {
+                    filled.get_or_insert(fields[i]);
+                }
+                seen[i] = true;
+                false
+            }
+            None => true,
+        },
+    );
+    if let Some(field) = filled {
+        return Err(de::Error::custom(format_args!(
+            "honeypot field `{}` is filled in",
+            field
+        )));
+    }
+    match seen.iter().position(|seen| !seen) {
+        Some(i) => Err(de::Error::custom(format_args!(
+            "missing field `{}`",
+            fields[i]
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>> FromData<'r> for Honeypot<T> {
+    type Error = Error<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let config = EnumFormConfig::of(req);
+        let fields: Vec<&str> = match config.honeypot_fields.is_empty() {
+            true => vec![DEFAULT_FIELD],
+            false => config.honeypot_fields.iter().map(String::as_str).collect(),
+        };
+        let opts = config.options();
+        let limit = crate::form_limit(req);
+        crate::parse_body(req, data, &ContentType::Form, limit, |body| {
+            let mut pairs = codec::decode_bytes(body, &opts);
+            if let Err(e) = take_honeypots(&mut pairs, &fields) {
+                if config.honeypot_action == HoneypotAction::Drop {
+                    info_!("dropping submission: {}", e);
+                    return Ok(Honeypot(None));
+                }
+                return Err(Error::Parse(crate::raw_text(body), e));
+            }
+            de::from_pairs_with(pairs, opts)
+                .map(|value| Honeypot(Some(value)))
+                .map_err(|e| Error::Parse(crate::raw_text(body), e))
+        })
+        .await
+    }
+}
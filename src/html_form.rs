@@ -0,0 +1,128 @@
+// Copyright (c) 2021 Cognite AS
+//! Forms with repeated keys backed by [`serde_html_form`].
+//!
+//! This module is only available with the `html-form` feature enabled.
+
+use std::ops::{Deref, DerefMut};
+
+use rocket::data::{Data, FromData, Outcome};
+use rocket::error_;
+use rocket::http::{ContentType, Status};
+use rocket::request::Request;
+use rocket::response::{self, Responder};
+use serde::de::Error as _;
+use serde::{Deserialize, Serialize};
+
+use crate::{de, EnumFormConfig, Error};
+
+/// The HtmlFormEncoded guard: consume x-www-form-urlencoded requests as
+/// submitted by HTML forms.
+///
+/// `HtmlFormEncoded` works like [`UrlEncoded`](crate::UrlEncoded), except that
+/// repeated keys, such as the `tags=a&tags=b` a multi-select submits, are
+/// collected into sequence fields, following the conventions of
+/// [`serde_html_form`].
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket_enumform::html_form::HtmlFormEncoded;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Post {
+///     title: String,
+///     tags: Vec<String>,
+/// }
+///
+/// #[post("/post", format = "form", data = "<post>")]
+/// fn post(post: HtmlFormEncoded<Post>) -> String {
+///     format!("{:?}", post.into_inner())
+/// }
+/// ```
+///
+/// The body is read like that of `UrlEncoded`: the same `limits.enumform`
+/// data limit applies, and so does the configuration of
+/// [`EnumFormConfig`] for reading bodies and reporting
+/// errors, while the deserialization options are left to [`serde_html_form`].
+/// Failures are reported as [`Error`], with the error of
+/// `serde_html_form` as the message of [`Error::Parse`].
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HtmlFormEncoded<T>(pub T);
+
+impl<T> HtmlFormEncoded<T> {
+    /// Consumes the HtmlFormEncoded wrapper and returns the wrapped item.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rocket_enumform::html_form::HtmlFormEncoded;
+    /// let string = "Hello".to_string();
+    /// let outer = HtmlFormEncoded(string);
+    /// assert_eq!(outer.into_inner(), "Hello".to_string());
+    /// ```
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<'r, T: Deserialize<'r>> HtmlFormEncoded<T> {
+    fn from_str(s: &'r str) -> Result<Self, Error<'r>> {
+        serde_html_form::from_str(s)
+            .map(HtmlFormEncoded)
+            .map_err(|e| Error::Parse(s, de::Error::custom(e)))
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>> FromData<'r> for HtmlFormEncoded<T> {
+    type Error = Error<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let limit = crate::form_limit(req);
+        crate::parse_body(req, data, &ContentType::Form, limit, |s| {
+            Self::from_str(crate::utf8(s)?)
+        })
+        .await
+    }
+}
+
+/// Serializes the wrapped value, repeating keys for sequences. Returns a
+/// response with Content-Type application/x-www-form-urlencoded, with a
+/// `charset=utf-8` parameter as configured by
+/// [`EnumFormConfig::response_charset`], and a fixed-size body with the
+/// serialized value. If serialization fails, an `Err` of
+/// `Status::InternalServerError` is returned.
+impl<'r, T: Serialize> Responder<'r, 'static> for HtmlFormEncoded<T> {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let string = serde_html_form::to_string(&self.0).map_err(|e| {
+            error_!("HtmlFormEncoding failed to serialize: {:?}", e);
+            Status::InternalServerError
+        })?;
+
+        let content_type = EnumFormConfig::of(req).response_content_type(ContentType::Form);
+        (content_type, string).respond_to(req)
+    }
+}
+
+impl<T> From<T> for HtmlFormEncoded<T> {
+    fn from(value: T) -> Self {
+        HtmlFormEncoded(value)
+    }
+}
+
+impl<T> Deref for HtmlFormEncoded<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for HtmlFormEncoded<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
@@ -0,0 +1,238 @@
+// Copyright (c) 2021 Cognite AS
+//! Accepting and producing the same payload as JSON or as a form.
+//!
+//! This module is only available with the `json` feature enabled.
+
+use std::ops::{Deref, DerefMut};
+use std::{error, fmt};
+
+use rocket::data::{Data, FromData, Outcome};
+use rocket::error_;
+use rocket::http::{ContentType, MediaType, Status};
+use rocket::request::Request;
+use rocket::response::{self, Responder, Response};
+use rocket::serde::json::{self, Json};
+use serde::{Deserialize, Serialize};
+
+use crate::UrlEncoded;
+
+/// The FormOrJson guard: consume a body as JSON or as a form, depending on its
+/// Content-Type.
+///
+/// Requests with a JSON Content-Type are deserialized with Rocket's
+/// [`Json`] guard and requests with an `application/x-www-form-urlencoded`
+/// Content-Type with [`UrlEncoded`]; either way the handler receives a single
+/// `T`. Requests with any other, or no, Content-Type are forwarded with
+/// `Status::UnsupportedMediaType`.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket_enumform::json::FormOrJson;
+///
+/// #[derive(Debug, Deserialize)]
+/// #[serde(tag = "type")]
+/// enum Body {
+///     #[serde(rename = "variant_one")]
+///     VariantOne { content_one: String },
+///     #[serde(rename = "variant_two")]
+///     VariantTwo { content_two: usize },
+/// }
+///
+/// #[post("/body", data = "<data>")]
+/// fn body(data: FormOrJson<Body>) -> String {
+///     format!("{:?}", data.into_inner())
+/// }
+/// ```
+///
+/// The `limits.json` and `limits.enumform` data limits apply respectively.
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FormOrJson<T>(pub T);
+
+/// Error returned by the [`FormOrJson`] guard when deserialization fails.
+#[derive(Debug)]
+pub enum Error<'a> {
+    /// The body was JSON and failed to deserialize.
+    Json(json::Error<'a>),
+
+    /// The body was a form and failed to deserialize.
+    Form(crate::Error<'a>),
+}
+
+impl<'a> fmt::Display for Error<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "json {}", err),
+            Self::Form(err) => write!(f, "form {}", err),
+        }
+    }
+}
+
+impl<'a> error::Error for Error<'a> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Json(err) => err.source(),
+            Self::Form(err) => err.source(),
+        }
+    }
+}
+
+impl<T> FormOrJson<T> {
+    /// Consumes the FormOrJson wrapper and returns the wrapped item.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rocket_enumform::json::FormOrJson;
+    /// let string = "Hello".to_string();
+    /// let outer = FormOrJson(string);
+    /// assert_eq!(outer.into_inner(), "Hello".to_string());
+    /// ```
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>> FromData<'r> for FormOrJson<T> {
+    type Error = Error<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        match req.content_type() {
+            Some(ct) if ct.is_json() => <Json<T> as FromData>::from_data(req, data)
+                .await
+                .map(|json| FormOrJson(json.into_inner()))
+                .map_error(|(status, e)| (status, Error::Json(e))),
+            Some(ct) if ct.is_form() => <UrlEncoded<T> as FromData>::from_data(req, data)
+                .await
+                .map(|form| FormOrJson(form.into_inner()))
+                .map_error(|(status, e)| (status, Error::Form(e))),
+            _ => Outcome::Forward((data, Status::UnsupportedMediaType)),
+        }
+    }
+}
+
+impl<T> From<T> for FormOrJson<T> {
+    fn from(value: T) -> Self {
+        FormOrJson(value)
+    }
+}
+
+impl<T> Deref for FormOrJson<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for FormOrJson<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+/// The Negotiated responder: produce a form or JSON, depending on the
+/// request's Accept header.
+///
+/// The media types of the Accept header are considered in order of their
+/// q-values. If `application/x-www-form-urlencoded` is preferred to any JSON
+/// media type, the wrapped value is serialized like [`UrlEncoded`] does,
+/// otherwise, including when there is no Accept header, as JSON. The response
+/// carries `Vary: Accept` so that caches keep the representations apart.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Serialize;
+/// use rocket_enumform::json::Negotiated;
+///
+/// #[derive(Debug, Serialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[get("/user")]
+/// fn user() -> Negotiated<User> {
+///     Negotiated(User { name: "Bob".into() })
+/// }
+/// ```
+///
+/// If serialization fails, an `Err` of `Status::InternalServerError` is
+/// returned.
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Negotiated<T>(pub T);
+
+impl<T> Negotiated<T> {
+    /// Consumes the Negotiated wrapper and returns the wrapped item.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// Whether the Accept header of `req` prefers a form over JSON.
+fn prefers_form(req: &Request<'_>) -> bool {
+    let accept = match req.accept() {
+        Some(accept) => accept,
+        None => return false,
+    };
+
+    let mut media_types: Vec<_> = accept
+        .iter()
+        .map(|qmt| (qmt.weight_or(1.0), qmt.media_type()))
+        .filter(|(q, _)| *q > 0.0)
+        .collect();
+    media_types.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+    let is_json = |mt: &MediaType| {
+        mt.is_json() || mt.top() == "*" || (mt.top() == "application" && mt.sub() == "*")
+    };
+    media_types
+        .into_iter()
+        .find(|(_, mt)| mt.is_form() || is_json(mt))
+        .is_some_and(|(_, mt)| mt.is_form())
+}
+
+impl<'r, T: Serialize> Responder<'r, 'static> for Negotiated<T> {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let response = if prefers_form(req) {
+            UrlEncoded(self.0).respond_to(req)?
+        } else {
+            let string = json::to_string(&self.0).map_err(|e| {
+                error_!("JSON failed to serialize: {:?}", e);
+                Status::InternalServerError
+            })?;
+            (ContentType::JSON, string).respond_to(req)?
+        };
+
+        Response::build_from(response)
+            .raw_header("Vary", "Accept")
+            .ok()
+    }
+}
+
+impl<T> From<T> for Negotiated<T> {
+    fn from(value: T) -> Self {
+        Negotiated(value)
+    }
+}
+
+impl<T> Deref for Negotiated<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Negotiated<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
@@ -31,36 +31,238 @@
 //! #[post("/form", format = "form", data = "<data>")]
 //! fn body(data: UrlEncoded<Body>) -> String { format!("{:?}", data) }
 //! ```
-//! ## status
 //!
-//! Works but not tested, nor have local testing affordances been added yet.
+//! ## Testing
 //!
-// # Testing
-//
-// TODO; idea is use the underlying serde_urlencoded serializer and implement the glue
-// needed as extension traits.
-//
-// ///The [`LocalRequest`] and [`LocalResponse`] types provide [`json()`] and
-// ///[`into_json()`] methods to create a request with serialized JSON and
-// ///deserialize a response as JSON, respectively.
-//
-// ///[`LocalRequest`]: crate::local::blocking::LocalRequest [`LocalResponse`]:
-// ///crate::local::blocking::LocalResponse [`json()`]:
-// ///crate::local::blocking::LocalRequest::json() [`into_json()`]:
-// ///crate::local::blocking::LocalResponse::into_json()
+//! The [`local`] module extends Rocket's local client:
+//! [`LocalRequestExt`](local::blocking::LocalRequestExt) and
+//! [`LocalResponseExt`](local::blocking::LocalResponseExt) provide
+//! `urlencoded()` and `into_urlencoded()` methods to create a request with a
+//! serialized form body and deserialize a form response, respectively.
+//!
+//! The [`testing`] module asserts that values round-trip through forms and
+//! that forms parse into the expected values.
 
+use std::borrow::Cow;
+use std::convert::TryFrom;
 use std::ops::{Deref, DerefMut};
 use std::{error, fmt, io};
 
-use rocket::data::{Data, FromData, Limits, Outcome};
-use rocket::error_;
+use rocket::data::{ByteUnit, Capped, Data, FromData, Limits, Outcome};
 use rocket::form::prelude as form;
 use rocket::http::uri::fmt::{Formatter as UriFormatter, FromUriParam, Query, UriDisplay};
 use rocket::http::{ContentType, Status};
-use rocket::request::{local_cache, Request};
-use rocket::response::{self, content, Responder};
+use rocket::request::{self, local_cache, FromRequest, Request};
+use rocket::response::{self, Responder};
+use rocket::{error_, info_, warn, warn_, Ignite, Rocket, Sentinel};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+mod cache;
+mod case;
+#[cfg(feature = "charset")]
+mod charset;
+mod checkbox;
+#[cfg(feature = "chrono")]
+pub mod chrono;
+mod code;
+mod codec;
+#[cfg(feature = "compression")]
+mod compression;
+mod config;
+mod context;
+#[cfg(feature = "csrf")]
+mod csrf;
+#[cfg(any(feature = "chrono", feature = "time"))]
+mod date;
+pub mod de;
+#[cfg(feature = "decimal")]
+pub mod decimal;
+mod delimited;
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod derive;
+mod example;
+mod failure;
+mod flash;
+#[cfg(feature = "fluent")]
+pub mod fluent;
+mod form_cookie;
+mod honeypot;
+#[cfg(feature = "html-form")]
+pub mod html_form;
+#[cfg(feature = "json")]
+pub mod json;
+mod limited;
+pub mod local;
+#[cfg(feature = "metrics")]
+mod measure;
+mod method;
+mod mode;
+mod multi_select;
+pub mod multipart;
+#[cfg(feature = "oauth")]
+pub mod oauth;
+#[cfg(feature = "okapi")]
+mod okapi;
+mod owned;
+#[cfg(feature = "secrets")]
+mod private_cookie;
+mod problem;
+#[cfg(feature = "qs")]
+pub mod qs;
+mod query;
+mod raw;
+mod redirect;
+mod render;
+#[cfg(feature = "schemars")]
+pub mod schemars;
+mod secret;
+pub mod ser;
+#[cfg(feature = "secrets")]
+mod signed;
+mod stats;
+#[cfg(feature = "strum")]
+pub mod strum;
+#[cfg(feature = "templates")]
+pub mod templates;
+pub mod testing;
+#[cfg(feature = "time")]
+pub mod time;
+#[cfg(feature = "tracing")]
+mod trace;
+#[cfg(feature = "utoipa")]
+mod utoipa;
+#[cfg(feature = "uuid")]
+pub mod uuid;
+#[cfg(feature = "validator")]
+mod validated;
+mod value;
+#[cfg(feature = "verify")]
+pub mod verify;
+
+pub use case::KeyCase;
+#[cfg(feature = "charset")]
+use charset::decode_body;
+pub use checkbox::Checkbox;
+pub use code::ErrorCode;
+pub use codec::{Encoded, FormCodec, UrlEncodedCodec};
+#[cfg(feature = "compression")]
+use compression::decompress;
+pub use config::{EnumForm, EnumFormConfig, ErrorStatusPolicy, HoneypotAction, RawData};
+pub use context::{ContextualUrlEncoded, FieldError, FormContext};
+#[cfg(feature = "csrf")]
+pub use csrf::{CsrfProtected, CsrfToken};
+pub use de::Error as ParseError;
+pub use delimited::Delimited;
+pub use example::FormExample;
+pub use failure::ParseFailure;
+pub use flash::FlashedForm;
+pub use form_cookie::{CookieSpec, FormCookie};
+pub use honeypot::Honeypot;
+pub use limited::{CappedUrlEncoded, Limited};
+pub use method::MethodOverride;
+pub use mode::{LenientUrlEncoded, StrictUrlEncoded};
+pub use multi_select::MultiSelect;
+pub use owned::UrlEncodedOwned;
+#[cfg(feature = "secrets")]
+pub use private_cookie::PrivateFormCookie;
+pub use query::QueryEncoded;
+pub use raw::{RawForm, WithRaw};
+pub use redirect::RedirectWithForm;
+pub use render::ErrorRenderer;
+pub use secret::Secret;
+#[cfg(feature = "secrets")]
+pub use signed::{FormSigner, SignedForm};
+pub use stats::ParseStats;
+#[cfg(feature = "validator")]
+pub use validated::Validated;
+pub use value::{from_json_value, from_value, to_json_value, to_value, Value};
+
+/// Derives Rocket's [`FromForm`](rocket::form::FromForm) for an internally
+/// tagged enum, so that it can be used with [`Form`](rocket::form::Form) and
+/// as a field of other `FromForm` types.
+///
+/// The enum must also implement [`Deserialize`] and be marked
+/// `#[serde(tag = "...")]` (or `#[serde(untagged)]`). The fields of the form,
+/// named relative to the enum, are collected and deserialized like
+/// [`UrlEncoded`] does. Deserialization failures are reported as a custom
+/// [`form::Error`].
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket::form::Form;
+/// use rocket_enumform::EnumForm;
+///
+/// #[derive(Debug, Deserialize, EnumForm)]
+/// #[serde(tag = "type")]
+/// enum Body {
+///     #[serde(rename = "variant_one")]
+///     VariantOne { content_one: String },
+///     #[serde(rename = "variant_two")]
+///     VariantTwo { content_two: usize },
+/// }
+///
+/// #[derive(Debug, FromForm)]
+/// struct Envelope {
+///     id: usize,
+///     body: Body,
+/// }
+///
+/// #[post("/form", data = "<data>")]
+/// fn envelope(data: Form<Envelope>) -> String {
+///     format!("{:?}", data.into_inner())
+/// }
+/// ```
+///
+/// This macro is only available with the `derive` feature enabled.
+#[cfg(feature = "derive")]
+pub use rocket_enumform_derive::EnumForm;
+
+/// Derives Rocket's [`FromFormField`](rocket::form::FromFormField) and
+/// [`UriDisplay<Query>`](rocket::http::uri::fmt::UriDisplay) for an enum of
+/// unit variants, so that it can be used as a single form or query field.
+///
+/// Values are the names serde would use for the variants: `#[serde(rename)]`,
+/// `#[serde(rename_all)]` and `#[serde(alias)]` are honored, and a variant
+/// marked `#[serde(other)]` accepts any other value. Unlike Rocket's own
+/// derive, values are matched case-sensitively, exactly like serde does.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::{Deserialize, Serialize};
+/// use rocket_enumform::FormFieldEnum;
+///
+/// #[derive(Debug, Deserialize, Serialize, FormFieldEnum)]
+/// #[serde(rename_all = "lowercase")]
+/// enum Sort {
+///     Asc,
+///     #[serde(alias = "descending")]
+///     Desc,
+/// }
+///
+/// #[get("/items?<sort>")]
+/// fn items(sort: Sort) -> String {
+///     format!("{:?}", sort)
+/// }
+///
+/// assert_eq!(uri!(items(Sort::Desc)).to_string(), "/items?sort=desc");
+/// ```
+///
+/// This macro is only available with the `derive` feature enabled.
+#[cfg(feature = "derive")]
+pub use rocket_enumform_derive::FormFieldEnum;
+
+/// Derives [`FormExample`](trait@FormExample), with an example per variant
+/// of an enum, or one of a struct, built from the `#[form_example(...)]`
+/// attributes of the fields or from their defaults. See the trait for an
+/// example.
+///
+/// This macro is only available with the `derive` feature enabled.
+#[cfg(feature = "derive")]
+pub use rocket_enumform_derive::FormExample;
+
 /// The UrlEncoded guard: easily consume x-www-form-urlencoded requests.
 ///
 /// ## Receiving
@@ -71,9 +273,9 @@ use serde::{Deserialize, Serialize};
 ///
 /// To deserialize request body data from x-www-form-urlencoded, add a `data`
 /// route argument with a target type of `UrlEncoded<T>`, where `T` is some type
-/// you'd like to parse. `T` must implement [`serde::Deserialize`]. See
-/// [`serde_urlencoded`](serde_urlencoded) docs on the flatten-workaround for important hints about
-/// more complex datatypes.
+/// you'd like to parse. `T` must implement [`serde::Deserialize`]. The
+/// [`de`] module documents how flattened and internally tagged types are
+/// handled.
 ///
 /// ```rust
 /// # #[macro_use] extern crate rocket;
@@ -92,50 +294,278 @@ use serde::{Deserialize, Serialize};
 /// specify "application/x-www-form-urlencoded" as its `Content-Type` header
 /// value will not be routed to the handler.
 ///
+/// The body is read once per request, into the request-local cache, together
+/// with its decoded pairs. The guards of routes which a request is forwarded
+/// to, such as [`StrictUrlEncoded`], [`RawForm`] or [`WithRaw`], reuse them
+/// instead of reading the body again.
+///
+/// The pairs are split from the bytes of the body, which is not validated as
+/// UTF-8 beforehand: percent-decoded keys and values which are not valid UTF-8
+/// have their invalid sequences replaced with `U+FFFD`, and the raw data of an
+/// [`Error::Parse`] ends before the first byte of the body which is not valid
+/// UTF-8. Guards which keep the body as text, such as [`WithRaw`], fail on
+/// such a body with [`Error::Io`].
+///
+/// ### Lenient and Strict Parsing
+///
+/// `UrlEncoded` ignores keys which are not fields of `T`, like serde does by
+/// default. Use [`LenientUrlEncoded`] to also accept empty values for
+/// optional fields, or [`StrictUrlEncoded`] to reject unknown keys.
+///
 /// ### Incoming Data Limits
 ///
 /// The default size limit for incoming UrlEncoded data is the built in form
 /// limit. Setting a limit protects your application from denial of service
 /// (DoS) attacks and from resource exhaustion through high memory consumption.
-/// The limit can be increased by setting the `limits.form` configuration
-/// parameter. For instance, to increase the UrlEncoded limit to 5MiB for all
-/// environments, you may add the following to your `Rocket.toml`:
+/// The limit can be increased by setting the `limits.enumform` configuration
+/// parameter, which applies to the guards of this crate only, or
+/// `limits.form`, which also applies to Rocket's own `Form` and is used when
+/// `limits.enumform` is not set. For instance, to increase the UrlEncoded
+/// limit to 5MiB for all environments, you may add the following to your
+/// `Rocket.toml`:
 ///
 /// ```toml
 /// [global.limits]
-/// form = 5242880
+/// enumform = 5242880
+/// ```
+///
+/// To change the limit of a single route only, use [`Limited`]. To handle
+/// oversized bodies in the route instead of failing, use [`CappedUrlEncoded`].
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket::data::{Limits, ToByteUnit};
+/// use rocket::http::{ContentType, Status};
+/// use rocket::local::blocking::Client;
+/// use rocket_enumform::UrlEncoded;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[post("/user", data = "<user>")]
+/// fn new_user(user: UrlEncoded<User>) -> String {
+///     user.into_inner().name
+/// }
+///
+/// let status = |limits: Limits| {
+///     let config = rocket::Config { limits, ..rocket::Config::debug_default() };
+///     let rocket = rocket::custom(config).mount("/", routes![new_user]);
+///     let client = Client::untracked(rocket).unwrap();
+///     let request = client.post("/user").header(ContentType::Form);
+///     let response = request.body("name=rocket").dispatch();
+///     response.status()
+/// };
+///
+/// assert_eq!(status(Limits::new().limit("form", 4.bytes())), Status::PayloadTooLarge);
+/// let limits = Limits::new().limit("form", 4.bytes()).limit("enumform", 1.kibibytes());
+/// assert_eq!(status(limits), Status::Ok);
 /// ```
 #[repr(transparent)]
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct UrlEncoded<T>(pub T);
 
-/// Error returned by the [`UrlEncoded`] guard when deserialization fails.
-#[derive(Debug)]
+/// Error returned by the [`UrlEncoded`] and [`QueryEncoded`] guards when deserialization fails.
+///
+/// Its Debug output, which Rocket logs, redacts the values of
+/// [`Secret`] fields from the raw data of [`Error::Parse`].
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket::data::{Limits, ToByteUnit};
+/// use rocket::http::ContentType;
+/// use rocket::local::blocking::Client;
+/// use rocket_enumform::{Error, UrlEncoded};
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[post("/user", data = "<user>")]
+/// fn new_user(user: Result<UrlEncoded<User>, Error<'_>>) -> String {
+///     match user {
+///         Ok(user) => user.into_inner().name,
+///         Err(Error::TooLarge { limit, .. }) => format!("at most {}", limit),
+///         Err(e) => e.to_string(),
+///     }
+/// }
+///
+/// let limits = Limits::new().limit("enumform", 4.bytes());
+/// let config = rocket::Config { limits, ..rocket::Config::debug_default() };
+/// let rocket = rocket::custom(config).mount("/", routes![new_user]);
+/// let client = Client::untracked(rocket).unwrap();
+/// let request = client.post("/user").header(ContentType::Form);
+/// let response = request.body("name=rocket").dispatch();
+/// assert_eq!(response.into_string().unwrap(), "at most 4B");
+/// ```
+#[non_exhaustive]
 pub enum Error<'a> {
     /// An I/O error occurred while reading the incoming request data.
     Io(io::Error),
 
+    /// The request data exceeded the data limit, `limit`, after `read` bytes
+    /// of it were read.
+    TooLarge {
+        /// The data limit of the guard.
+        limit: ByteUnit,
+        /// The number of bytes read before the limit was reached.
+        read: ByteUnit,
+    },
+
     /// The client's data was received successfully but failed to parse as valid
     /// UrlEncoded or as the requested type. The `&str` value in `.0` is the raw data
     /// received from the user, while the `Error` in `.1` is the deserialization
-    /// error from `serde`.
-    Parse(&'a str, ::serde_urlencoded::de::Error),
+    /// error from `serde`, whose [`path()`](de::Error::path) names the offending
+    /// field where known.
+    Parse(&'a str, de::Error),
+
+    /// The request's Content-Type, in `.0`, is missing or not the one the
+    /// guard accepts, when [`EnumFormConfig::strict_content_type`] is set, or
+    /// names a charset which cannot be decoded, with the `charset` feature.
+    ContentType(Option<&'a ContentType>),
+
+    /// The request's Content-Encoding, in `.0`, is not supported. Without the
+    /// `compression` feature, no encoding but `identity` is.
+    ContentEncoding(&'a str),
+
+    /// The CSRF token of the form is missing or does not match the one of its
+    /// cookie, with the `csrf` feature.
+    Csrf,
+
+    /// The signature of a webhook is missing, invalid or expired, with the
+    /// `verify` feature.
+    Signature,
+
+    /// The data deserialized but failed the constraints of
+    /// [`Validated`], in `.0`, with the `validator` feature.
+    #[cfg(feature = "validator")]
+    Validation(validator::ValidationErrors),
+}
+
+impl<'a> fmt::Debug for Error<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => f.debug_tuple("Io").field(err).finish(),
+            Self::TooLarge { limit, read } => f
+                .debug_struct("TooLarge")
+                .field("limit", limit)
+                .field("read", read)
+                .finish(),
+            Self::Parse(s, err) => f
+                .debug_tuple("Parse")
+                .field(&err.redact(s))
+                .field(err)
+                .finish(),
+            Self::ContentType(ct) => f.debug_tuple("ContentType").field(ct).finish(),
+            Self::ContentEncoding(e) => f.debug_tuple("ContentEncoding").field(e).finish(),
+            Self::Csrf => f.write_str("Csrf"),
+            Self::Signature => f.write_str("Signature"),
+            #[cfg(feature = "validator")]
+            Self::Validation(errors) => f.debug_tuple("Validation").field(errors).finish(),
+        }
+    }
 }
 
 impl<'a> fmt::Display for Error<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Io(err) => write!(f, "i/o error: {}", err),
+            Self::TooLarge { limit, .. } => write!(f, "data limit of {} exceeded", limit),
             Self::Parse(_, err) => write!(f, "parse error: {}", err),
+            Self::ContentType(Some(ct)) => write!(f, "unsupported content type: {}", ct),
+            Self::ContentType(None) => write!(f, "missing content type"),
+            Self::ContentEncoding(e) => write!(f, "unsupported content encoding: {}", e),
+            Self::Csrf => write!(f, "missing or invalid CSRF token"),
+            Self::Signature => write!(f, "missing, invalid or expired signature"),
+            #[cfg(feature = "validator")]
+            Self::Validation(errors) => {
+                f.write_str("validation failed")?;
+                for (i, e) in validated::field_errors(errors).iter().enumerate() {
+                    f.write_str(if i == 0 { ": " } else { "; " })?;
+                    match &e.name {
+                        Some(name) => write!(f, "{}: {}", name, e.message)?,
+                        None => f.write_str(&e.message)?,
+                    }
+                }
+                Ok(())
+            }
         }
     }
 }
 
+impl<'a> Error<'a> {
+    /// The status the guards fail with for this error:
+    /// `Status::PayloadTooLarge` if the data limit was exceeded,
+    /// `Status::UnprocessableEntity` if the data failed to parse, unless it
+    /// exceeded a structural [`de::Limit`], which is also
+    /// `Status::PayloadTooLarge`, or to validate,
+    /// `Status::UnsupportedMediaType` if the Content-Type or Content-Encoding
+    /// was rejected,
+    /// `Status::Forbidden` if the CSRF token was rejected,
+    /// `Status::Unauthorized` if the signature was and
+    /// `Status::BadRequest` otherwise.
+    ///
+    /// The guards fail with this status as mapped by
+    /// [`EnumFormConfig::error_status_policy`].
+    pub fn status(&self) -> Status {
+        match self {
+            Self::Io(_) => Status::BadRequest,
+            Self::TooLarge { .. } => Status::PayloadTooLarge,
+            Self::Parse(_, e) => parse_status(e),
+            Self::ContentType(_) | Self::ContentEncoding(_) => Status::UnsupportedMediaType,
+            Self::Csrf => Status::Forbidden,
+            Self::Signature => Status::Unauthorized,
+            #[cfg(feature = "validator")]
+            Self::Validation(_) => Status::UnprocessableEntity,
+        }
+    }
+
+    /// What went wrong, see [`ErrorCode`].
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Io(_) => ErrorCode::Io,
+            Self::TooLarge { .. } => ErrorCode::BodyTooLarge,
+            Self::Parse(_, e) => e.code(),
+            Self::ContentType(_) => ErrorCode::UnsupportedContentType,
+            Self::ContentEncoding(_) => ErrorCode::UnsupportedContentEncoding,
+            Self::Csrf => ErrorCode::InvalidCsrfToken,
+            Self::Signature => ErrorCode::InvalidSignature,
+            #[cfg(feature = "validator")]
+            Self::Validation(_) => ErrorCode::ValidationFailed,
+        }
+    }
+}
+
+/// The status a guard of `req` fails with for an error of status `status`,
+/// as mapped by [`EnumFormConfig::error_status_policy`].
+pub(crate) fn error_status(req: &Request<'_>, status: Status) -> Status {
+    EnumFormConfig::of(req).error_status_policy.status(status)
+}
+
+/// The status for a deserialization error.
+pub(crate) fn parse_status(e: &de::Error) -> Status {
+    match e.limit() {
+        Some(_) => Status::PayloadTooLarge,
+        None => Status::UnprocessableEntity,
+    }
+}
+
 impl<'a> error::Error for Error<'a> {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
             Self::Io(err) => Some(err),
             Self::Parse(_, err) => Some(err),
+            Self::TooLarge { .. }
+            | Self::ContentType(_)
+            | Self::ContentEncoding(_)
+            | Self::Csrf
+            | Self::Signature => None,
+            #[cfg(feature = "validator")]
+            Self::Validation(_) => None,
         }
     }
 }
@@ -156,25 +586,348 @@ impl<T> UrlEncoded<T> {
     }
 }
 
+impl<T: Serialize> UrlEncoded<T> {
+    /// Serializes the wrapped value into its canonical form: pairs sorted by
+    /// key and percent-encoded the same way every time, so equal values give
+    /// byte for byte equal strings for HMAC signatures or cache keys. See
+    /// [`ser::Options::canonical`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use rocket_enumform::UrlEncoded;
+    /// use std::collections::HashMap;
+    ///
+    /// let form = UrlEncoded(HashMap::from([("q", "a b*"), ("page", "2")]));
+    /// assert_eq!(form.to_canonical_string().unwrap(), "page=2&q=a%20b%2A");
+    /// ```
+    pub fn to_canonical_string(&self) -> Result<String, ser::Error> {
+        let opts = ser::Options {
+            canonical: true,
+            ..Default::default()
+        };
+        ser::to_string_with(&self.0, opts)
+    }
+}
+
 impl<'r, T: Deserialize<'r>> UrlEncoded<T> {
-    fn from_str(s: &'r str) -> Result<Self, Error<'r>> {
-        ::serde_urlencoded::from_str(s)
+    fn from_str(s: &'r str, opts: de::Options) -> Result<Self, Error<'r>> {
+        codec::decode_with::<UrlEncodedCodec, T>(s, opts)
             .map(UrlEncoded)
             .map_err(|e| Error::Parse(s, e))
     }
+}
 
-    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Result<Self, Error<'r>> {
-        let limit = req.limits().get("form").unwrap_or(Limits::FORM);
-        let string = match data.open(limit).into_string().await {
-            Ok(s) if s.is_complete() => s.into_inner(),
-            Ok(_) => {
-                let eof = io::ErrorKind::UnexpectedEof;
-                return Err(Error::Io(io::Error::new(eof, "data limit exceeded")));
-            }
-            Err(e) => return Err(Error::Io(e)),
+/// The data limit for form bodies: `limits.enumform`, falling back to
+/// `limits.form` and then to Rocket's default form limit.
+pub(crate) fn form_limit(req: &Request<'_>) -> ByteUnit {
+    let limits = req.limits();
+    limits
+        .get("enumform")
+        .or_else(|| limits.get("form"))
+        .unwrap_or(Limits::FORM)
+}
+
+/// Reads the request body, up to the form limit, into the request-local cache.
+async fn read_body<'r>(req: &'r Request<'_>, data: Data<'r>) -> Result<&'r [u8], Error<'r>> {
+    read_body_with(req, data, form_limit(req)).await
+}
+
+/// Reads the request body, up to `limit`, into the request-local cache.
+async fn read_body_with<'r>(
+    req: &'r Request<'_>,
+    data: Data<'r>,
+    limit: ByteUnit,
+) -> Result<&'r [u8], Error<'r>> {
+    let capped = read_capped(req, data, limit).await?;
+    if !capped.is_complete() {
+        let read = capped.n.written.into();
+        return Err(Error::TooLarge { limit, read });
+    }
+
+    Ok(capped.into_inner())
+}
+
+/// The size of Rocket's peek buffer.
+const PEEK_BYTES: usize = 512;
+
+/// Checks the Content-Type of `req` against `expected`, ignoring parameters
+/// such as `charset`, if [`EnumFormConfig::strict_content_type`] is set.
+pub(crate) fn check_content_type<'r>(
+    req: &'r Request<'_>,
+    expected: &ContentType,
+) -> Result<(), Error<'r>> {
+    if !EnumFormConfig::of(req).strict_content_type {
+        return Ok(());
+    }
+
+    match req.content_type() {
+        Some(ct) if ct == expected => Ok(()),
+        ct => Err(Error::ContentType(ct)),
+    }
+}
+
+/// Reads the request body, up to `limit`, and parses it with `parse`.
+///
+/// The body is passed as bytes, which guards parsing it as text validate with
+/// [`utf8()`], while urlencoded pairs are split from the bytes directly.
+///
+/// With [`EnumFormConfig::forward_on_error`], a body which fits in Rocket's
+/// peek buffer is parsed without consuming it, so that the guard can forward
+/// it to the next route if it fails to parse.
+async fn parse_body<'r, S, F>(
+    req: &'r Request<'_>,
+    mut data: Data<'r>,
+    content_type: &ContentType,
+    limit: ByteUnit,
+    parse: F,
+) -> Outcome<'r, S, Error<'r>>
+where
+    F: FnOnce(&'r [u8]) -> Result<S, Error<'r>> + Send,
+{
+    if let Err(e) = check_content_type(req, content_type) {
+        return data_outcome(req, Err(e));
+    }
+
+    let forward = EnumFormConfig::of(req).forward_on_error && content_encoding(req).is_none();
+    if forward {
+        // A peek shorter than requested holds the whole body within `limit`.
+        let num = usize::try_from(limit.as_u64())
+            .map_or(PEEK_BYTES, |n| n.saturating_add(1).min(PEEK_BYTES));
+        let peeked = data.peek(num).await.to_vec();
+        let body = match peeked.len() < num && data.peek_complete() {
+            true => decode_body(req, peeked).ok(),
+            false => None,
         };
+        if let Some(body) = body {
+            let len = body.len();
+            let body = cache::store_body(req, Capped::complete(body, len)).into_inner();
+            return match instrumented(req, body, parse) {
+                Err(e @ Error::Parse(..)) => {
+                    let e = guard_failure(req, e);
+                    info_!("forwarding to the next route");
+                    failure::store_error(req, &e);
+                    Outcome::Forward((data, error_status(req, e.status())))
+                }
+                result => data_outcome(req, result),
+            };
+        }
+    }
+
+    let body = read_body_with(req, data, limit).await;
+    let result = body.and_then(|body| instrumented(req, body, parse));
+    if forward && matches!(result, Err(Error::Parse(..))) {
+        // Reading the body consumed the data, which Rocket offers no way to
+        // hand to the next route.
+        warn_!(
+            "not forwarding: the body exceeds Rocket's {} byte peek buffer",
+            PEEK_BYTES
+        );
+    }
+    data_outcome(req, result)
+}
+
+/// Runs `parse` on `data`, the body or query of `req`, recording it in the
+/// [`ParseStats`] of the application, if any, in a span with the `tracing`
+/// feature and measured with the `metrics` feature.
+pub(crate) fn instrumented<'r, D, S, F>(
+    req: &'r Request<'_>,
+    data: &'r D,
+    parse: F,
+) -> Result<S, Error<'r>>
+where
+    D: AsRef<[u8]> + ?Sized,
+    F: FnOnce(&'r D) -> Result<S, Error<'r>>,
+{
+    #[cfg(feature = "metrics")]
+    let parse = |data| measure::measured(data, parse);
+    #[cfg(feature = "tracing")]
+    let parse = |data| trace::traced(req, data, parse);
+    let start = std::time::Instant::now();
+    let result = parse(data);
+    ParseStats::record(req, result.is_ok(), start.elapsed());
+    result
+}
+
+/// Reads the request body, truncated at `limit`, into the request-local cache,
+/// unless another guard of the request has read it already.
+async fn read_capped<'r>(
+    req: &'r Request<'_>,
+    data: Data<'r>,
+    limit: ByteUnit,
+) -> Result<Capped<&'r [u8]>, Error<'r>> {
+    if let Some(body) = cache::body(req) {
+        return Ok(body);
+    }
+
+    Ok(cache::store_body(
+        req,
+        read_decoded(req, data, limit).await?,
+    ))
+}
+
+/// Reads `data`, truncated at `limit`, decompressing and decoding it.
+async fn read_decoded<'r>(
+    req: &'r Request<'_>,
+    data: Data<'r>,
+    limit: ByteUnit,
+) -> Result<Capped<Vec<u8>>, Error<'r>> {
+    let capped = match content_encoding(req) {
+        Some(encoding) => decompress(req, encoding, data, limit).await?,
+        None => read_bytes(req, data, limit).await.map_err(Error::Io)?,
+    };
+    let n = capped.n;
+    let body = decode_body(req, capped.into_inner())?;
+
+    Ok(Capped::new(body, n))
+}
+
+/// Deserializes the urlencoded `body` of `req` with `opts`, reusing the pairs
+/// of previous guards of the request where possible.
+fn decode_form<'r, T: Deserialize<'r>>(
+    req: &'r Request<'_>,
+    body: &'r [u8],
+    opts: de::Options,
+) -> Result<T, Error<'r>> {
+    de::from_pairs_with(cache::pairs(req, body, &opts), opts)
+        .map_err(|e| Error::Parse(raw_text(body), e))
+}
+
+/// `body` as text, for guards which parse it as such, failing unless it is
+/// valid UTF-8.
+pub(crate) fn utf8(body: &[u8]) -> Result<&str, Error<'_>> {
+    std::str::from_utf8(body).map_err(|e| Error::Io(io::Error::new(io::ErrorKind::InvalidData, e)))
+}
+
+/// The bytes of the hexadecimal `hex`, such as a signature, if valid.
+#[cfg(any(feature = "secrets", feature = "verify"))]
+pub(crate) fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 == 1 || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// `body`, which failed to parse, as the raw data of [`Error::Parse`]: up to
+/// its first byte which is not valid UTF-8, if any.
+pub(crate) fn raw_text(body: &[u8]) -> &str {
+    match std::str::from_utf8(body) {
+        Ok(text) => text,
+        Err(e) => std::str::from_utf8(&body[..e.valid_up_to()]).unwrap_or_default(),
+    }
+}
+
+/// Reads `data`, truncated at `limit`, into a buffer allocated up front for
+/// the Content-Length of `req`, if it is declared, up to `limit`.
+pub(crate) async fn read_bytes(
+    req: &Request<'_>,
+    data: Data<'_>,
+    limit: ByteUnit,
+) -> io::Result<Capped<Vec<u8>>> {
+    let len = req
+        .headers()
+        .get_one("Content-Length")
+        .and_then(|len| len.trim().parse::<u64>().ok())
+        .map_or(0, |len| len.min(limit.as_u64()));
+    let mut bytes = Vec::with_capacity(usize::try_from(len).unwrap_or(0));
+    let n = data.open(limit).stream_to(&mut bytes).await?;
+    Ok(Capped::new(bytes, n))
+}
+
+/// The Content-Encoding of `req`, unless there is none or it is `identity`.
+fn content_encoding<'r>(req: &'r Request<'_>) -> Option<&'r str> {
+    req.headers()
+        .get_one("Content-Encoding")
+        .map(str::trim)
+        .filter(|e| !e.is_empty() && !e.eq_ignore_ascii_case("identity"))
+}
+
+/// Fails, since decompressing bodies requires the `compression` feature.
+#[cfg(not(feature = "compression"))]
+async fn decompress<'r>(
+    _req: &'r Request<'_>,
+    encoding: &'r str,
+    _data: Data<'r>,
+    _limit: ByteUnit,
+) -> Result<Capped<Vec<u8>>, Error<'r>> {
+    Err(Error::ContentEncoding(encoding))
+}
+
+/// Decodes a body, which is UTF-8 without the `charset` feature and is kept
+/// as it is.
+#[cfg(not(feature = "charset"))]
+fn decode_body<'r>(_req: &'r Request<'_>, bytes: Vec<u8>) -> Result<Vec<u8>, Error<'r>> {
+    Ok(bytes)
+}
+
+/// Maps the result of reading and parsing a body to a data guard outcome.
+pub(crate) fn data_outcome<'r, S>(
+    req: &'r Request<'_>,
+    result: Result<S, Error<'r>>,
+) -> Outcome<'r, S, Error<'r>> {
+    match result {
+        Ok(value) => Outcome::Success(value),
+        Err(e) => {
+            let e = guard_failure(req, e);
+            failure::store_error(req, &e);
+            Outcome::Error((error_status(req, e.status()), e))
+        }
+    }
+}
+
+/// Keeps as much of `e`, an error of a guard of `req`, as
+/// [`EnumFormConfig::raw_data`] allows if the data failed to parse, logs it
+/// and passes it to the [`EnumForm::on_error()`] hook.
+pub(crate) fn guard_failure<'r>(req: &'r Request<'_>, e: Error<'r>) -> Error<'r> {
+    let e = match e {
+        Error::Parse(raw, e) => {
+            let e = e.locate(raw.as_bytes());
+            let e = match EnumFormConfig::of(req).raw_data {
+                RawData::Keep => e,
+                RawData::RedactValues | RawData::Omit => e.quiet(),
+            };
+            log_parse_error(req, &e);
+            Error::Parse(raw_data(req, raw), e)
+        }
+        e => e,
+    };
+    config::ErrorHook::call(req, &e);
+    e
+}
+
+/// Logs `e` at [`EnumFormConfig::log_level`] with
+/// [`EnumFormConfig::log_target`].
+fn log_parse_error(req: &Request<'_>, e: &de::Error) {
+    let config = EnumFormConfig::of(req);
+    if let Some(level) = config.log_level.to_level() {
+        // The target of Rocket's indented `error_!` and friends.
+        let target = config.log_target.as_deref();
+        let target = target.unwrap_or(concat!(module_path!(), "::_"));
+        log::log!(target: target, level, "{:?}", e);
+    }
+}
 
-        Self::from_str(local_cache!(req, string))
+/// What [`EnumFormConfig::raw_data`] and
+/// [`EnumFormConfig::max_raw_data_len`] keep of `raw`, data of `req` which
+/// failed to parse.
+pub(crate) fn raw_data<'r>(req: &'r Request<'_>, raw: &'r str) -> &'r str {
+    let config = EnumFormConfig::of(req);
+    let raw = match config.raw_data {
+        RawData::Keep => raw,
+        RawData::RedactValues => cache::redacted(req, raw),
+        RawData::Omit => "",
+    };
+    match config.max_raw_data_len {
+        Some(max) if raw.len() > max => {
+            let end = (0..=max)
+                .rev()
+                .find(|&i| raw.is_char_boundary(i))
+                .unwrap_or(0);
+            &raw[..end]
+        }
+        _ => raw,
     }
 }
 
@@ -183,16 +936,131 @@ impl<'r, T: Deserialize<'r>> FromData<'r> for UrlEncoded<T> {
     type Error = Error<'r>;
 
     async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
-        match Self::from_data(req, data).await {
-            Ok(value) => Outcome::Success(value),
-            Err(Error::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
-                Outcome::Failure((Status::PayloadTooLarge, Error::Io(e)))
+        let opts = EnumFormConfig::of(req).options();
+        let limit = form_limit(req);
+        parse_body(req, data, &ContentType::Form, limit, |s| {
+            decode_form(req, s, opts).map(UrlEncoded)
+        })
+        .await
+    }
+}
+
+/// Checks at launch that catchers handle the statuses the guard fails with,
+/// `413 Payload Too Large` and `422 Unprocessable Entity`, which would
+/// otherwise get Rocket's default error page. A catcher without a status
+/// handles both. With [`EnumFormConfig::warn_missing_catchers`], each status
+/// without a catcher is logged as a warning, and with
+/// [`EnumFormConfig::require_catchers`] as an error aborting launch.
+///
+/// Sentinels are not told which routes use the guard, so their `format` is
+/// not checked.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket::error::ErrorKind;
+/// use rocket::local::blocking::Client;
+/// use rocket_enumform::{EnumFormConfig, UrlEncoded};
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[post("/user", data = "<user>")]
+/// fn new_user(user: UrlEncoded<User>) -> String {
+///     user.into_inner().name
+/// }
+///
+/// #[catch(default)]
+/// fn failed() -> &'static str {
+///     "Please check the form."
+/// }
+///
+/// let mut config = EnumFormConfig::default();
+/// config.require_catchers = true;
+/// let rocket = rocket::build().manage(config.clone()).mount("/", routes![new_user]);
+/// let e = Client::untracked(rocket).unwrap_err();
+/// assert!(matches!(e.kind(), ErrorKind::SentinelAborts(_)));
+///
+/// let rocket = rocket::build().manage(config).mount("/", routes![new_user]);
+/// assert!(Client::untracked(rocket.register("/", catchers![failed])).is_ok());
+/// ```
+impl<T> Sentinel for UrlEncoded<T> {
+    fn abort(rocket: &Rocket<Ignite>) -> bool {
+        static DEFAULT: EnumFormConfig = EnumFormConfig::DEFAULT;
+        let config = rocket.state::<EnumFormConfig>().unwrap_or(&DEFAULT);
+        let mut abort = false;
+        for status in [Status::PayloadTooLarge, Status::UnprocessableEntity] {
+            let caught = rocket
+                .catchers()
+                .any(|catcher| catcher.code.is_none() || catcher.code == Some(status.code));
+            if caught {
+                continue;
             }
-            Err(Error::Parse(s, e)) => {
-                error_!("{:?}", e);
-                Outcome::Failure((Status::UnprocessableEntity, Error::Parse(s, e)))
+            let name = std::any::type_name::<Self>();
+            if config.require_catchers {
+                rocket::error!("{} fails with {}, which no catcher handles.", name, status);
+                abort = true;
+            } else if config.warn_missing_catchers {
+                warn!("{} fails with {}, which no catcher handles.", name, status);
+            }
+        }
+        abort
+    }
+}
+
+/// Borrows the value of the request body from the request-local cache.
+///
+/// The body must have been read by a data guard of this crate already, such
+/// as by [`UrlEncoded`] on the route itself or on a route the request was
+/// forwarded from, so this is for response fairings, catchers and guards
+/// invoked with [`Request::guard()`] after the data guard. Otherwise the guard
+/// forwards with `Status::InternalServerError`. The value is deserialized
+/// once per request and type, from the cached pairs, with the configured
+/// options; it fails like the data guard would.
+///
+/// ```rust
+/// # use serde::Deserialize;
+/// use rocket::fairing::AdHoc;
+/// use rocket::outcome::Outcome;
+/// use rocket_enumform::UrlEncoded;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Event {
+///     kind: String,
+/// }
+///
+/// let audit = AdHoc::on_response("Audit", |req, _| {
+///     Box::pin(async move {
+///         if let Outcome::Success(event) = req.guard::<&UrlEncoded<Event>>().await {
+///             rocket::info!("event: {}", event.kind);
+///         }
+///     })
+/// });
+/// ```
+#[rocket::async_trait]
+impl<'r, T> FromRequest<'r> for &'r UrlEncoded<T>
+where
+    T: DeserializeOwned + Send + Sync + 'static,
+{
+    type Error = Error<'r>;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let body = match cache::body(req) {
+            Some(body) if body.is_complete() => body.into_inner(),
+            _ => return request::Outcome::Forward(Status::InternalServerError),
+        };
+        let parsed = req.local_cache(|| {
+            let opts = EnumFormConfig::of(req).options();
+            de::from_pairs_with::<T>(cache::pairs(req, body, &opts), opts).map(UrlEncoded)
+        });
+        match parsed {
+            Ok(value) => request::Outcome::Success(value),
+            Err(e) => {
+                let e = guard_failure(req, Error::Parse(raw_text(body), e.clone()));
+                request::Outcome::Error((error_status(req, e.status()), e))
             }
-            Err(e) => Outcome::Failure((Status::BadRequest, e)),
         }
     }
 }
@@ -200,20 +1068,48 @@ impl<'r, T: Deserialize<'r>> FromData<'r> for UrlEncoded<T> {
 /// Serializes the wrapped value into UrlEncoding. Returns a response with Content-Type
 /// application/x-www-form-urlencode and a fixed-size body with the serialized value. If serialization
 /// fails, an `Err` of `Status::InternalServerError` is returned.
+///
+/// The pairs are percent-encoded straight into the body as they are
+/// serialized, with [`ser::to_writer_with`].
+///
+/// The Content-Type has a `charset=utf-8` parameter unless
+/// [`EnumFormConfig::response_charset`] is unset.
+///
+/// Rocket sends the size of a fixed-size body as its Content-Length. The
+/// header is therefore not set on the response itself, where Rocket would
+/// send it a second time.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::local::blocking::Client;
+/// use rocket_enumform::UrlEncoded;
+///
+/// #[get("/search")]
+/// fn search() -> UrlEncoded<[(&'static str, &'static str); 1]> {
+///     UrlEncoded([("q", "rocket")])
+/// }
+///
+/// let client = Client::untracked(rocket::build().mount("/", routes![search])).unwrap();
+/// let response = client.get("/search").dispatch();
+/// assert_eq!(response.body().preset_size(), Some("q=rocket".len()));
+/// ```
 impl<'r, T: Serialize> Responder<'r, 'static> for UrlEncoded<T> {
     fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
-        let string = ::serde_urlencoded::to_string(&self.0).map_err(|e| {
+        let config = EnumFormConfig::of(req);
+        let mut body = Vec::new();
+        ser::to_writer_with(&mut body, &self.0, config.ser_options()).map_err(|e| {
             error_!("UrlEncoding failed to serialize: {:?}", e);
             Status::InternalServerError
         })?;
 
-        content::Custom(ContentType::Form, string).respond_to(req)
+        let content_type = config.response_content_type(UrlEncodedCodec::content_type());
+        (content_type, body).respond_to(req)
     }
 }
 
 impl<T: Serialize> UriDisplay<Query> for UrlEncoded<T> {
     fn fmt(&self, f: &mut UriFormatter<'_, Query>) -> fmt::Result {
-        let string = ::serde_urlencoded::to_string(&self.0).map_err(|_| fmt::Error)?;
+        let string = ser::to_string(&self.0).map_err(|_| fmt::Error)?;
         f.write_value(&string)
     }
 }
@@ -259,23 +1155,81 @@ impl<T> DerefMut for UrlEncoded<T> {
     }
 }
 
-impl From<Error<'_>> for form::Error<'_> {
-    fn from(e: Error<'_>) -> Self {
+/// Converts an error deserializing `pairs` into a form error named after the
+/// offending field, relative to `parent`.
+pub(crate) fn form_error<'v>(
+    e: de::Error,
+    pairs: &[(Cow<'v, str>, Cow<'v, str>)],
+    parent: Option<&'v form::Name>,
+) -> form::Error<'v> {
+    let key = e.path().map(String::from);
+    let value = key.as_ref().and_then(|key| {
+        pairs.iter().find_map(|(k, v)| match v {
+            Cow::Borrowed(v) if k == key && !e.is_secret(v) => Some(*v),
+            _ => None,
+        })
+    });
+    let mut error = form::Error::custom(e);
+    if let Some(key) = key {
+        if let Some(value) = value {
+            error.set_value(value);
+        }
+        error.set_name((parent, key));
+    }
+    error
+}
+
+/// Converts a parse error of the urlencoded `input` into a form error.
+fn parse_error<'v>(
+    input: &'v str,
+    e: de::Error,
+    parent: Option<&'v form::Name>,
+) -> form::Error<'v> {
+    let pairs = de::parse_pairs(input.as_bytes());
+    form_error(e, &pairs, parent)
+}
+
+/// Names the offending field where the deserialization error allows.
+impl<'v> From<Error<'v>> for form::Error<'v> {
+    fn from(e: Error<'v>) -> Self {
         match e {
             Error::Io(e) => e.into(),
-            Error::Parse(_, e) => form::Error::custom(e),
+            Error::TooLarge { limit, .. } => (None, Some(limit.as_u64())).into(),
+            Error::Parse(s, e) => parse_error(s, e, None),
+            e @ (Error::ContentType(_)
+            | Error::ContentEncoding(_)
+            | Error::Csrf
+            | Error::Signature) => form::Error::validation(e.to_string()),
+            #[cfg(feature = "validator")]
+            e @ Error::Validation(_) => form::Error::validation(e.to_string()),
         }
     }
 }
 
+/// Fields of forms parse urlencoded values as their data does, except that
+/// text fields, such as those of query strings, use the default
+/// [`de::Options`] rather than the [`EnumFormConfig`] of the application, as
+/// Rocket does not pass the request to them.
 #[rocket::async_trait]
 impl<'v, T: Deserialize<'v> + Send> form::FromFormField<'v> for UrlEncoded<T> {
     fn from_value(field: form::ValueField<'v>) -> Result<Self, form::Errors<'v>> {
-        Ok(Self::from_str(field.value)?)
+        Self::from_str(field.value, de::Options::default()).map_err(|e| match e {
+            Error::Parse(s, e) => parse_error(s, e, Some(field.name.source())).into(),
+            e => e.into(),
+        })
     }
 
     async fn from_data(f: form::DataField<'v, '_>) -> Result<Self, form::Errors<'v>> {
-        Ok(Self::from_data(f.request, f.data).await?)
+        // The data of a field is not the request body, and is not shared.
+        let opts = EnumFormConfig::of(f.request).options();
+        let limit = form_limit(f.request);
+        let capped = read_decoded(f.request, f.data, limit).await?;
+        if !capped.is_complete() {
+            let read = capped.n.written.into();
+            return Err(Error::TooLarge { limit, read }.into());
+        }
+        let body = local_cache!(f.request, capped.into_inner());
+        Ok(Self::from_str(utf8(body)?, opts)?)
     }
 }
 
@@ -310,11 +1264,11 @@ impl<'v, T: Deserialize<'v> + Send> form::FromFormField<'v> for UrlEncoded<T> {
 /// the UrlEncoded map or some number is too big to fit in the expected primitive
 /// type.
 #[inline(always)]
-pub fn from_slice<'a, T>(slice: &'a [u8]) -> Result<T, ::serde_urlencoded::de::Error>
+pub fn from_slice<'a, T>(slice: &'a [u8]) -> Result<T, de::Error>
 where
     T: Deserialize<'a>,
 {
-    ::serde_urlencoded::from_bytes(slice)
+    de::from_bytes(slice)
 }
 
 /// Deserialize an instance of type `T` from a string of UrlEncoded text.
@@ -348,9 +1302,78 @@ where
 /// the UrlEncoded map or some number is too big to fit in the expected primitive
 /// type.
 #[inline(always)]
-pub fn from_str<'a, T>(string: &'a str) -> Result<T, ::serde_urlencoded::de::Error>
+pub fn from_str<'a, T>(string: &'a str) -> Result<T, de::Error>
 where
     T: Deserialize<'a>,
 {
-    ::serde_urlencoded::from_str(string)
+    de::from_str(string)
+}
+
+/// Serialize an instance of type `T` into a string of UrlEncoded text.
+///
+/// Internally tagged enums are written as a tag pair followed by the fields of
+/// the variant, and externally tagged ones keyed by their variant, the same
+/// wire format [`UrlEncoded`] responds with and deserializes from. See [`ser`]
+/// for details and for serializing with options.
+///
+/// # Example
+///
+/// ```
+/// use rocket::serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// #[serde(tag = "type")]
+/// enum Data<'r> {
+///     #[serde(rename = "framework")]
+///     Framework { name: &'r str, stars: usize },
+/// }
+///
+/// let data = Data::Framework { name: "Rocket", stars: 5 };
+/// let string = rocket_enumform::to_string(&data).unwrap();
+/// assert_eq!(string, "type=framework&name=Rocket&stars=5");
+/// ```
+///
+/// # Errors
+///
+/// This conversion can fail if `T` has no representation as key/value pairs,
+/// for example if it is a sequence of structs or a bare string, or if `T`'s
+/// implementation of `Serialize` decides to fail.
+#[inline(always)]
+pub fn to_string<T>(value: &T) -> Result<String, ser::Error>
+where
+    T: Serialize + ?Sized,
+{
+    ser::to_string(value)
+}
+
+/// Serialize an instance of type `T` into bytes of UrlEncoded text.
+///
+/// See [`to_string()`] for the wire format and errors.
+#[inline(always)]
+pub fn to_vec<T>(value: &T) -> Result<Vec<u8>, ser::Error>
+where
+    T: Serialize + ?Sized,
+{
+    ser::to_vec(value)
+}
+
+/// Serialize an instance of type `T` as UrlEncoded text into `writer`.
+///
+/// See [`to_string()`] for the wire format and errors. Failures of `writer`
+/// are reported as errors as well.
+///
+/// # Example
+///
+/// ```
+/// let mut body = Vec::new();
+/// rocket_enumform::to_writer(&mut body, &[("q", "rocket")]).unwrap();
+/// assert_eq!(body, b"q=rocket");
+/// ```
+#[inline(always)]
+pub fn to_writer<W, T>(writer: W, value: &T) -> Result<(), ser::Error>
+where
+    W: io::Write,
+    T: Serialize + ?Sized,
+{
+    ser::to_writer(writer, value)
 }
@@ -0,0 +1,174 @@
+// Copyright (c) 2021 Cognite AS
+//! [`UrlEncoded`](crate::UrlEncoded) guards with a per-route data limit or
+//! exposing truncation.
+
+use std::ops::{Deref, DerefMut};
+
+use rocket::data::{ByteUnit, Capped, Data, FromData, Outcome};
+use rocket::http::ContentType;
+use rocket::request::Request;
+use serde::Deserialize;
+
+use crate::{EnumFormConfig, Error};
+
+/// The Limited guard: [`UrlEncoded`](crate::UrlEncoded) with a data limit of
+/// `BYTES` instead of the configured `limits.enumform` or `limits.form`.
+///
+/// This lets a single route accept larger (or only smaller) bodies while the
+/// rest of the application keeps the configured limit. A body exceeding
+/// `BYTES` fails the guard with `Status::PayloadTooLarge`.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket_enumform::Limited;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Upload {
+///     name: String,
+///     content: String,
+/// }
+///
+/// // Accept up to 5 MiB on this route only.
+/// #[post("/upload", format = "form", data = "<upload>")]
+/// fn upload(upload: Limited<Upload, { 5 * 1024 * 1024 }>) -> String {
+///     upload.into_inner().name
+/// }
+/// ```
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Limited<T, const BYTES: u64>(pub T);
+
+impl<T, const BYTES: u64> Limited<T, BYTES> {
+    /// The data limit of this guard.
+    pub const LIMIT: ByteUnit = ByteUnit::Byte(BYTES);
+
+    /// Consumes the Limited wrapper and returns the wrapped item.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>, const BYTES: u64> FromData<'r> for Limited<T, BYTES> {
+    type Error = Error<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let opts = EnumFormConfig::of(req).options();
+        crate::parse_body(req, data, &ContentType::Form, Self::LIMIT, |s| {
+            crate::decode_form(req, s, opts).map(Limited)
+        })
+        .await
+    }
+}
+
+impl<T, const BYTES: u64> From<T> for Limited<T, BYTES> {
+    fn from(value: T) -> Self {
+        Limited(value)
+    }
+}
+
+impl<T, const BYTES: u64> Deref for Limited<T, BYTES> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T, const BYTES: u64> DerefMut for Limited<T, BYTES> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+/// The CappedUrlEncoded guard: [`UrlEncoded`](crate::UrlEncoded) which does
+/// not fail when the body exceeds the data limit.
+///
+/// The body is read up to the configured `limits.enumform` or `limits.form`
+/// limit and whatever was read is deserialized. The result is wrapped in
+/// Rocket's [`Capped`], so that the route can check
+/// [`is_complete()`](Capped::is_complete) and decide itself how to respond to
+/// an oversized body. Note that the last value of a truncated body may be cut
+/// short. The guard still fails if the data that was read does not
+/// deserialize.
+///
+/// Rocket's own `Capped<UrlEncoded<T>>` cannot be provided outside of Rocket,
+/// hence the separate guard.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket::http::Status;
+/// use rocket_enumform::CappedUrlEncoded;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Comment {
+///     text: String,
+/// }
+///
+/// #[post("/comment", format = "form", data = "<comment>")]
+/// fn comment(comment: CappedUrlEncoded<Comment>) -> (Status, String) {
+///     let comment = comment.into_inner();
+///     match comment.is_complete() {
+///         true => (Status::Ok, comment.into_inner().text),
+///         false => (Status::Accepted, "comment truncated".into()),
+///     }
+/// }
+/// ```
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy)]
+pub struct CappedUrlEncoded<T>(pub Capped<T>);
+
+impl<T> CappedUrlEncoded<T> {
+    /// Consumes the CappedUrlEncoded wrapper and returns the wrapped item.
+    #[inline(always)]
+    pub fn into_inner(self) -> Capped<T> {
+        self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>> FromData<'r> for CappedUrlEncoded<T> {
+    type Error = Error<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let opts = EnumFormConfig::of(req).options();
+        let limit = crate::form_limit(req);
+        if let Err(e) = crate::check_content_type(req, &ContentType::Form) {
+            return crate::data_outcome(req, Err(e));
+        }
+
+        let result = crate::read_capped(req, data, limit).await.and_then(|s| {
+            let n = s.n;
+            crate::decode_form(req, s.into_inner(), opts)
+                .map(|value| CappedUrlEncoded(Capped::new(value, n)))
+        });
+        crate::data_outcome(req, result)
+    }
+}
+
+impl<T> From<Capped<T>> for CappedUrlEncoded<T> {
+    fn from(value: Capped<T>) -> Self {
+        CappedUrlEncoded(value)
+    }
+}
+
+impl<T> Deref for CappedUrlEncoded<T> {
+    type Target = Capped<T>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Capped<T> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for CappedUrlEncoded<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Capped<T> {
+        &mut self.0
+    }
+}
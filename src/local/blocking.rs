@@ -0,0 +1,104 @@
+// Copyright (c) 2021 Cognite AS
+//! Extensions to Rocket's blocking local client.
+
+use rocket::http::ContentType;
+use rocket::local::blocking::{LocalRequest, LocalResponse};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{de, ser};
+
+/// Extension methods for [`LocalRequest`].
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::local::blocking::Client;
+/// use rocket_enumform::local::blocking::LocalRequestExt;
+/// use rocket_enumform::UrlEncoded;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Serialize, Deserialize)]
+/// #[serde(tag = "type")]
+/// enum Body {
+///     #[serde(rename = "variant_one")]
+///     VariantOne { content_one: String },
+/// }
+///
+/// #[post("/form", format = "form", data = "<data>")]
+/// fn body(data: UrlEncoded<Body>) -> String {
+///     format!("{:?}", data.into_inner())
+/// }
+///
+/// let client = Client::tracked(rocket::build().mount("/", routes![body])).unwrap();
+/// let body = Body::VariantOne { content_one: "Hello".into() };
+/// let response = client.post("/form").urlencoded(&body).dispatch();
+/// assert_eq!(response.into_string().unwrap(), r#"VariantOne { content_one: "Hello" }"#);
+/// ```
+pub trait LocalRequestExt: Sized {
+    /// Sets the body to `value` serialized as x-www-form-urlencoded with
+    /// `Content-Type` [`ContentType::Form`].
+    ///
+    /// If `value` fails to serialize, the body is set to empty. The
+    /// `Content-Type` header is _always_ set.
+    fn urlencoded<T: Serialize>(self, value: &T) -> Self;
+
+    /// Like [`urlencoded()`](LocalRequestExt::urlencoded), percent-encoding
+    /// the body with `opts`, such as spaces as `%20`.
+    fn urlencoded_with<T: Serialize>(self, value: &T, opts: ser::Options) -> Self;
+}
+
+impl LocalRequestExt for LocalRequest<'_> {
+    fn urlencoded<T: Serialize>(self, value: &T) -> Self {
+        self.urlencoded_with(value, ser::Options::default())
+    }
+
+    fn urlencoded_with<T: Serialize>(self, value: &T, opts: ser::Options) -> Self {
+        let string = ser::to_string_with(value, opts).unwrap_or_default();
+        self.header(ContentType::Form).body(string)
+    }
+}
+
+/// Extension methods for [`LocalResponse`].
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::local::blocking::Client;
+/// use rocket_enumform::local::blocking::LocalResponseExt;
+/// use rocket_enumform::UrlEncoded;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// #[serde(tag = "type")]
+/// enum Body {
+///     #[serde(rename = "variant_two")]
+///     VariantTwo { content_two: usize },
+/// }
+///
+/// #[get("/form")]
+/// fn body() -> UrlEncoded<Body> {
+///     UrlEncoded(Body::VariantTwo { content_two: 5 })
+/// }
+///
+/// let client = Client::tracked(rocket::build().mount("/", routes![body])).unwrap();
+/// let response = client.get("/form").dispatch();
+/// let body: Body = response.into_urlencoded().unwrap();
+/// assert_eq!(body, Body::VariantTwo { content_two: 5 });
+/// ```
+pub trait LocalResponseExt {
+    /// Consumes `self` and deserializes its body as x-www-form-urlencoded.
+    ///
+    /// If deserialization fails or the body is unset in the response, returns
+    /// `None`. Otherwise, returns `Some`.
+    fn into_urlencoded<T: DeserializeOwned>(self) -> Option<T>;
+}
+
+impl LocalResponseExt for LocalResponse<'_> {
+    fn into_urlencoded<T: DeserializeOwned>(self) -> Option<T> {
+        let string = self.into_string()?;
+        de::from_str(&string).ok()
+    }
+}
@@ -0,0 +1,11 @@
+// Copyright (c) 2021 Cognite AS
+//! Extensions to Rocket's local client for testing form-encoded routes.
+//!
+//! The extension traits mirror the `json()` family of methods on Rocket's
+//! [`LocalRequest`](rocket::local::blocking::LocalRequest) and
+//! [`LocalResponse`](rocket::local::blocking::LocalResponse): bring the trait
+//! of the client flavour in use into scope and call the methods on requests
+//! and responses as usual.
+
+pub mod asynchronous;
+pub mod blocking;
@@ -0,0 +1,39 @@
+// Copyright (c) 2021 Cognite AS
+//! Metrics of the deserialization of form data, emitted through the
+//! [`metrics`](::metrics) facade.
+
+use std::any::type_name;
+use std::time::Instant;
+
+use crate::Error;
+
+/// The counter of deserializations, labeled with their `outcome` and the
+/// `type` deserialized.
+const PARSE_TOTAL: &str = "enumform_parse_total";
+
+/// The histogram of the sizes of the data deserialized in bytes, labeled with
+/// the `type` deserialized.
+const BODY_BYTES: &str = "enumform_parse_body_bytes";
+
+/// The histogram of the durations of deserializations in seconds, labeled
+/// with the `type` deserialized.
+const DURATION_SECONDS: &str = "enumform_parse_duration_seconds";
+
+/// Runs `parse` on `data`, counting it as `enumform_parse_total` with the
+/// `outcome` `success` or `failure`, and recording the size of `data` as
+/// `enumform_parse_body_bytes` and the duration as
+/// `enumform_parse_duration_seconds`. Each is labeled with the `type` `S`.
+pub(crate) fn measured<'r, D, S, F>(data: &'r D, parse: F) -> Result<S, Error<'r>>
+where
+    D: AsRef<[u8]> + ?Sized,
+    F: FnOnce(&'r D) -> Result<S, Error<'r>>,
+{
+    let ty = type_name::<S>();
+    let start = Instant::now();
+    let result = parse(data);
+    let outcome = if result.is_ok() { "success" } else { "failure" };
+    ::metrics::counter!(PARSE_TOTAL, "outcome" => outcome, "type" => ty).increment(1);
+    ::metrics::histogram!(BODY_BYTES, "type" => ty).record(data.as_ref().len() as f64);
+    ::metrics::histogram!(DURATION_SECONDS, "type" => ty).record(start.elapsed().as_secs_f64());
+    result
+}
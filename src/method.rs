@@ -0,0 +1,112 @@
+// Copyright (c) 2021 Cognite AS
+//! Overriding the method of form submissions with a `_method` field.
+
+use rocket::data::Data;
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Method;
+use rocket::request::Request;
+
+use crate::EnumFormConfig;
+
+/// The name of the field which overrides the method of a form submission.
+pub(crate) const METHOD_FIELD: &str = "_method";
+
+/// A fairing which lets HTML forms, which can only `POST`, reach `PUT`,
+/// `PATCH` and `DELETE` routes with a `_method` field.
+///
+/// For `POST` requests with an `application/x-www-form-urlencoded` body, the
+/// request's method is replaced by the value of the `_method` field before
+/// routing, if that method is allowed. Only the first 512 bytes of the body,
+/// the size of Rocket's peek buffer, are searched for the field, so it should
+/// come early in the form. The field is ignored when deserializing the form.
+///
+/// Rocket itself already overrides the method when `_method` is the _first_
+/// field of the form, for any method. This fairing resets such requests to
+/// `POST` when their method is not allowed.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::http::Method;
+/// use rocket_enumform::MethodOverride;
+///
+/// #[put("/item", data = "<item>")]
+/// fn put_item(item: String) -> String {
+///     item
+/// }
+///
+/// #[launch]
+/// fn rocket() -> _ {
+///     rocket::build()
+///         .attach(MethodOverride::new([Method::Put, Method::Delete]))
+///         .mount("/", routes![put_item])
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodOverride {
+    allowed: Vec<Method>,
+}
+
+impl MethodOverride {
+    /// Allows overriding the method with any of `allowed`.
+    pub fn new<I: IntoIterator<Item = Method>>(allowed: I) -> Self {
+        MethodOverride {
+            allowed: allowed.into_iter().collect(),
+        }
+    }
+
+    /// Whether the method may be overridden with `method`.
+    pub fn allows(&self, method: Method) -> bool {
+        self.allowed.contains(&method)
+    }
+}
+
+/// Allows `PUT`, `PATCH` and `DELETE`.
+impl Default for MethodOverride {
+    fn default() -> Self {
+        MethodOverride::new([Method::Put, Method::Patch, Method::Delete])
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for MethodOverride {
+    fn info(&self) -> Info {
+        Info {
+            name: "EnumForm Method Override",
+            kind: Kind::Request,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, data: &mut Data<'_>) {
+        if !req.content_type().is_some_and(|ct| ct.is_form()) {
+            return;
+        }
+
+        let semicolons = EnumFormConfig::of(req).semicolon_separator;
+        let is_separator = |b: &u8| *b == b'&' || (semicolons && *b == b';');
+        let mut peeked = data.peek(crate::PEEK_BYTES).await.to_vec();
+        if !data.peek_complete() {
+            // The last pair may be cut short.
+            let end = peeked.iter().rposition(is_separator).unwrap_or(0);
+            peeked.truncate(end);
+        }
+        let first_is_method = form_urlencoded::parse(&peeked)
+            .next()
+            .is_some_and(|(key, _)| key == METHOD_FIELD);
+        let method = peeked
+            .split(is_separator)
+            .flat_map(form_urlencoded::parse)
+            .find(|(key, _)| key == METHOD_FIELD)
+            .and_then(|(_, value)| value.parse::<Method>().ok());
+
+        match (req.method(), method) {
+            (Method::Post, Some(method)) if self.allows(method) => req.set_method(method),
+            // Undo Rocket's own override from the first field.
+            (current, Some(method))
+                if current == method && first_is_method && !self.allows(method) =>
+            {
+                req.set_method(Method::Post)
+            }
+            _ => {}
+        }
+    }
+}
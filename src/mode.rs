@@ -0,0 +1,183 @@
+// Copyright (c) 2021 Cognite AS
+//! Lenient and strict variants of the [`UrlEncoded`](crate::UrlEncoded) guard.
+
+use std::ops::{Deref, DerefMut};
+
+use rocket::data::{Data, FromData, Outcome};
+use rocket::http::ContentType;
+use rocket::request::Request;
+use serde::Deserialize;
+
+use crate::de::Options;
+use crate::{EnumFormConfig, Error};
+
+/// The configured options of `req`, with the leniency of [`Options::LENIENT`].
+fn lenient(req: &Request<'_>) -> Options {
+    Options {
+        empty_as_none: Options::LENIENT.empty_as_none,
+        deny_unknown_fields: Options::LENIENT.deny_unknown_fields,
+        ..EnumFormConfig::of(req).options()
+    }
+}
+
+/// The configured options of `req`, with the strictness of [`Options::STRICT`].
+fn strict(req: &Request<'_>) -> Options {
+    Options {
+        empty_as_none: Options::STRICT.empty_as_none,
+        deny_unknown_fields: Options::STRICT.deny_unknown_fields,
+        ..EnumFormConfig::of(req).options()
+    }
+}
+
+macro_rules! mode_guard {
+    ($(#[$attr:meta])* $name:ident, $opts:path) => {
+        $(#[$attr])*
+        #[repr(transparent)]
+        #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name<T>(pub T);
+
+        impl<T> $name<T> {
+            #[doc = concat!("Consumes the ", stringify!($name), " wrapper and returns the wrapped item.")]
+            #[inline(always)]
+            pub fn into_inner(self) -> T {
+                self.0
+            }
+        }
+
+        #[rocket::async_trait]
+        impl<'r, T: Deserialize<'r>> FromData<'r> for $name<T> {
+            type Error = Error<'r>;
+
+            async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+                let opts = $opts(req);
+                let limit = crate::form_limit(req);
+                crate::parse_body(req, data, &ContentType::Form, limit, |s| {
+                    crate::decode_form(req, s, opts).map($name)
+                })
+                .await
+            }
+        }
+
+        impl<T> From<T> for $name<T> {
+            fn from(value: T) -> Self {
+                $name(value)
+            }
+        }
+
+        impl<T> Deref for $name<T> {
+            type Target = T;
+
+            #[inline(always)]
+            fn deref(&self) -> &T {
+                &self.0
+            }
+        }
+
+        impl<T> DerefMut for $name<T> {
+            #[inline(always)]
+            fn deref_mut(&mut self) -> &mut T {
+                &mut self.0
+            }
+        }
+    };
+}
+
+mode_guard!(
+    /// The LenientUrlEncoded guard: [`UrlEncoded`](crate::UrlEncoded) with
+    /// the leniency of [`Options::LENIENT`]. The other options, such as the
+    /// limits, are configured by [`EnumFormConfig`].
+    ///
+    /// Keys which are not fields of `T` are ignored and empty values of
+    /// optional fields, as submitted for blank text inputs, are `None` instead
+    /// of failing to parse.
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// # use serde::Deserialize;
+    /// use rocket_enumform::LenientUrlEncoded;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Search {
+    ///     query: String,
+    ///     limit: Option<usize>,
+    /// }
+    ///
+    /// // `query=rocket&limit=` yields `limit: None`.
+    /// #[post("/search", format = "form", data = "<search>")]
+    /// fn search(search: LenientUrlEncoded<Search>) -> String {
+    ///     format!("{:?}", search.into_inner())
+    /// }
+    /// ```
+    LenientUrlEncoded,
+    lenient
+);
+
+mode_guard!(
+    /// The StrictUrlEncoded guard: [`UrlEncoded`](crate::UrlEncoded) with
+    /// the strictness of [`Options::STRICT`], which rejects keys that are
+    /// not fields of `T`, except within flattened structs and internally
+    /// tagged enums. The other options, such as the limits, are configured
+    /// by [`EnumFormConfig`].
+    ///
+    /// Keys which are not fields of `T` fail the guard with
+    /// `Status::UnprocessableEntity`, and are all listed by
+    /// [`de::Error::unknown_fields()`](crate::de::Error::unknown_fields).
+    /// Serde buffers the fields of flattened structs and internally tagged
+    /// enums before handing them to their type, which ignores unknown keys
+    /// out of sight of the guard: an internally tagged enum needs
+    /// `#[serde(deny_unknown_fields)]` to reject them, and a struct with
+    /// flattened fields accepts them.
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// # use serde::Deserialize;
+    /// use rocket_enumform::StrictUrlEncoded;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Login {
+    ///     user: String,
+    ///     password: String,
+    /// }
+    ///
+    /// #[post("/login", format = "form", data = "<login>")]
+    /// fn login(login: StrictUrlEncoded<Login>) -> String {
+    ///     login.into_inner().user
+    /// }
+    /// ```
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// # use serde::Deserialize;
+    /// use rocket::http::{ContentType, Status};
+    /// use rocket::local::blocking::Client;
+    /// use rocket_enumform::StrictUrlEncoded;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// #[serde(tag = "type")]
+    /// enum Payment {
+    ///     Card { number: String },
+    /// }
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// #[serde(tag = "type", deny_unknown_fields)]
+    /// enum Refund {
+    ///     Card { number: String },
+    /// }
+    ///
+    /// #[post("/pay", data = "<payment>")]
+    /// fn pay(payment: StrictUrlEncoded<Payment>) {}
+    ///
+    /// #[post("/refund", data = "<refund>")]
+    /// fn refund(refund: StrictUrlEncoded<Refund>) {}
+    ///
+    /// let client = Client::untracked(rocket::build().mount("/", routes![pay, refund])).unwrap();
+    /// let post = |uri| {
+    ///     let body = "type=Card&number=4242&extra=1";
+    ///     client.post(uri).header(ContentType::Form).body(body).dispatch().status()
+    /// };
+    /// assert_eq!(post("/pay"), Status::Ok);
+    /// assert_eq!(post("/refund"), Status::UnprocessableEntity);
+    /// ```
+    StrictUrlEncoded,
+    strict
+);
@@ -0,0 +1,155 @@
+// Copyright (c) 2021 Cognite AS
+//! A form field for keys submitted more than once.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use serde::de::{self, Deserialize, Deserializer, IntoDeserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use crate::de::MULTI_SELECT;
+
+/// The MultiSelect field: all the values submitted for a key, such as the
+/// selected options of a `<select multiple>` or checkboxes sharing a name.
+///
+/// `tags=a&tags=b` deserializes into a `tags: MultiSelect<String>` field as
+/// `["a", "b"]` without a [`Duplicates`](crate::de::Duplicates) policy, where
+/// other fields submitted more than once fail. A policy, if set, applies to a
+/// MultiSelect as well. A key submitted once is a selection of one value, and
+/// a missing key fails like any other field unless it is marked
+/// `#[serde(default)]`.
+///
+/// The fields of flattened structs and internally tagged enums are buffered by
+/// serde before they are deserialized, so that a MultiSelect there receives
+/// more than one value only with
+/// [`Duplicates::Collect`](crate::de::Duplicates::Collect).
+///
+/// It serializes as a sequence, repeating its key once per value.
+///
+/// ```rust
+/// # use serde::Deserialize;
+/// use rocket_enumform::MultiSelect;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Filter {
+///     #[serde(default)]
+///     tags: MultiSelect<String>,
+///     limit: usize,
+/// }
+///
+/// let filter: Filter = rocket_enumform::from_str("tags=a&limit=5&tags=b").unwrap();
+/// assert_eq!(*filter.tags, ["a", "b"]);
+/// let filter: Filter = rocket_enumform::from_str("limit=5").unwrap();
+/// assert!(filter.tags.is_empty());
+/// ```
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MultiSelect<T>(pub Vec<T>);
+
+impl<T> MultiSelect<T> {
+    /// Consumes the MultiSelect wrapper and returns the values.
+    #[inline(always)]
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> Default for MultiSelect<T> {
+    fn default() -> Self {
+        MultiSelect(Vec::new())
+    }
+}
+
+impl<T> From<Vec<T>> for MultiSelect<T> {
+    fn from(values: Vec<T>) -> Self {
+        MultiSelect(values)
+    }
+}
+
+impl<T> Deref for MultiSelect<T> {
+    type Target = Vec<T>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Vec<T> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for MultiSelect<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.0
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for MultiSelect<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(MULTI_SELECT, MultiSelectVisitor(PhantomData))
+    }
+}
+
+struct MultiSelectVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for MultiSelectVisitor<T> {
+    type Value = MultiSelect<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("one or more values")
+    }
+
+    /// Other deserializers, which buffer one or more values.
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(MultiSelect(values))
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        single(v.into_deserializer())
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        single(v.into_deserializer())
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        single(v.into_deserializer())
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        single(v.into_deserializer())
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        single(de::value::StrDeserializer::new(v))
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        single(v.into_deserializer())
+    }
+}
+
+/// A selection of the one value of `deserializer`.
+fn single<'de, T, D>(deserializer: D) -> Result<MultiSelect<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(|value| MultiSelect(vec![value]))
+}
+
+impl<T: Serialize> Serialize for MultiSelect<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
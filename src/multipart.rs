@@ -0,0 +1,360 @@
+// Copyright (c) 2021 Cognite AS
+//! Serde-based guard for `multipart/form-data` requests.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::{fmt, io};
+
+use rocket::data::{Data, FromData, Limits, Outcome};
+use rocket::form::{self, DataField, Form, FromForm, FromFormField, Options, ValueField};
+use rocket::http::ContentType;
+use rocket::request::Request;
+use rocket::tokio::{fs, task};
+use serde::de::{self as serde_de, Deserializer, Visitor};
+use serde::Deserialize;
+use tempfile::{NamedTempFile, TempPath};
+
+use crate::{de, EnumFormConfig, Error};
+
+/// The MultipartEncoded guard: consume multipart/form-data requests with serde.
+///
+/// Browsers switch to multipart/form-data as soon as a form contains a file
+/// input. `MultipartEncoded` parses such bodies with Rocket's own form parser,
+/// collects the text parts into key/value pairs and deserializes `T` from them
+/// exactly like [`UrlEncoded`](crate::UrlEncoded) does, so the same tagged
+/// enums work for both encodings.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket_enumform::multipart::MultipartEncoded;
+///
+/// #[derive(Debug, Deserialize)]
+/// #[serde(tag = "type")]
+/// enum Body {
+///     #[serde(rename = "variant_one")]
+///     VariantOne { content_one: String },
+///     #[serde(rename = "variant_two")]
+///     VariantTwo { content_two: usize },
+/// }
+///
+/// #[post("/form", format = "multipart", data = "<data>")]
+/// fn body(data: MultipartEncoded<Body>) -> String {
+///     format!("{:?}", data.into_inner())
+/// }
+/// ```
+///
+/// Parts without a Content-Type, and `text/*` parts without a file name, are
+/// collected as text. All other parts are files, which are streamed to disk
+/// and can be received with [`FormFile`] fields. Rocket's `limits.data-form`,
+/// `limits.string` and `limits.file` limits apply. The guard fails with the
+/// [`form::Errors`] reported by Rocket, or with `Status::UnprocessableEntity`
+/// if the collected pairs do not deserialize as `T`.
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MultipartEncoded<T>(pub T);
+
+impl<T> MultipartEncoded<T> {
+    /// Consumes the MultipartEncoded wrapper and returns the wrapped item.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rocket_enumform::multipart::MultipartEncoded;
+    /// let string = "Hello".to_string();
+    /// let outer = MultipartEncoded(string);
+    /// assert_eq!(outer.into_inner(), "Hello".to_string());
+    /// ```
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// A file part of a [`MultipartEncoded`] form.
+///
+/// `FormFile` is a field type: declare fields of this type, or `Option<FormFile>`
+/// for optional file inputs, anywhere in the `T` of a `MultipartEncoded<T>`,
+/// including inside enum variants.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket_enumform::multipart::{FormFile, MultipartEncoded};
+///
+/// #[derive(Debug, Deserialize)]
+/// #[serde(tag = "type")]
+/// enum Upload {
+///     #[serde(rename = "avatar")]
+///     Avatar { image: FormFile },
+///     #[serde(rename = "document")]
+///     Document { title: String, file: Option<FormFile> },
+/// }
+///
+/// #[post("/upload", format = "multipart", data = "<upload>")]
+/// async fn upload(upload: MultipartEncoded<Upload>) -> std::io::Result<()> {
+///     if let Upload::Avatar { mut image } = upload.into_inner() {
+///         image.persist_to("/srv/avatar").await?;
+///     }
+///     Ok(())
+/// }
+/// ```
+///
+/// File parts are streamed into `temp_dir` under the `limits.file` limit, or
+/// `limits.file/$ext` for the extension of the part's Content-Type. A file
+/// which is not persisted is deleted once the request has been handled. A
+/// file input left empty by the browser is treated as absent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormFile {
+    name: Option<String>,
+    content_type: ContentType,
+    path: PathBuf,
+    len: u64,
+}
+
+/// Temporary files of the request, removed when the request is dropped.
+#[derive(Default)]
+struct TempPaths(Mutex<Vec<TempPath>>);
+
+impl FormFile {
+    async fn from_field<'r>(field: DataField<'r, '_>) -> Result<Option<Self>, form::Error<'r>> {
+        let raw_name = field
+            .file_name
+            .map(|n| n.dangerous_unsafe_unsanitized_raw());
+        if raw_name.is_some_and(|n| n.is_empty()) {
+            return Ok(None);
+        }
+
+        let req = field.request;
+        let limit = field
+            .content_type
+            .extension()
+            .and_then(|ext| req.limits().find(["file", ext.as_str()]))
+            .or_else(|| req.limits().get("file"))
+            .unwrap_or(Limits::FILE);
+
+        let temp_dir = req.rocket().config().temp_dir.relative();
+        let file = task::spawn_blocking(move || NamedTempFile::new_in(temp_dir))
+            .await
+            .map_err(|_| io::Error::other("spawn_blocking panic"))??;
+        let (file, temp_path) = file.into_parts();
+        let path = temp_path.to_path_buf();
+        req.local_cache(TempPaths::default)
+            .0
+            .lock()
+            .expect("temp path lock")
+            .push(temp_path);
+
+        let mut file = fs::File::from_std(file);
+        let n = field.data.open(limit).stream_to(&mut file).await?;
+        if !n.complete {
+            let (min, max) = (None, Some(limit.as_u64()));
+            return Err(form::error::ErrorKind::InvalidLength { min, max }.into());
+        }
+
+        Ok(Some(FormFile {
+            name: field.file_name.and_then(|n| n.as_str()).map(String::from),
+            content_type: field.content_type,
+            path,
+            len: n.written,
+        }))
+    }
+
+    /// The sanitized file name submitted by the client, if any. See
+    /// [`FileName::as_str()`](rocket::fs::FileName::as_str).
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The Content-Type of the part.
+    pub fn content_type(&self) -> &ContentType {
+        &self.content_type
+    }
+
+    /// The size of the file in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether the file is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The current location of the file on disk.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Opens the file for reading.
+    pub async fn open(&self) -> io::Result<fs::File> {
+        fs::File::open(&self.path).await
+    }
+
+    /// Moves the file to `path` so that it is kept after the request. As with
+    /// [`TempFile::persist_to()`](rocket::fs::TempFile::persist_to), `path`
+    /// must be on the same filesystem as `temp_dir`.
+    pub async fn persist_to<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        fs::rename(&self.path, path).await?;
+        self.path = path.to_path_buf();
+        Ok(())
+    }
+
+    /// Copies the file to `path`; the temporary file is still deleted after
+    /// the request.
+    pub async fn copy_to<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::copy(&self.path, path).await.map(|_| ())
+    }
+}
+
+/// Marks the value of a pair which refers to a file part.
+const FILE_TOKEN: &str = "\0rocket-enumform-file:";
+
+thread_local! {
+    /// The files of the form currently being deserialized.
+    static FILES: RefCell<Vec<FormFile>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs `f` with `files` resolvable by [`FormFile`]'s `Deserialize` impl.
+fn with_files<R>(files: Vec<FormFile>, f: impl FnOnce() -> R) -> R {
+    FILES.with(|cell| *cell.borrow_mut() = files);
+    let result = f();
+    FILES.with(|cell| cell.borrow_mut().clear());
+    result
+}
+
+impl<'de> Deserialize<'de> for FormFile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FileVisitor;
+
+        impl<'de> Visitor<'de> for FileVisitor {
+            type Value = FormFile;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a file upload")
+            }
+
+            fn visit_str<E: serde_de::Error>(self, value: &str) -> Result<FormFile, E> {
+                value
+                    .strip_prefix(FILE_TOKEN)
+                    .and_then(|index| index.parse::<usize>().ok())
+                    .and_then(|index| FILES.with(|cell| cell.borrow().get(index).cloned()))
+                    .ok_or_else(|| E::invalid_type(serde_de::Unexpected::Str(value), &self))
+            }
+        }
+
+        deserializer.deserialize_str(FileVisitor)
+    }
+}
+
+/// The parts of a form, in submission order.
+struct Parts<'r> {
+    pairs: de::Pairs<'r>,
+    files: Vec<FormFile>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromForm<'r> for Parts<'r> {
+    type Context = (Parts<'r>, form::Errors<'r>);
+
+    fn init(_opts: Options) -> Self::Context {
+        let parts = Parts {
+            pairs: Vec::new(),
+            files: Vec::new(),
+        };
+        (parts, form::Errors::new())
+    }
+
+    fn push_value((parts, _): &mut Self::Context, field: ValueField<'r>) {
+        let name = field.name.source().as_str();
+        parts
+            .pairs
+            .push((Cow::Borrowed(name), Cow::Borrowed(field.value)));
+    }
+
+    async fn push_data((parts, errors): &mut Self::Context, field: DataField<'r, '_>) {
+        let name = field.name.source().as_str();
+        if field.file_name.is_some() || field.content_type.top() != "text" {
+            match FormFile::from_field(field).await {
+                Ok(Some(file)) => {
+                    let token = format!("{}{}", FILE_TOKEN, parts.files.len());
+                    parts.files.push(file);
+                    parts.pairs.push((Cow::Borrowed(name), Cow::Owned(token)));
+                }
+                Ok(None) => {}
+                Err(e) => errors.push(e.with_name(name)),
+            }
+            return;
+        }
+
+        match <String as FromFormField<'r>>::from_data(field).await {
+            Ok(value) => parts.pairs.push((Cow::Borrowed(name), Cow::Owned(value))),
+            Err(e) => errors.extend(e.with_name(name)),
+        }
+    }
+
+    fn push_error((_, errors): &mut Self::Context, error: form::Error<'r>) {
+        errors.push(error);
+    }
+
+    fn finalize((parts, errors): Self::Context) -> form::Result<'r, Self> {
+        if errors.is_empty() {
+            Ok(parts)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>> FromData<'r> for MultipartEncoded<T> {
+    type Error = form::Errors<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let parts = match Form::<Parts<'r>>::from_data(req, data).await {
+            Outcome::Success(parts) => parts.into_inner(),
+            Outcome::Error(e) => return Outcome::Error(e),
+            Outcome::Forward(f) => return Outcome::Forward(f),
+        };
+
+        let Parts { pairs, files } = parts;
+        let opts = EnumFormConfig::of(req).options();
+        match with_files(files, || de::from_pairs_with(pairs.clone(), opts)) {
+            Ok(value) => Outcome::Success(MultipartEncoded(value)),
+            Err(e) => {
+                // Multipart data has no raw form.
+                crate::guard_failure(req, Error::Parse("", e.clone()));
+                let status = crate::error_status(req, crate::parse_status(&e));
+                let field = e.path().map(String::from);
+                crate::failure::store(req, status, e.code(), e.to_string(), field);
+                let errors = form::Errors::from(crate::form_error(e, &pairs, None));
+                Outcome::Error((status, errors))
+            }
+        }
+    }
+}
+
+impl<T> From<T> for MultipartEncoded<T> {
+    fn from(value: T) -> Self {
+        MultipartEncoded(value)
+    }
+}
+
+impl<T> Deref for MultipartEncoded<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for MultipartEncoded<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
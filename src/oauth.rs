@@ -0,0 +1,485 @@
+// Copyright (c) 2021 Cognite AS
+//! Types for OAuth 2.0 token endpoints, whose requests are forms tagged by
+//! their `grant_type`, as specified by
+//! [RFC 6749](https://www.rfc-editor.org/rfc/rfc6749) and, for the device
+//! code grant, [RFC 8628](https://www.rfc-editor.org/rfc/rfc8628), and for
+//! the other endpoints of an authorization server: authorization with
+//! [PKCE](https://www.rfc-editor.org/rfc/rfc7636) and the parameters of
+//! [OpenID Connect](https://openid.net/specs/openid-connect-core-1_0.html),
+//! [revocation](https://www.rfc-editor.org/rfc/rfc7009) and
+//! [introspection](https://www.rfc-editor.org/rfc/rfc7662).
+//!
+//! Client credentials sent in the body, `client_id` and `client_secret`, are
+//! fields of the grants. Those sent with HTTP Basic authentication are left
+//! to the application.
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! use rocket::http::ContentType;
+//! use rocket_enumform::oauth::{ErrorCode, ErrorResponse, TokenRequest, TokenResponse};
+//! use rocket_enumform::UrlEncoded;
+//!
+//! #[post("/token", data = "<request>")]
+//! fn token(request: UrlEncoded<TokenRequest>) -> (ContentType, String) {
+//!     let response = match request.into_inner() {
+//!         TokenRequest::ClientCredentials(grant) if grant.client_id.as_deref() == Some("cli") => {
+//!             serde_json::to_string(&TokenResponse::bearer("token", Some(3600)))
+//!         }
+//!         _ => serde_json::to_string(&ErrorResponse::new(ErrorCode::UnauthorizedClient)),
+//!     };
+//!     (ContentType::JSON, response.unwrap())
+//! }
+//! ```
+//!
+//! This module is only available with the `oauth` feature enabled.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64URL;
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{Delimited, Secret};
+
+/// A space-delimited scope, such as `openid profile email`.
+pub type Scope = Delimited<Vec<String>, ' '>;
+
+/// A request to a token endpoint, tagged by its `grant_type`.
+///
+/// ```rust
+/// use rocket_enumform::de;
+/// use rocket_enumform::oauth::TokenRequest;
+///
+/// let body = "grant_type=refresh_token&refresh_token=tGzv3JOkF0XG5Qx2TlKWIA&scope=read";
+/// match de::from_str(body).unwrap() {
+///     TokenRequest::RefreshToken(grant) => assert_eq!(*grant.scope.unwrap(), ["read"]),
+///     request => panic!("unexpected {}", request.grant_type()),
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "grant_type")]
+pub enum TokenRequest {
+    /// `authorization_code`: exchanges the code of an authorization
+    /// response.
+    #[serde(rename = "authorization_code")]
+    AuthorizationCode(AuthorizationCodeGrant),
+
+    /// `client_credentials`: authenticates the client itself.
+    #[serde(rename = "client_credentials")]
+    ClientCredentials(ClientCredentialsGrant),
+
+    /// `refresh_token`: exchanges a refresh token for a new access token.
+    #[serde(rename = "refresh_token")]
+    RefreshToken(RefreshTokenGrant),
+
+    /// `password`: authenticates with the credentials of the resource owner.
+    #[serde(rename = "password")]
+    Password(PasswordGrant),
+
+    /// `urn:ietf:params:oauth:grant-type:device_code`: polls for the
+    /// authorization of a device.
+    #[serde(rename = "urn:ietf:params:oauth:grant-type:device_code")]
+    DeviceCode(DeviceCodeGrant),
+}
+
+impl TokenRequest {
+    /// The `grant_type` of the request.
+    pub fn grant_type(&self) -> &'static str {
+        match self {
+            TokenRequest::AuthorizationCode(_) => "authorization_code",
+            TokenRequest::ClientCredentials(_) => "client_credentials",
+            TokenRequest::RefreshToken(_) => "refresh_token",
+            TokenRequest::Password(_) => "password",
+            TokenRequest::DeviceCode(_) => "urn:ietf:params:oauth:grant-type:device_code",
+        }
+    }
+
+    /// The `client_id` of the request, if sent in the body.
+    pub fn client_id(&self) -> Option<&str> {
+        match self {
+            TokenRequest::AuthorizationCode(grant) => grant.client_id.as_deref(),
+            TokenRequest::ClientCredentials(grant) => grant.client_id.as_deref(),
+            TokenRequest::RefreshToken(grant) => grant.client_id.as_deref(),
+            TokenRequest::Password(grant) => grant.client_id.as_deref(),
+            TokenRequest::DeviceCode(grant) => Some(&grant.client_id),
+        }
+    }
+}
+
+/// The `authorization_code` grant, [RFC 6749, section
+/// 4.1.3](https://www.rfc-editor.org/rfc/rfc6749#section-4.1.3).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AuthorizationCodeGrant {
+    /// The code of the authorization response.
+    pub code: String,
+    /// The `redirect_uri` of the authorization request, if it had one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirect_uri: Option<String>,
+    /// The PKCE code verifier of the `code_challenge` of the authorization
+    /// request, see [`CodeChallengeMethod::verify`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_verifier: Option<Secret<String>>,
+    /// The identifier of the client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// The secret of the client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<Secret<String>>,
+}
+
+/// The `client_credentials` grant, [RFC 6749, section
+/// 4.4.2](https://www.rfc-editor.org/rfc/rfc6749#section-4.4.2).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ClientCredentialsGrant {
+    /// The scope requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<Scope>,
+    /// The identifier of the client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// The secret of the client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<Secret<String>>,
+}
+
+/// The `refresh_token` grant, [RFC 6749, section
+/// 6](https://www.rfc-editor.org/rfc/rfc6749#section-6).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RefreshTokenGrant {
+    /// The refresh token issued to the client.
+    pub refresh_token: Secret<String>,
+    /// The scope requested, within the one granted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<Scope>,
+    /// The identifier of the client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// The secret of the client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<Secret<String>>,
+}
+
+/// The `password` grant, [RFC 6749, section
+/// 4.3.2](https://www.rfc-editor.org/rfc/rfc6749#section-4.3.2).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PasswordGrant {
+    /// The username of the resource owner.
+    pub username: String,
+    /// The password of the resource owner.
+    pub password: Secret<String>,
+    /// The scope requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<Scope>,
+    /// The identifier of the client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// The secret of the client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<Secret<String>>,
+}
+
+/// The device code grant, [RFC 8628, section
+/// 3.4](https://www.rfc-editor.org/rfc/rfc8628#section-3.4).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DeviceCodeGrant {
+    /// The device code of the device authorization response.
+    pub device_code: String,
+    /// The identifier of the client.
+    pub client_id: String,
+}
+
+/// A successful token response, [RFC 6749, section
+/// 5.1](https://www.rfc-editor.org/rfc/rfc6749#section-5.1), which is sent as
+/// JSON.
+///
+/// ```rust
+/// use rocket_enumform::oauth::TokenResponse;
+///
+/// let response = TokenResponse::bearer("2YotnFZFEjr1zCsicMWpAA", Some(3600));
+/// assert_eq!(
+///     serde_json::to_string(&response).unwrap(),
+///     r#"{"access_token":"2YotnFZFEjr1zCsicMWpAA","token_type":"Bearer","expires_in":3600}"#
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TokenResponse {
+    /// The access token issued.
+    pub access_token: String,
+    /// The type of the token, such as `Bearer`.
+    pub token_type: String,
+    /// The lifetime of the access token in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_in: Option<u64>,
+    /// A refresh token, to obtain new access tokens with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// The scope granted, if it differs from the one requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<Scope>,
+    /// The OpenID Connect ID token, for requests with the `openid` scope.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+}
+
+impl TokenResponse {
+    /// A response issuing the bearer token `access_token`, which expires in
+    /// `expires_in` seconds.
+    pub fn bearer(access_token: impl Into<String>, expires_in: Option<u64>) -> Self {
+        TokenResponse {
+            access_token: access_token.into(),
+            token_type: "Bearer".to_string(),
+            expires_in,
+            refresh_token: None,
+            scope: None,
+            id_token: None,
+        }
+    }
+}
+
+/// An error response, [RFC 6749, section
+/// 5.2](https://www.rfc-editor.org/rfc/rfc6749#section-5.2), which is sent as
+/// JSON with `400 Bad Request`, or `401 Unauthorized` for
+/// [`ErrorCode::InvalidClient`] with HTTP authentication.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ErrorResponse {
+    /// The error.
+    pub error: ErrorCode,
+    /// A description of the error for the developer of the client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_description: Option<String>,
+    /// A page describing the error.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_uri: Option<String>,
+}
+
+impl ErrorResponse {
+    /// A response with `error` and no description.
+    pub fn new(error: ErrorCode) -> Self {
+        ErrorResponse {
+            error,
+            error_description: None,
+            error_uri: None,
+        }
+    }
+
+    /// Sets the description of the error.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.error_description = Some(description.into());
+        self
+    }
+}
+
+/// The `error` of an [`ErrorResponse`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The request is missing a parameter or is otherwise malformed.
+    InvalidRequest,
+    /// The client failed to authenticate.
+    InvalidClient,
+    /// The grant or refresh token is invalid, expired or revoked.
+    InvalidGrant,
+    /// The client may not use the grant type.
+    UnauthorizedClient,
+    /// The server does not support the grant type.
+    UnsupportedGrantType,
+    /// The scope requested is invalid or exceeds the one granted.
+    InvalidScope,
+    /// The device authorization is still pending, RFC 8628.
+    AuthorizationPending,
+    /// The device polls too often, RFC 8628.
+    SlowDown,
+    /// The authorization was denied, RFC 8628.
+    AccessDenied,
+    /// The device code expired, RFC 8628.
+    ExpiredToken,
+}
+
+/// A request to the authorization endpoint, [RFC 6749, section
+/// 4.1.1](https://www.rfc-editor.org/rfc/rfc6749#section-4.1.1), sent in the
+/// query, with the parameters of PKCE and OpenID Connect.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket_enumform::oauth::{AuthorizationRequest, CodeChallengeMethod};
+/// use rocket_enumform::QueryEncoded;
+///
+/// #[get("/authorize")]
+/// fn authorize(request: QueryEncoded<AuthorizationRequest>) -> String {
+///     let method = request.code_challenge_method.unwrap_or_default();
+///     format!("{} {:?}", request.client_id, method)
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AuthorizationRequest {
+    /// The response requested, such as `code`, or `code id_token` with
+    /// OpenID Connect.
+    pub response_type: Scope,
+    /// The identifier of the client.
+    pub client_id: String,
+    /// The URI to redirect to with the response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirect_uri: Option<String>,
+    /// The scope requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<Scope>,
+    /// A value to return in the response, against cross-site request
+    /// forgery.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    /// The PKCE code challenge, derived from the `code_verifier` of the token
+    /// request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_challenge: Option<String>,
+    /// How the code challenge is derived, `plain` if left out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_challenge_method: Option<CodeChallengeMethod>,
+    /// The OpenID Connect value to include in the ID token, against replays.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    /// The OpenID Connect prompts requested, such as `login consent`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<Scope>,
+    /// The OpenID Connect maximum age of the authentication, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<u64>,
+    /// The OpenID Connect hint of the identifier the user logs in with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub login_hint: Option<String>,
+}
+
+/// How a PKCE code challenge is derived from its code verifier, [RFC 7636,
+/// section 4.2](https://www.rfc-editor.org/rfc/rfc7636#section-4.2).
+///
+/// ```rust
+/// use rocket_enumform::oauth::CodeChallengeMethod;
+///
+/// let verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+/// let challenge = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+/// assert!(CodeChallengeMethod::S256.verify(verifier, challenge));
+/// assert!(!CodeChallengeMethod::Plain.verify(verifier, challenge));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum CodeChallengeMethod {
+    /// `plain`: the challenge is the verifier.
+    #[default]
+    #[serde(rename = "plain")]
+    Plain,
+
+    /// `S256`: the challenge is the unpadded base64url of the SHA-256 of the
+    /// verifier.
+    S256,
+}
+
+impl CodeChallengeMethod {
+    /// Whether `code_challenge` is derived from `code_verifier` with this
+    /// method.
+    pub fn verify(self, code_verifier: &str, code_challenge: &str) -> bool {
+        match self {
+            CodeChallengeMethod::Plain => code_verifier == code_challenge,
+            CodeChallengeMethod::S256 => {
+                BASE64URL.encode(Sha256::digest(code_verifier.as_bytes())) == code_challenge
+            }
+        }
+    }
+}
+
+/// A request to the revocation endpoint, [RFC 7009, section
+/// 2.1](https://www.rfc-editor.org/rfc/rfc7009#section-2.1).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RevocationRequest {
+    /// The token to revoke.
+    pub token: Secret<String>,
+    /// The type of the token, if the client gives it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_type_hint: Option<TokenTypeHint>,
+    /// The identifier of the client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// The secret of the client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<Secret<String>>,
+}
+
+/// A request to the introspection endpoint, [RFC 7662, section
+/// 2.1](https://www.rfc-editor.org/rfc/rfc7662#section-2.1).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct IntrospectionRequest {
+    /// The token to introspect.
+    pub token: Secret<String>,
+    /// The type of the token, if the client gives it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_type_hint: Option<TokenTypeHint>,
+    /// The identifier of the client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// The secret of the client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<Secret<String>>,
+}
+
+/// The `token_type_hint` of a revocation or introspection request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenTypeHint {
+    /// An access token.
+    AccessToken,
+    /// A refresh token.
+    RefreshToken,
+}
+
+/// The response of the introspection endpoint, [RFC 7662, section
+/// 2.2](https://www.rfc-editor.org/rfc/rfc7662#section-2.2), which is sent as
+/// JSON.
+///
+/// ```rust
+/// use rocket_enumform::oauth::IntrospectionResponse;
+///
+/// let response = IntrospectionResponse::inactive();
+/// assert_eq!(serde_json::to_string(&response).unwrap(), r#"{"active":false}"#);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct IntrospectionResponse {
+    /// Whether the token is active.
+    pub active: bool,
+    /// The scope of the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<Scope>,
+    /// The identifier of the client the token was issued to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// The username of the resource owner.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// The type of the token, such as `Bearer`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    /// When the token expires, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<u64>,
+    /// When the token was issued, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat: Option<u64>,
+    /// When the token becomes valid, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<u64>,
+    /// The subject of the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    /// The intended audience of the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    /// The issuer of the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// The identifier of the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+}
+
+impl IntrospectionResponse {
+    /// The response for a token which is not active, without any other
+    /// member.
+    pub fn inactive() -> Self {
+        Self::default()
+    }
+}
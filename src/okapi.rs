@@ -0,0 +1,72 @@
+// Copyright (c) 2021 Cognite AS
+//! Documenting the bodies of [`UrlEncoded`] and [`UrlEncodedOwned`] in the
+//! OpenAPI specs of [`rocket_okapi`].
+//!
+//! This module is only available with the `okapi` feature enabled.
+
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::okapi::openapi3::{MediaType, RequestBody};
+use rocket_okapi::okapi::Map;
+use rocket_okapi::request::OpenApiFromData;
+use rocket_okapi::Result;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::{UrlEncoded, UrlEncodedOwned};
+
+/// A required `application/x-www-form-urlencoded` body with the schema of
+/// `T`.
+fn request_body<T: JsonSchema>(gen: &mut OpenApiGenerator) -> Result<RequestBody> {
+    let media_type = MediaType {
+        schema: Some(gen.json_schema::<T>()),
+        ..MediaType::default()
+    };
+    let mut content = Map::new();
+    content.insert("application/x-www-form-urlencoded".to_string(), media_type);
+    Ok(RequestBody {
+        content,
+        required: true,
+        ..RequestBody::default()
+    })
+}
+
+/// Documents the body as `application/x-www-form-urlencoded`, with the
+/// schema of `T`.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket_enumform::UrlEncoded;
+/// use rocket_okapi::{openapi, openapi_get_spec};
+/// use schemars::JsonSchema;
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize, JsonSchema)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[openapi]
+/// #[post("/user", data = "<user>")]
+/// fn new_user(user: UrlEncoded<User>) -> String {
+///     user.into_inner().name
+/// }
+///
+/// let spec = serde_json::to_value(openapi_get_spec![new_user]).unwrap();
+/// let body = &spec["paths"]["/user"]["post"]["requestBody"];
+/// let schema = &body["content"]["application/x-www-form-urlencoded"]["schema"];
+/// assert_eq!(schema["$ref"], "#/components/schemas/User");
+/// assert_eq!(body["required"], true);
+/// ```
+impl<'r, T: Deserialize<'r> + JsonSchema> OpenApiFromData<'r> for UrlEncoded<T> {
+    fn request_body(gen: &mut OpenApiGenerator) -> Result<RequestBody> {
+        request_body::<T>(gen)
+    }
+}
+
+/// Documents the body as [`UrlEncoded<T>`] does.
+impl<'r, T: DeserializeOwned + JsonSchema> OpenApiFromData<'r> for UrlEncodedOwned<T> {
+    fn request_body(gen: &mut OpenApiGenerator) -> Result<RequestBody> {
+        request_body::<T>(gen)
+    }
+}
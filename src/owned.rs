@@ -0,0 +1,122 @@
+// Copyright (c) 2021 Cognite AS
+//! A [`UrlEncoded`](crate::UrlEncoded) guard for types which do not borrow
+//! from the body.
+
+use std::ops::{Deref, DerefMut};
+
+use rocket::data::{Capped, Data, FromData, Outcome};
+use rocket::http::ContentType;
+use rocket::request::Request;
+use serde::de::DeserializeOwned;
+
+use crate::{cache, codec, de, EnumFormConfig, Error};
+
+/// The UrlEncodedOwned guard: [`UrlEncoded`](crate::UrlEncoded) for types
+/// which do not borrow from the body, which is dropped as soon as it is
+/// deserialized instead of being kept in the request-local cache for the rest
+/// of the request.
+///
+/// The body is only cached where the guard fails, for the raw data of
+/// [`Error::Parse`], and with [`EnumFormConfig::forward_on_error`], to forward
+/// it. A body which an earlier guard of the request has cached is
+/// deserialized from the cache. Guards relying on the cached body, such as
+/// `&UrlEncoded<T>` and [`RawForm`](crate::RawForm), do not see the body of
+/// a request this guard deserialized.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket_enumform::UrlEncodedOwned;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Upload {
+///     name: String,
+///     content: String,
+/// }
+///
+/// #[post("/upload", format = "form", data = "<upload>")]
+/// fn upload(upload: UrlEncodedOwned<Upload>) -> String {
+///     upload.into_inner().name
+/// }
+/// ```
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UrlEncodedOwned<T>(pub T);
+
+impl<T> UrlEncodedOwned<T> {
+    /// Consumes the UrlEncodedOwned wrapper and returns the wrapped item.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<'r, T: DeserializeOwned> UrlEncodedOwned<T> {
+    fn from_bytes(body: &'r [u8], opts: de::Options) -> Result<Self, Error<'r>> {
+        de::from_pairs_with(codec::decode_bytes(body, &opts), opts)
+            .map(UrlEncodedOwned)
+            .map_err(|e| Error::Parse(crate::raw_text(body), e))
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: DeserializeOwned> FromData<'r> for UrlEncodedOwned<T> {
+    type Error = Error<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let opts = EnumFormConfig::of(req).options();
+        let limit = crate::form_limit(req);
+        let cached = cache::body(req).is_some();
+        if cached || EnumFormConfig::of(req).forward_on_error {
+            return crate::parse_body(req, data, &ContentType::Form, limit, |s| {
+                Self::from_bytes(s, opts)
+            })
+            .await;
+        }
+
+        if let Err(e) = crate::check_content_type(req, &ContentType::Form) {
+            return crate::data_outcome(req, Err(e));
+        }
+        let capped = match crate::read_decoded(req, data, limit).await {
+            Ok(capped) if capped.is_complete() => capped,
+            Ok(capped) => {
+                let read = capped.n.written.into();
+                return crate::data_outcome(req, Err(Error::TooLarge { limit, read }));
+            }
+            Err(e) => return crate::data_outcome(req, Err(e)),
+        };
+
+        let (body, n) = (capped.value, capped.n);
+        if let Ok(value) =
+            crate::instrumented(req, &body[..], |s| Self::from_bytes(s, opts.clone()))
+        {
+            return Outcome::Success(value);
+        }
+        // The error borrows the body, which is only kept, in the request-local
+        // cache, when it fails to parse, so it is parsed again from there.
+        let body = cache::store_body(req, Capped::new(body, n)).into_inner();
+        crate::data_outcome(req, Self::from_bytes(body, opts))
+    }
+}
+
+impl<T> From<T> for UrlEncodedOwned<T> {
+    fn from(value: T) -> Self {
+        UrlEncodedOwned(value)
+    }
+}
+
+impl<T> Deref for UrlEncodedOwned<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for UrlEncodedOwned<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
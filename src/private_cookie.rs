@@ -0,0 +1,161 @@
+// Copyright (c) 2021 Cognite AS
+//! Typed values kept form-encoded in private cookies.
+//!
+//! This module is only available with the `secrets` feature enabled.
+
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use rocket::http::{CookieJar, Status};
+use rocket::request::{self, FromRequest, Request};
+use serde::{Deserialize, Serialize};
+
+use crate::form_cookie::checked_cookie;
+use crate::{de, guard_failure, ser, CookieSpec, Error};
+
+/// The PrivateFormCookie guard: a [`FormCookie`](crate::FormCookie) kept in
+/// a private cookie, which Rocket encrypts and authenticates with the
+/// application's `secret_key`, so that clients can neither read nor change
+/// it.
+///
+/// The length checked against [`CookieSpec::MAX_LEN`] is that of the
+/// encrypted cookie, with the attributes [`CookieSpec::cookie`] sets, short
+/// of the few bytes percent-encoding the `+` and `/` of its base64 takes.
+/// Rocket adds further defaults to private cookies, such as an expiry a week
+/// ahead, which are not counted either.
+///
+/// A cookie which fails to decrypt, such as one changed by the client or
+/// encrypted with another key, is treated as missing: the guard forwards
+/// with `Status::NotFound`.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::{Deserialize, Serialize};
+/// use rocket::http::CookieJar;
+/// use rocket_enumform::{CookieSpec, PrivateFormCookie};
+///
+/// struct Preferences;
+///
+/// impl CookieSpec for Preferences {
+///     const NAME: &'static str = "preferences";
+/// }
+///
+/// #[derive(Debug, Default, Deserialize, Serialize)]
+/// struct Prefs {
+///     theme: String,
+/// }
+///
+/// #[get("/theme?<theme>")]
+/// fn set_theme(theme: String, jar: &CookieJar<'_>) -> Result<(), String> {
+///     PrivateFormCookie::<_, Preferences>::new(Prefs { theme })
+///         .add_to(jar)
+///         .map_err(|e| e.to_string())
+/// }
+///
+/// #[get("/")]
+/// fn index(prefs: Option<PrivateFormCookie<Prefs, Preferences>>) -> String {
+///     prefs.map(|prefs| prefs.into_inner()).unwrap_or_default().theme
+/// }
+/// ```
+pub struct PrivateFormCookie<T, S>(pub T, PhantomData<S>);
+
+impl<T, S> PrivateFormCookie<T, S> {
+    /// Wraps `value`, to keep it in the private cookie of `S`.
+    #[inline(always)]
+    pub fn new(value: T) -> Self {
+        PrivateFormCookie(value, PhantomData)
+    }
+
+    /// Consumes the PrivateFormCookie wrapper and returns the wrapped item.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T, S: CookieSpec> PrivateFormCookie<T, S> {
+    /// Removes the private cookie of `S` from `jar`.
+    pub fn remove(jar: &CookieJar<'_>) {
+        jar.remove_private(S::cookie(String::new()));
+    }
+}
+
+impl<T: Serialize, S: CookieSpec> PrivateFormCookie<T, S> {
+    /// Serializes the value into the private cookie of `S` and adds it to
+    /// `jar`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the value cannot be serialized, or if the encrypted cookie
+    /// would be longer than [`CookieSpec::MAX_LEN`], without adding it.
+    pub fn add_to(&self, jar: &CookieJar<'_>) -> Result<(), ser::Error> {
+        jar.add_private(checked_cookie::<S>(ser::to_string(&self.0)?, true)?);
+        Ok(())
+    }
+}
+
+/// The decrypted value of the private cookie of `S`, cached for the request
+/// so that errors can borrow it.
+struct Decrypted<S>(Option<String>, PhantomData<fn() -> S>);
+
+#[rocket::async_trait]
+impl<'r, T, S> FromRequest<'r> for PrivateFormCookie<T, S>
+where
+    T: Deserialize<'r>,
+    S: CookieSpec + 'static,
+{
+    type Error = Error<'r>;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let decrypted = req.local_cache(|| {
+            let cookie = req.cookies().get_private(S::NAME);
+            Decrypted::<S>(cookie.map(|cookie| cookie.value().to_string()), PhantomData)
+        });
+        let value = match &decrypted.0 {
+            Some(value) => value.as_str(),
+            None => return request::Outcome::Forward(Status::NotFound),
+        };
+        match de::from_str(value) {
+            Ok(value) => request::Outcome::Success(PrivateFormCookie::new(value)),
+            Err(e) => {
+                let e = guard_failure(req, Error::Parse(value, e));
+                request::Outcome::Error((crate::error_status(req, e.status()), e))
+            }
+        }
+    }
+}
+
+impl<T, S> From<T> for PrivateFormCookie<T, S> {
+    fn from(value: T) -> Self {
+        PrivateFormCookie::new(value)
+    }
+}
+
+impl<T, S> Deref for PrivateFormCookie<T, S> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T, S> DerefMut for PrivateFormCookie<T, S> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+// Implemented by hand, as derives would require them of `S`.
+impl<T: Clone, S> Clone for PrivateFormCookie<T, S> {
+    fn clone(&self) -> Self {
+        PrivateFormCookie::new(self.0.clone())
+    }
+}
+
+impl<T: std::fmt::Debug, S> std::fmt::Debug for PrivateFormCookie<T, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("PrivateFormCookie").field(&self.0).finish()
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright (c) 2021 Cognite AS
+//! RFC 9457 problem details for [`Error`].
+
+use rocket::http::{ContentType, Status};
+use rocket::request::Request;
+use rocket::response::{self, Responder};
+use serde::Serialize;
+
+#[cfg(feature = "fluent")]
+use crate::fluent::Catalog;
+use crate::render::Renderer;
+use crate::{de, Error, ErrorCode, FieldError};
+
+/// The `application/problem+json` body of an [`Error`] response.
+#[derive(Debug, Serialize)]
+struct Problem<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    title: &'static str,
+    status: u16,
+    code: ErrorCode,
+    detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    field: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expected: Option<&'a str>,
+    #[serde(rename = "unknownFields", skip_serializing_if = "<[_]>::is_empty")]
+    unknown_fields: &'a [String],
+    #[serde(rename = "invalidFields", skip_serializing_if = "Vec::is_empty")]
+    invalid_fields: Vec<FieldError>,
+}
+
+/// Responds with the [`status()`](Error::status) of the error and an RFC 9457
+/// `application/problem+json` body, unless an
+/// [`ErrorRenderer`](crate::ErrorRenderer) is attached:
+///
+/// ```json
+/// {
+///   "type": "about:blank",
+///   "title": "Unprocessable Entity",
+///   "status": 422,
+///   "code": "missing_field",
+///   "detail": "missing field `content_two`",
+///   "field": "content_two"
+/// }
+/// ```
+///
+/// `code` is the [`code()`](Error::code) of the error, for clients to
+/// branch on. With the `fluent` feature, `detail` is localized by the
+/// managed `fluent::Catalog`, if any. `field` is only present when the failing field is known, see
+/// [`de::Error::path()`](crate::de::Error::path), `offset` and `expected`
+/// when [`de::Error::offset()`](crate::de::Error::offset) and
+/// [`de::Error::expected()`](crate::de::Error::expected) are, and `unknownFields` only
+/// when keys were denied by
+/// [`deny_unknown_fields`](crate::de::Options::deny_unknown_fields), see
+/// [`de::Error::unknown_fields()`](crate::de::Error::unknown_fields). With the
+/// `validator` feature, `invalidFields` lists the `name` and `message` of each
+/// constraint a `Validated` form failed. This lets handlers take
+/// `Result<UrlEncoded<T>, Error<'_>>` and return the error as is:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket_enumform::{Error, UrlEncoded};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[post("/user", data = "<user>")]
+/// fn new_user(user: Result<UrlEncoded<User>, Error<'_>>) -> Result<String, Error<'_>> {
+///     Ok(user?.into_inner().name)
+/// }
+/// ```
+impl<'r, 'a> Responder<'r, 'static> for Error<'a> {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let status = crate::error_status(req, self.status());
+        if let Some(renderer) = Renderer::of(req) {
+            return renderer.render(req, &self, status);
+        }
+        let parse = match &self {
+            Error::Parse(_, e) => Some(e),
+            _ => None,
+        };
+        let field = match &self {
+            Error::Parse(_, e) => e.path(),
+            Error::Io(_)
+            | Error::TooLarge { .. }
+            | Error::ContentType(_)
+            | Error::ContentEncoding(_)
+            | Error::Csrf
+            | Error::Signature => None,
+            #[cfg(feature = "validator")]
+            Error::Validation(_) => None,
+        };
+        let unknown_fields = match &self {
+            Error::Parse(_, e) => e.unknown_fields(),
+            Error::Io(_)
+            | Error::TooLarge { .. }
+            | Error::ContentType(_)
+            | Error::ContentEncoding(_)
+            | Error::Csrf
+            | Error::Signature => &[],
+            #[cfg(feature = "validator")]
+            Error::Validation(_) => &[],
+        };
+        let invalid_fields = match &self {
+            #[cfg(feature = "validator")]
+            Error::Validation(errors) => crate::validated::field_errors(errors),
+            _ => Vec::new(),
+        };
+        let detail = match &self {
+            Error::Parse(_, e) => e.to_string(),
+            Error::Io(e) => e.to_string(),
+            e @ (Error::TooLarge { .. }
+            | Error::ContentType(_)
+            | Error::ContentEncoding(_)
+            | Error::Csrf
+            | Error::Signature) => e.to_string(),
+            #[cfg(feature = "validator")]
+            e @ Error::Validation(_) => e.to_string(),
+        };
+        #[cfg(feature = "fluent")]
+        let detail = match req.rocket().state::<Catalog>() {
+            Some(catalog) => catalog.localize(req, &self),
+            None => detail,
+        };
+        let problem = Problem {
+            kind: "about:blank",
+            title: status.reason_lossy(),
+            status: status.code,
+            code: self.code(),
+            detail,
+            field,
+            offset: parse.and_then(de::Error::offset),
+            expected: parse.and_then(de::Error::expected),
+            unknown_fields,
+            invalid_fields,
+        };
+        let body = serde_json::to_string(&problem).map_err(|_| Status::InternalServerError)?;
+        let content_type = ContentType::new("application", "problem+json");
+
+        (status, (content_type, body)).respond_to(req)
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright (c) 2021 Cognite AS
+//! Bracket-nested forms backed by [`serde_qs`].
+//!
+//! This module is only available with the `qs` feature enabled.
+
+use std::ops::{Deref, DerefMut};
+
+use rocket::data::{Data, FromData, Outcome};
+use rocket::error_;
+use rocket::http::{ContentType, Status};
+use rocket::request::Request;
+use rocket::response::{self, Responder};
+use serde::de::Error as _;
+use serde::{Deserialize, Serialize};
+
+use crate::{de, EnumFormConfig, Error};
+
+/// The QsEncoded guard: consume x-www-form-urlencoded requests with
+/// bracket-nested keys.
+///
+/// `QsEncoded` works like [`UrlEncoded`](crate::UrlEncoded), except that keys
+/// such as `filter[range][min]=3` address fields of nested structs, maps,
+/// sequences and enums, following the conventions of [`serde_qs`].
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket_enumform::qs::QsEncoded;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Range {
+///     min: usize,
+///     max: Option<usize>,
+/// }
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Filter {
+///     range: Range,
+/// }
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Search {
+///     filter: Filter,
+/// }
+///
+/// #[post("/search", format = "form", data = "<search>")]
+/// fn search(search: QsEncoded<Search>) -> String {
+///     format!("{:?}", search.into_inner())
+/// }
+/// ```
+///
+/// The body is read like that of `UrlEncoded`: the same `limits.enumform`
+/// data limit applies, and so does the configuration of
+/// [`EnumFormConfig`] for reading bodies and reporting
+/// errors, while the deserialization options are left to [`serde_qs`].
+/// Failures are reported as [`Error`], with the error of
+/// `serde_qs` as the message of [`Error::Parse`].
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct QsEncoded<T>(pub T);
+
+impl<T> QsEncoded<T> {
+    /// Consumes the QsEncoded wrapper and returns the wrapped item.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rocket_enumform::qs::QsEncoded;
+    /// let string = "Hello".to_string();
+    /// let outer = QsEncoded(string);
+    /// assert_eq!(outer.into_inner(), "Hello".to_string());
+    /// ```
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<'r, T: Deserialize<'r>> QsEncoded<T> {
+    fn from_str(s: &'r str) -> Result<Self, Error<'r>> {
+        serde_qs::from_str(s)
+            .map(QsEncoded)
+            .map_err(|e| Error::Parse(s, de::Error::custom(e)))
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>> FromData<'r> for QsEncoded<T> {
+    type Error = Error<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let limit = crate::form_limit(req);
+        crate::parse_body(req, data, &ContentType::Form, limit, |s| {
+            Self::from_str(crate::utf8(s)?)
+        })
+        .await
+    }
+}
+
+/// Serializes the wrapped value with bracket-nested keys. Returns a response
+/// with Content-Type application/x-www-form-urlencoded, with a `charset=utf-8`
+/// parameter as configured by [`EnumFormConfig::response_charset`], and a
+/// fixed-size body with the serialized value. If serialization fails, an `Err`
+/// of `Status::InternalServerError` is returned.
+impl<'r, T: Serialize> Responder<'r, 'static> for QsEncoded<T> {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let string = serde_qs::to_string(&self.0).map_err(|e| {
+            error_!("QsEncoding failed to serialize: {:?}", e);
+            Status::InternalServerError
+        })?;
+
+        let content_type = EnumFormConfig::of(req).response_content_type(ContentType::Form);
+        (content_type, string).respond_to(req)
+    }
+}
+
+impl<T> From<T> for QsEncoded<T> {
+    fn from(value: T) -> Self {
+        QsEncoded(value)
+    }
+}
+
+impl<T> Deref for QsEncoded<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for QsEncoded<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright (c) 2021 Cognite AS
+//! Request guard for typed query strings.
+
+use std::ops::{Deref, DerefMut};
+
+use rocket::info_;
+use rocket::request::{self, FromRequest, Request};
+use serde::Deserialize;
+
+use crate::{codec, de, EnumFormConfig, Error, UrlEncodedCodec};
+
+/// The QueryEncoded guard: easily consume x-www-form-urlencoded query strings.
+///
+/// `QueryEncoded` is a request guard which deserializes the whole query
+/// string of the request with serde, the same way [`UrlEncoded`](crate::UrlEncoded)
+/// deserializes request bodies. This makes tagged enums usable in `GET`
+/// endpoints, which Rocket's own `FromForm` cannot express.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket_enumform::QueryEncoded;
+///
+/// #[derive(Debug, Deserialize)]
+/// #[serde(tag = "type")]
+/// enum Search {
+///     #[serde(rename = "by_name")]
+///     ByName { name: String },
+///     #[serde(rename = "by_id")]
+///     ById { id: usize },
+/// }
+///
+/// #[get("/search")]
+/// fn search(query: QueryEncoded<Search>) -> String {
+///     format!("{:?}", query.into_inner())
+/// }
+/// ```
+///
+/// A request without a query string is deserialized as if the query string
+/// was empty. If deserialization fails the guard fails with
+/// `Status::UnprocessableEntity`, or with `Status::PayloadTooLarge` if a
+/// structural [`de::Limit`](crate::de::Limit) was exceeded.
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct QueryEncoded<T>(pub T);
+
+impl<T> QueryEncoded<T> {
+    /// Consumes the QueryEncoded wrapper and returns the wrapped item.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rocket_enumform::QueryEncoded;
+    /// let string = "Hello".to_string();
+    /// let outer = QueryEncoded(string);
+    /// assert_eq!(outer.into_inner(), "Hello".to_string());
+    /// ```
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<'r, T: Deserialize<'r>> QueryEncoded<T> {
+    fn from_str(s: &'r str, opts: de::Options) -> Result<Self, Error<'r>> {
+        codec::decode_pairs::<UrlEncodedCodec>(s, &opts)
+            .and_then(|pairs| de::from_pairs_with(pairs, opts))
+            .map(QueryEncoded)
+            .map_err(|e| Error::Parse(s, e))
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>> FromRequest<'r> for QueryEncoded<T> {
+    type Error = Error<'r>;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let query = req.uri().query().map(|q| q.as_str()).unwrap_or("");
+        let opts = EnumFormConfig::of(req).options();
+        match crate::instrumented(req, query, |query| Self::from_str(query, opts)) {
+            Ok(value) => request::Outcome::Success(value),
+            Err(e) if EnumFormConfig::of(req).forward_on_error => {
+                let e = crate::guard_failure(req, e);
+                info_!("forwarding to the next route");
+                crate::failure::store_error(req, &e);
+                request::Outcome::Forward(crate::error_status(req, e.status()))
+            }
+            Err(e) => {
+                let e = crate::guard_failure(req, e);
+                crate::failure::store_error(req, &e);
+                request::Outcome::Error((crate::error_status(req, e.status()), e))
+            }
+        }
+    }
+}
+
+impl<T> From<T> for QueryEncoded<T> {
+    fn from(value: T) -> Self {
+        QueryEncoded(value)
+    }
+}
+
+impl<T> Deref for QueryEncoded<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for QueryEncoded<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright (c) 2021 Cognite AS
+//! Guards exposing the raw form body.
+
+use std::borrow::Cow;
+use std::ops::{Deref, DerefMut};
+
+use rocket::data::{Data, FromData, Outcome};
+use rocket::http::ContentType;
+use rocket::request::Request;
+use serde::Deserialize;
+
+use crate::{de, EnumFormConfig, Error};
+
+/// The RawForm guard: the decoded key/value pairs of an
+/// `application/x-www-form-urlencoded` body, for inspecting keys which are
+/// not part of a type, such as feature flags, before or alongside parsing it.
+///
+/// The body is read like [`UrlEncoded`](crate::UrlEncoded) does, into the
+/// request-local cache, and is split into pairs with the configured options.
+/// [`parse()`](RawForm::parse) deserializes the same pairs without reading or
+/// splitting the body again.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket_enumform::{Error, RawForm};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Search {
+///     q: String,
+/// }
+///
+/// #[post("/search", format = "form", data = "<form>")]
+/// fn search(form: RawForm<'_>) -> Result<String, Error<'_>> {
+///     let beta = form.get("beta").is_some();
+///     let search: Search = form.parse()?;
+///     Ok(format!("{} (beta: {})", search.q, beta))
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RawForm<'r> {
+    body: &'r str,
+    pairs: de::Pairs<'r>,
+    opts: de::Options,
+}
+
+impl<'r> RawForm<'r> {
+    /// The body, after decompressing and transcoding it to UTF-8 where
+    /// enabled, but before percent-decoding.
+    pub fn as_str(&self) -> &'r str {
+        self.body
+    }
+
+    /// The percent-decoded key/value pairs, in submission order, borrowing
+    /// from the body where no decoding was needed.
+    pub fn pairs(&self) -> impl Iterator<Item = (Cow<'r, str>, Cow<'r, str>)> + '_ {
+        self.pairs.iter().cloned()
+    }
+
+    /// The first value submitted for `key`, if any.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs.iter().find(|(k, _)| k == key).map(|(_, v)| &**v)
+    }
+
+    /// Deserializes the body into `T` with the configured options, failing
+    /// like [`UrlEncoded`](crate::UrlEncoded) would.
+    pub fn parse<T: Deserialize<'r>>(&self) -> Result<T, Error<'r>> {
+        de::from_pairs_with(self.pairs.clone(), self.opts.clone())
+            .map_err(|e| Error::Parse(self.body, e))
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromData<'r> for RawForm<'r> {
+    type Error = Error<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let opts = EnumFormConfig::of(req).options();
+        let result = match crate::check_content_type(req, &ContentType::Form) {
+            Ok(()) => crate::read_body(req, data).await,
+            Err(e) => Err(e),
+        };
+        let result = result.and_then(|body| {
+            let pairs = crate::cache::pairs(req, body, &opts);
+            Ok(RawForm {
+                body: crate::utf8(body)?,
+                pairs,
+                opts,
+            })
+        });
+        crate::data_outcome(req, result)
+    }
+}
+
+/// The WithRaw guard: [`UrlEncoded`](crate::UrlEncoded) which keeps the body
+/// it parsed, for verifying webhook signatures or audit logging.
+///
+/// `raw` is the body from the request-local cache. It is the body exactly as
+/// received unless it was decompressed or transcoded from another charset,
+/// which the `compression` and `charset` features do where the request asks
+/// for it.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket_enumform::WithRaw;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Event {
+///     kind: String,
+/// }
+///
+/// #[post("/webhook", format = "form", data = "<event>")]
+/// fn webhook(event: WithRaw<'_, Event>) -> String {
+///     format!("{} ({} bytes)", event.value.kind, event.raw.len())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WithRaw<'r, T> {
+    /// The deserialized value.
+    pub value: T,
+
+    /// The body the value was deserialized from.
+    pub raw: &'r str,
+}
+
+impl<'r, T> WithRaw<'r, T> {
+    /// Consumes the WithRaw wrapper and returns the wrapped value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>> FromData<'r> for WithRaw<'r, T> {
+    type Error = Error<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let opts = EnumFormConfig::of(req).options();
+        let limit = crate::form_limit(req);
+        crate::parse_body(req, data, &ContentType::Form, limit, |raw| {
+            let value = crate::decode_form(req, raw, opts)?;
+            Ok(WithRaw {
+                value,
+                raw: crate::utf8(raw)?,
+            })
+        })
+        .await
+    }
+}
+
+impl<T> Deref for WithRaw<'_, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for WithRaw<'_, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
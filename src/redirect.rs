@@ -0,0 +1,116 @@
+// Copyright (c) 2021 Cognite AS
+//! A redirect carrying a value in the query of its Location.
+
+use std::convert::TryInto;
+
+use rocket::error_;
+use rocket::http::uri::Reference;
+use rocket::http::Status;
+use rocket::request::Request;
+use rocket::response::{self, Redirect, Responder};
+use serde::Serialize;
+
+use crate::{codec, EnumFormConfig, UrlEncodedCodec};
+
+/// The RedirectWithForm responder: a `303 See Other` redirect to a URI with
+/// a value encoded into its query, for post/redirect/get flows carrying state
+/// to the page redirected to.
+///
+/// The value is serialized like the body of a [`UrlEncoded`](crate::UrlEncoded)
+/// response and appended to the query of the URI, before its fragment, if
+/// any. A URI which is invalid, or becomes invalid with the query, and a value
+/// which fails to serialize respond with `Status::InternalServerError`.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::{Deserialize, Serialize};
+/// use rocket::http::Status;
+/// use rocket::local::blocking::Client;
+/// use rocket_enumform::{QueryEncoded, RedirectWithForm, UrlEncoded};
+///
+/// #[derive(Debug, Deserialize, Serialize)]
+/// struct Saved {
+///     id: u32,
+///     name: String,
+/// }
+///
+/// #[post("/items", data = "<item>")]
+/// fn save(item: UrlEncoded<Saved>) -> RedirectWithForm<Saved> {
+///     RedirectWithForm::to("/items/saved?notice=1", item.into_inner())
+/// }
+///
+/// #[get("/items/saved")]
+/// fn saved(saved: QueryEncoded<Saved>) -> String {
+///     format!("saved {}", saved.name)
+/// }
+///
+/// let client = Client::untracked(rocket::build().mount("/", routes![save, saved])).unwrap();
+/// let request = client.post("/items").header(rocket::http::ContentType::Form);
+/// let response = request.body("id=7&name=a+b").dispatch();
+/// assert_eq!(response.status(), Status::SeeOther);
+/// let location = response.headers().get_one("Location").unwrap();
+/// assert_eq!(location, "/items/saved?notice=1&id=7&name=a+b");
+/// assert_eq!(client.get(location).dispatch().into_string().unwrap(), "saved a b");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedirectWithForm<T> {
+    uri: Option<Reference<'static>>,
+    value: T,
+}
+
+impl<T> RedirectWithForm<T> {
+    /// Redirects to `uri`, with `value` in its query.
+    ///
+    /// Like with [`Redirect::to`], an invalid `uri` is only reported when
+    /// responding.
+    pub fn to<U: TryInto<Reference<'static>>>(uri: U, value: T) -> Self {
+        RedirectWithForm {
+            uri: uri.try_into().ok(),
+            value,
+        }
+    }
+
+    /// Consumes the RedirectWithForm and returns the value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+/// Appends `query` to the query of `uri`, before its fragment.
+fn with_query(uri: &Reference<'_>, query: &str) -> String {
+    let uri = uri.to_string();
+    if query.is_empty() {
+        return uri;
+    }
+    let (base, fragment) = match uri.find('#') {
+        Some(i) => uri.split_at(i),
+        None => (&*uri, ""),
+    };
+    let separator = match base.find('?') {
+        Some(i) if i + 1 < base.len() && !base.ends_with('&') => "&",
+        Some(_) => "",
+        None => "?",
+    };
+    format!("{}{}{}{}", base, separator, query, fragment)
+}
+
+impl<'r, T: Serialize> Responder<'r, 'static> for RedirectWithForm<T> {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let uri = self.uri.ok_or_else(|| {
+            error_!("Invalid URI used for RedirectWithForm.");
+            Status::InternalServerError
+        })?;
+        let opts = EnumFormConfig::of(req).ser_options();
+        let query = codec::encode::<UrlEncodedCodec, T>(&self.value, opts).map_err(|e| {
+            error_!("RedirectWithForm failed to serialize: {:?}", e);
+            Status::InternalServerError
+        })?;
+        let location = Reference::parse_owned(with_query(&uri, &query)).map_err(|e| {
+            error_!("RedirectWithForm built an invalid URI: {}", e);
+            Status::InternalServerError
+        })?;
+
+        Redirect::to(location).respond_to(req)
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright (c) 2021 Cognite AS
+//! Custom response bodies for [`Error`].
+
+use rocket::http::Status;
+use rocket::request::Request;
+use rocket::response;
+
+use crate::Error;
+
+/// Renders the response of an [`Error`] returned by a handler, in place of
+/// its problem details.
+///
+/// Attached with [`EnumForm::renderer()`](crate::EnumForm::renderer), a
+/// renderer is used by the [`Responder`](rocket::response::Responder)
+/// implementation of [`Error`], with the status the error responds with, as
+/// mapped by [`EnumFormConfig::error_status_policy`](crate::EnumFormConfig::error_status_policy).
+/// Closures taking the request, the error and the status implement it:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket::http::{ContentType, Status};
+/// use rocket::local::blocking::Client;
+/// use rocket::response::{content::RawHtml, Responder};
+/// use rocket_enumform::{EnumForm, Error, UrlEncoded};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[post("/user", data = "<user>")]
+/// fn new_user(user: Result<UrlEncoded<User>, Error<'_>>) -> Result<String, Error<'_>> {
+///     Ok(user?.into_inner().name)
+/// }
+///
+/// let renderer = |req: &rocket::Request<'_>, e: &Error<'_>, status: Status| {
+///     let html = format!("<p class=\"error\" data-code=\"{}\">Invalid form</p>", e.code());
+///     (status, RawHtml(html)).respond_to(req)
+/// };
+/// let rocket = rocket::build()
+///     .attach(EnumForm::renderer(renderer))
+///     .mount("/", routes![new_user]);
+/// let client = Client::untracked(rocket).unwrap();
+/// let response = client.post("/user").header(ContentType::Form).body("").dispatch();
+/// assert_eq!(response.status(), Status::UnprocessableEntity);
+/// assert_eq!(response.content_type(), Some(ContentType::HTML));
+/// let html = response.into_string().unwrap();
+/// assert_eq!(html, "<p class=\"error\" data-code=\"missing_field\">Invalid form</p>");
+/// ```
+pub trait ErrorRenderer: Send + Sync + 'static {
+    /// The response to `req` for `e`, with `status`.
+    fn render(&self, req: &Request<'_>, e: &Error<'_>, status: Status)
+        -> response::Result<'static>;
+}
+
+impl<F> ErrorRenderer for F
+where
+    F: Fn(&Request<'_>, &Error<'_>, Status) -> response::Result<'static> + Send + Sync + 'static,
+{
+    fn render(
+        &self,
+        req: &Request<'_>,
+        e: &Error<'_>,
+        status: Status,
+    ) -> response::Result<'static> {
+        self(req, e, status)
+    }
+}
+
+/// The renderer attached with [`EnumForm::renderer()`](crate::EnumForm::renderer).
+pub(crate) struct Renderer(pub(crate) Box<dyn ErrorRenderer>);
+
+impl Renderer {
+    /// The renderer of the application of `req`, if any.
+    pub(crate) fn of<'r>(req: &'r Request<'_>) -> Option<&'r dyn ErrorRenderer> {
+        req.rocket()
+            .state::<Renderer>()
+            .map(|Renderer(renderer)| &**renderer)
+    }
+}
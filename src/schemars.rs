@@ -0,0 +1,163 @@
+// Copyright (c) 2021 Cognite AS
+//! JSON schemas of forms, with [schemars](https://docs.rs/schemars).
+//!
+//! [`UrlEncoded<T>`] and [`UrlEncodedOwned<T>`] implement
+//! [`JsonSchema`] as `T` does. Forms flatten what JSON nests: internally
+//! tagged enums, whose schemas are flat already, submit their tag beside the
+//! fields of the variant, and with [`EnumFormConfig::dot_notation`] structs
+//! and the variants of other enums submit their fields under dotted keys,
+//! which the [`DotNotation`] visitor adjusts schemas to.
+//!
+//! This module is only available with the `schemars` feature enabled.
+//!
+//! [`EnumFormConfig::dot_notation`]: crate::EnumFormConfig::dot_notation
+
+use std::borrow::Cow;
+use std::mem;
+
+use ::schemars::gen::SchemaGenerator;
+use ::schemars::schema::{InstanceType, ObjectValidation, RootSchema, Schema, SchemaObject};
+use ::schemars::visit::{self, Visitor};
+use ::schemars::{JsonSchema, Map};
+
+use crate::{UrlEncoded, UrlEncodedOwned};
+
+macro_rules! impl_json_schema {
+    ($name:ident) => {
+        impl<T: JsonSchema> JsonSchema for $name<T> {
+            fn is_referenceable() -> bool {
+                T::is_referenceable()
+            }
+
+            fn schema_name() -> String {
+                T::schema_name()
+            }
+
+            fn schema_id() -> Cow<'static, str> {
+                T::schema_id()
+            }
+
+            fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+                T::json_schema(gen)
+            }
+        }
+    };
+}
+
+impl_json_schema!(UrlEncoded);
+impl_json_schema!(UrlEncodedOwned);
+
+/// A [`Visitor`] replacing the properties of schemas which are structs,
+/// including the struct variants of externally and adjacently tagged enums,
+/// by their fields under dotted keys, as forms submit them with
+/// [`EnumFormConfig::dot_notation`](crate::EnumFormConfig::dot_notation).
+///
+/// Nested fields are required where the property and the field are. Structs
+/// referenced from the definitions of a root schema are resolved. Properties
+/// which are maps, optional or enums themselves are kept as they are.
+///
+/// ```rust
+/// use rocket_enumform::schemars::DotNotation;
+/// use schemars::gen::SchemaSettings;
+/// use schemars::JsonSchema;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, JsonSchema)]
+/// struct Address {
+///     street: String,
+///     zip: u32,
+/// }
+///
+/// #[derive(Deserialize, JsonSchema)]
+/// #[serde(rename_all = "snake_case")]
+/// enum Contact {
+///     Home(Address),
+///     Email { address: String },
+/// }
+///
+/// let gen = SchemaSettings::draft07().with_visitor(DotNotation::default()).into_generator();
+/// let schema = serde_json::to_value(gen.into_root_schema_for::<Contact>()).unwrap();
+/// let home = &schema["oneOf"][0];
+/// assert_eq!(home["properties"]["home.street"]["type"], "string");
+/// assert_eq!(home["required"], serde_json::json!(["home.street", "home.zip"]));
+/// let email = &schema["oneOf"][1];
+/// assert_eq!(email["properties"]["email.address"]["type"], "string");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DotNotation {
+    /// The definitions of the root schema visited, to resolve references.
+    definitions: Map<String, Schema>,
+}
+
+impl DotNotation {
+    /// The fields of `schema`, flattened, if it is a struct.
+    fn nested(&self, schema: &Schema) -> Option<ObjectValidation> {
+        let mut schema = match schema {
+            Schema::Object(schema) => self.resolve(schema)?.clone(),
+            Schema::Bool(_) => return None,
+        };
+        let is_object = schema.has_type(InstanceType::Object) && schema.subschemas.is_none();
+        let object = schema.object.as_ref()?;
+        if !is_object || (object.additional_properties.is_some() && object.properties.is_empty()) {
+            return None;
+        }
+        self.flatten(&mut schema);
+        schema.object.map(|object| *object)
+    }
+
+    /// `schema`, or the definition it references.
+    fn resolve<'a>(&'a self, schema: &'a SchemaObject) -> Option<&'a SchemaObject> {
+        let name = match &schema.reference {
+            Some(reference) => reference.rsplit('/').next()?,
+            None => return Some(schema),
+        };
+        match self.definitions.get(name)? {
+            Schema::Object(schema) => Some(schema),
+            Schema::Bool(_) => None,
+        }
+    }
+
+    /// Replaces the struct properties of `schema` by their dotted fields.
+    fn flatten(&self, schema: &mut SchemaObject) {
+        let object = match schema.object.as_mut() {
+            Some(object) => object,
+            None => return,
+        };
+        let required = mem::take(&mut object.required);
+        for (key, property) in mem::take(&mut object.properties) {
+            let nested = match self.nested(&property) {
+                Some(nested) => nested,
+                None => {
+                    if required.contains(&key) {
+                        object.required.insert(key.clone());
+                    }
+                    object.properties.insert(key, property);
+                    continue;
+                }
+            };
+            if required.contains(&key) {
+                for field in nested.required {
+                    object.required.insert(format!("{}.{}", key, field));
+                }
+            }
+            for (field, property) in nested.properties {
+                object
+                    .properties
+                    .insert(format!("{}.{}", key, field), property);
+            }
+        }
+    }
+}
+
+impl Visitor for DotNotation {
+    fn visit_root_schema(&mut self, root: &mut RootSchema) {
+        self.definitions = root.definitions.clone();
+        visit::visit_root_schema(self, root);
+        self.definitions = Map::new();
+    }
+
+    fn visit_schema_object(&mut self, schema: &mut SchemaObject) {
+        visit::visit_schema_object(self, schema);
+        self.flatten(schema);
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright (c) 2021 Cognite AS
+//! A form field for passwords, tokens and other values which must not be
+//! logged.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use rocket::form::{self, DataField, FromFormField, ValueField};
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use crate::de::SECRET;
+
+/// The Secret field: a value, such as a password or a token, which is
+/// redacted where the crate shows form data.
+///
+/// Its Debug and Display implementations print `[redacted]`. When
+/// deserialization fails, the values of the Secret fields deserialized before
+/// the error are redacted from the raw data in the Debug output of
+/// [`Error::Parse`](crate::Error::Parse), and thereby from the guards' logs,
+/// and are not set as the value of form errors; see
+/// [`de::Error::redact()`](crate::de::Error::redact) for the caveats. A
+/// value which fails to deserialize as `T` fails with a message which does not
+/// include it.
+///
+/// It serializes as `T`.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket_enumform::{Secret, UrlEncoded};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Login {
+///     user: String,
+///     password: Secret<String>,
+/// }
+///
+/// #[post("/login", format = "form", data = "<form>")]
+/// fn login(form: UrlEncoded<Login>) -> String {
+///     format!("{:?}", form)
+/// }
+///
+/// let login: Login = rocket_enumform::from_str("user=ann&password=hunter2").unwrap();
+/// assert_eq!(*login.password, "hunter2");
+/// assert_eq!(format!("{:?}", login.password), "Secret([redacted])");
+/// ```
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Secret<T>(pub T);
+
+impl<T> Secret<T> {
+    /// Consumes the Secret wrapper and returns the wrapped item.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Secret(value)
+    }
+}
+
+impl<T> Deref for Secret<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Secret<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret([redacted])")
+    }
+}
+
+impl<T> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Secret<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(SECRET, SecretVisitor(PhantomData))
+    }
+}
+
+struct SecretVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for SecretVisitor<T> {
+    type Value = Secret<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a secret value")
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer)
+            .map(Secret)
+            .map_err(|_| de::Error::custom("invalid secret value"))
+    }
+}
+
+impl<T: Serialize> Serialize for Secret<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+/// Errors of `T`, without the submitted value.
+fn redacted(mut errors: form::Errors<'_>) -> form::Errors<'_> {
+    for error in errors.iter_mut() {
+        error.value = None;
+    }
+    errors
+}
+
+#[rocket::async_trait]
+impl<'v, T: FromFormField<'v>> FromFormField<'v> for Secret<T> {
+    fn from_value(field: ValueField<'v>) -> form::Result<'v, Self> {
+        T::from_value(field).map(Secret).map_err(redacted)
+    }
+
+    async fn from_data(field: DataField<'v, '_>) -> form::Result<'v, Self> {
+        T::from_data(field).await.map(Secret).map_err(redacted)
+    }
+
+    fn default() -> Option<Self> {
+        T::default().map(Secret)
+    }
+}
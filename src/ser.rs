@@ -0,0 +1,1355 @@
+// Copyright (c) 2021 Cognite AS
+//! Serialize x-www-form-urlencoded data.
+//!
+//! Unlike [`serde_urlencoded`], nested values are flattened into the
+//! surrounding pairs, so that whatever `T` deserializes from is also what
+//! serializing `T` produces:
+//!
+//! * Internally tagged enums (`#[serde(tag = "type")]`) are written as their
+//!   tag pair, `type=variant`, followed by the fields of the variant.
+//! * Externally tagged enums are keyed by their variant, the shape
+//!   [`de`](crate::de) reads them in: a unit variant is written as `variant=`
+//!   at the top level and as `field=variant` as a field, and the content of
+//!   other variants is nested under the variant, as `variant=5` or
+//!   `field.variant.x=1`. Nested keys are read back with
+//!   [`dot_notation`](crate::de::Options::dot_notation).
+//! * Nested structs and maps contribute their fields directly, mirroring
+//!   `#[serde(flatten)]`.
+//! * Sequences repeat their key once per element.
+//! * `None` values are omitted, or written as `key=` with
+//!   [`Options::none_as_empty`].
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! #[serde(tag = "type")]
+//! enum Body {
+//!     #[serde(rename = "variant_one")]
+//!     VariantOne { content_one: String },
+//!     #[serde(rename = "variant_two")]
+//!     VariantTwo { content_two: usize },
+//! }
+//!
+//! let body = Body::VariantTwo { content_two: 5 };
+//! let string = rocket_enumform::ser::to_string(&body).unwrap();
+//! assert_eq!(string, "type=variant_two&content_two=5");
+//! assert_eq!(rocket_enumform::from_str::<Body>(&string).unwrap(), body);
+//! ```
+
+use std::borrow::Cow;
+use std::{error, fmt, io, str};
+
+use serde::ser::{self, Impossible, Serialize};
+
+/// Options controlling how pairs are percent-encoded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// Encode spaces as `%20` instead of `+`, for verifiers which compare
+    /// bodies byte for byte or don't decode `+`.
+    pub percent_encode_space: bool,
+
+    /// Translate keys from `snake_case` to this case, see
+    /// [`KeyCase`](crate::KeyCase).
+    pub key_case: Option<crate::KeyCase>,
+
+    /// Prepend this prefix, such as `payload.`, to all keys, after
+    /// translating their case.
+    pub key_prefix: Option<Cow<'static, str>>,
+
+    /// Produce canonical output, for signing or cache keys: pairs are sorted
+    /// by key, keeping the order of the values of a repeated key, and every
+    /// byte but the RFC 3986 unreserved characters is percent-encoded with
+    /// uppercase hex digits, spaces included.
+    pub canonical: bool,
+
+    /// Write `None` values as a key with an empty value, `key=`, instead of
+    /// omitting the key, for receivers which tell the two apart.
+    pub none_as_empty: bool,
+
+    /// The characters left unencoded. Ignored with `canonical`, which always
+    /// uses [`EncodeSet::Unreserved`].
+    pub encode_set: EncodeSet,
+}
+
+/// The characters which are written as they are rather than percent-encoded.
+///
+/// ASCII letters and digits are never encoded, and spaces are written as `+`
+/// unless [`Options::percent_encode_space`] is set. Every other byte is
+/// encoded as `%XX` with uppercase hex digits.
+///
+/// ```rust
+/// use rocket_enumform::ser::{self, EncodeSet};
+///
+/// let opts = ser::Options {
+///     encode_set: EncodeSet::Unreserved,
+///     ..Default::default()
+/// };
+/// let body = ser::to_string_with(&[("q", "a*b~c!")], opts).unwrap();
+/// assert_eq!(body, "q=a%2Ab~c%21");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum EncodeSet {
+    /// `*`, `-`, `.` and `_`, as `application/x-www-form-urlencoded` and
+    /// `serde_urlencoded` leave them.
+    #[default]
+    Form,
+
+    /// The RFC 3986 unreserved characters `-`, `.`, `_` and `~`, as OAuth 1.0
+    /// signatures require.
+    Unreserved,
+
+    /// The ASCII characters in this string.
+    Custom(&'static str),
+}
+
+impl EncodeSet {
+    /// Whether `b` is written as it is.
+    fn keeps(self, b: u8) -> bool {
+        b.is_ascii_alphanumeric()
+            || match self {
+                EncodeSet::Form => matches!(b, b'*' | b'-' | b'.' | b'_'),
+                EncodeSet::Unreserved => matches!(b, b'-' | b'.' | b'_' | b'~'),
+                EncodeSet::Custom(keep) => b.is_ascii() && keep.as_bytes().contains(&b),
+            }
+    }
+}
+
+/// Serializes a value into a `application/x-www-form-urlencoded` `String`.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    to_string_with(value, Options::default())
+}
+
+/// Serializes a value into a `application/x-www-form-urlencoded` `String`
+/// with `opts`, writing each pair into it as it is serialized.
+///
+/// ```rust
+/// use rocket_enumform::ser;
+///
+/// let opts = ser::Options {
+///     percent_encode_space: true,
+///     ..Default::default()
+/// };
+/// let body = ser::to_string_with(&[("q", "a b+c")], opts).unwrap();
+/// assert_eq!(body, "q=a%20b%2Bc");
+/// ```
+pub fn to_string_with<T: Serialize + ?Sized>(value: &T, opts: Options) -> Result<String, Error> {
+    let mut body = Vec::new();
+    to_writer_with(&mut body, value, opts)?;
+    // Everything but ASCII letters, digits and the kept characters is
+    // percent-encoded, so this never fails.
+    String::from_utf8(body).map_err(ser::Error::custom)
+}
+
+/// Serializes a value into a `application/x-www-form-urlencoded` byte vector.
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    let mut body = Vec::new();
+    to_writer(&mut body, value)?;
+    Ok(body)
+}
+
+/// Serializes a value as `application/x-www-form-urlencoded` into `writer`.
+///
+/// Failures of `writer` are reported as [`Error::Custom`].
+pub fn to_writer<W, T>(writer: W, value: &T) -> Result<(), Error>
+where
+    W: io::Write,
+    T: Serialize + ?Sized,
+{
+    to_writer_with(writer, value, Options::default())
+}
+
+/// Serializes a value as `application/x-www-form-urlencoded` into `writer`
+/// with `opts`.
+///
+/// Each pair is percent-encoded and written as soon as it is serialized,
+/// without collecting the pairs or the body first. With
+/// [`Options::canonical`] the pairs are collected to be sorted before they
+/// are written.
+///
+/// Failures of `writer` are reported as [`Error::Custom`].
+///
+/// ```rust
+/// use rocket_enumform::ser;
+///
+/// let mut body = Vec::new();
+/// ser::to_writer_with(&mut body, &[("q", "a b")], ser::Options::default()).unwrap();
+/// assert_eq!(body, b"q=a+b");
+/// ```
+pub fn to_writer_with<W, T>(writer: W, value: &T, opts: Options) -> Result<(), Error>
+where
+    W: io::Write,
+    T: Serialize + ?Sized,
+{
+    let none_as_empty = opts.none_as_empty;
+    if opts.canonical {
+        let pairs = to_pairs_with(value, opts.clone())?;
+        let mut writer = Writer::new(writer, opts);
+        for (key, value) in ordered(&pairs, &writer.opts) {
+            writer.write(key, value)?;
+        }
+        return Ok(());
+    }
+    let mut writer = Writer::new(writer, opts);
+    value.serialize(Serializer::with_sink(&mut writer).none_as_empty(none_as_empty))
+}
+
+/// Where a [`Serializer`] puts the pairs it serializes.
+trait Sink {
+    fn push(&mut self, key: &str, value: String) -> Result<(), Error>;
+}
+
+impl Sink for Vec<(String, String)> {
+    fn push(&mut self, key: &str, value: String) -> Result<(), Error> {
+        Vec::push(self, (key.to_owned(), value));
+        Ok(())
+    }
+}
+
+/// Percent-encodes pairs into a writer, one at a time.
+struct Writer<W> {
+    writer: W,
+    opts: Options,
+    buf: String,
+    empty: bool,
+}
+
+impl<W: io::Write> Writer<W> {
+    fn new(writer: W, opts: Options) -> Self {
+        Writer {
+            writer,
+            opts,
+            buf: String::new(),
+            empty: true,
+        }
+    }
+
+    /// Writes a pair, whose key is translated and prefixed already.
+    fn write(&mut self, key: &str, value: &str) -> Result<(), Error> {
+        let (set, space_as_plus) = self.opts.escaping();
+        self.buf.clear();
+        if !self.empty {
+            self.buf.push('&');
+        }
+        escape(&mut self.buf, key, set, space_as_plus);
+        self.buf.push('=');
+        escape(&mut self.buf, value, set, space_as_plus);
+        self.empty = false;
+        self.writer
+            .write_all(self.buf.as_bytes())
+            .map_err(|e| Error::Custom(e.to_string().into()))
+    }
+}
+
+impl<W: io::Write> Sink for Writer<W> {
+    fn push(&mut self, key: &str, value: String) -> Result<(), Error> {
+        let key = match self.opts.key_case {
+            Some(case) => Cow::Owned(case.encode(key)),
+            None => Cow::Borrowed(key),
+        };
+        let key = match &self.opts.key_prefix {
+            Some(prefix) => Cow::Owned(format!("{}{}", prefix, key)),
+            None => key,
+        };
+        self.write(&key, &value)
+    }
+}
+
+impl Options {
+    /// The characters left unencoded, and whether spaces are written as `+`.
+    fn escaping(&self) -> (EncodeSet, bool) {
+        match self.canonical {
+            true => (EncodeSet::Unreserved, false),
+            false => (self.encode_set, !self.percent_encode_space),
+        }
+    }
+}
+
+/// Percent-encodes decoded pairs with `opts`, into a body allocated once at
+/// its final length.
+pub(crate) fn encode_pairs(pairs: &[(String, String)], opts: &Options) -> String {
+    let pairs = ordered(pairs, opts);
+    let (set, space_as_plus) = opts.escaping();
+    let len = pairs
+        .iter()
+        .map(|(key, value)| {
+            escaped_len(key, set, space_as_plus) + escaped_len(value, set, space_as_plus) + 2
+        })
+        .sum::<usize>();
+
+    let mut body = String::with_capacity(len.saturating_sub(1));
+    for (key, value) in pairs {
+        if !body.is_empty() {
+            body.push('&');
+        }
+        escape(&mut body, key, set, space_as_plus);
+        body.push('=');
+        escape(&mut body, value, set, space_as_plus);
+    }
+    body
+}
+
+/// `pairs` in the order they are written in: sorted by key, keeping the order
+/// of the values of a repeated key, with `canonical`.
+pub(crate) fn ordered<'a>(
+    pairs: &'a [(String, String)],
+    opts: &Options,
+) -> Vec<&'a (String, String)> {
+    let mut ordered: Vec<_> = pairs.iter().collect();
+    if opts.canonical {
+        ordered.sort_by(|(a, _), (b, _)| a.cmp(b));
+    }
+    ordered
+}
+
+/// The length of `s` once [`escape`]d.
+fn escaped_len(s: &str, set: EncodeSet, space_as_plus: bool) -> usize {
+    s.bytes()
+        .map(|b| match set.keeps(b) || b == b' ' && space_as_plus {
+            true => 1,
+            false => 3,
+        })
+        .sum()
+}
+
+fn escape(out: &mut String, s: &str, set: EncodeSet, space_as_plus: bool) {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+    for &b in s.as_bytes() {
+        if set.keeps(b) {
+            out.push(b as char);
+        } else if b == b' ' && space_as_plus {
+            out.push('+');
+        } else {
+            out.push('%');
+            out.push(HEX[usize::from(b >> 4)] as char);
+            out.push(HEX[usize::from(b & 0xf)] as char);
+        }
+    }
+}
+
+/// Serializes a value into decoded key/value pairs.
+pub fn to_pairs<T: Serialize + ?Sized>(value: &T) -> Result<Vec<(String, String)>, Error> {
+    let mut pairs = Vec::new();
+    value.serialize(Serializer::new(&mut pairs))?;
+    Ok(pairs)
+}
+
+/// Serializes a value into decoded key/value pairs, with keys translated and
+/// prefixed as set by `opts`.
+pub fn to_pairs_with<T: Serialize + ?Sized>(
+    value: &T,
+    opts: Options,
+) -> Result<Vec<(String, String)>, Error> {
+    let mut pairs = Vec::new();
+    value.serialize(Serializer::new(&mut pairs).none_as_empty(opts.none_as_empty))?;
+    if let Some(case) = opts.key_case {
+        for (key, _) in &mut pairs {
+            *key = case.encode(key);
+        }
+    }
+    if let Some(prefix) = &opts.key_prefix {
+        for (key, _) in &mut pairs {
+            key.insert_str(0, prefix);
+        }
+    }
+    Ok(pairs)
+}
+
+/// Errors returned during serializing to `application/x-www-form-urlencoded`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The value cannot be represented as key/value pairs, or its `Serialize`
+    /// implementation reported an error.
+    Custom(Cow<'static, str>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Custom(msg) => msg.fmt(f),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self::Custom(format!("{}", msg).into())
+    }
+}
+
+fn unsupported<T>(what: &'static str) -> Result<T, Error> {
+    Err(Error::Custom(what.into()))
+}
+
+/// A serializer for the `application/x-www-form-urlencoded` format.
+///
+/// * Supported top-level inputs are structs, maps, enums and sequences of
+///   pairs.
+///
+/// * Values are collected as decoded key/value pairs; percent-encoding is
+///   left to the caller.
+pub struct Serializer<'a> {
+    pairs: &'a mut dyn Sink,
+    /// The key of the variant whose content is serialized, if any, which the
+    /// keys of its fields are nested under.
+    path: Option<String>,
+    none_as_empty: bool,
+}
+
+impl<'a> Serializer<'a> {
+    /// Returns a new `Serializer` appending to `pairs`.
+    pub fn new(pairs: &'a mut Vec<(String, String)>) -> Self {
+        Self::with_sink(pairs)
+    }
+
+    fn with_sink(pairs: &'a mut dyn Sink) -> Self {
+        Serializer {
+            pairs,
+            path: None,
+            none_as_empty: false,
+        }
+    }
+
+    /// Writes `None` values as a key with an empty value, `key=`, instead of
+    /// omitting them.
+    pub fn none_as_empty(mut self, none_as_empty: bool) -> Self {
+        self.none_as_empty = none_as_empty;
+        self
+    }
+
+    /// A serializer appending to the same pairs with the same settings.
+    fn reborrow(&mut self) -> Serializer<'_> {
+        Serializer {
+            pairs: &mut *self.pairs,
+            path: self.path.clone(),
+            none_as_empty: self.none_as_empty,
+        }
+    }
+
+    /// `key`, nested under the current variant, if any.
+    fn key<'k>(&self, key: &'k str) -> Cow<'k, str> {
+        match &self.path {
+            Some(path) => Cow::Owned(format!("{}.{}", path, key)),
+            None => Cow::Borrowed(key),
+        }
+    }
+
+    /// A serializer nesting the keys of the fields it serializes under `path`.
+    fn nested(self, path: String) -> Self {
+        Serializer {
+            path: Some(path),
+            ..self
+        }
+    }
+}
+
+macro_rules! top_level_unsupported {
+    ($($method:ident($($arg:ty),*),)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<(), Error> {
+                unsupported("top-level serializer supports only maps, structs and enums")
+            }
+        )*
+    }
+}
+
+impl<'a> ser::Serializer for Serializer<'a> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = PairsSerializer<'a>;
+    type SerializeTuple = PairsSerializer<'a>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = MapSerializer<'a>;
+    type SerializeStruct = MapSerializer<'a>;
+    type SerializeStructVariant = MapSerializer<'a>;
+
+    top_level_unsupported! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.pairs.push(&self.key(variant), String::new())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(ValueSerializer {
+            key: self.key(variant),
+            inner: self,
+            nest: true,
+        })
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<PairsSerializer<'a>, Error> {
+        Ok(PairsSerializer { inner: self })
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<PairsSerializer<'a>, Error> {
+        Ok(PairsSerializer { inner: self })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        unsupported("tuple structs cannot be serialized as key/value pairs")
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        unsupported("tuple variants cannot be serialized as key/value pairs")
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapSerializer<'a>, Error> {
+        Ok(MapSerializer::new(self))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<MapSerializer<'a>, Error> {
+        Ok(MapSerializer::new(self))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<MapSerializer<'a>, Error> {
+        let path = self.key(variant).into_owned();
+        Ok(MapSerializer::new(self.nested(path)))
+    }
+}
+
+/// Serializes the fields of structs, maps and struct variants.
+#[doc(hidden)]
+pub struct MapSerializer<'a> {
+    inner: Serializer<'a>,
+    key: Option<String>,
+}
+
+impl<'a> MapSerializer<'a> {
+    fn new(inner: Serializer<'a>) -> Self {
+        MapSerializer { inner, key: None }
+    }
+
+    fn value_serializer<'b>(&'b mut self, key: &'b str) -> ValueSerializer<'b> {
+        ValueSerializer {
+            key: self.inner.key(key),
+            inner: self.inner.reborrow(),
+            nest: false,
+        }
+    }
+}
+
+impl<'a> ser::SerializeMap for MapSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self.key.take().ok_or_else(|| {
+            Error::Custom("tried to serialize a value before serializing key".into())
+        })?;
+        value.serialize(self.value_serializer(&key))
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeStruct for MapSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self.value_serializer(key))
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeStructVariant for MapSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self.value_serializer(key))
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Serializes top-level sequences of `(key, value)` tuples.
+#[doc(hidden)]
+pub struct PairsSerializer<'a> {
+    inner: Serializer<'a>,
+}
+
+impl<'a> PairsSerializer<'a> {
+    fn serialize_pair<T: Serialize + ?Sized>(&mut self, pair: &T) -> Result<(), Error> {
+        pair.serialize(PairSerializer {
+            inner: MapSerializer::new(self.inner.reborrow()),
+            state: 0,
+        })
+    }
+}
+
+impl<'a> ser::SerializeSeq for PairsSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.serialize_pair(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTuple for PairsSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.serialize_pair(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Serializes a single `(key, value)` tuple.
+struct PairSerializer<'a> {
+    inner: MapSerializer<'a>,
+    state: u8,
+}
+
+impl<'a> ser::SerializeTuple for PairSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.state += 1;
+        match self.state {
+            1 => ser::SerializeMap::serialize_key(&mut self.inner, value),
+            2 => ser::SerializeMap::serialize_value(&mut self.inner, value),
+            _ => unsupported("expected a (key, value) pair"),
+        }
+    }
+
+    fn end(self) -> Result<(), Error> {
+        match self.state {
+            2 => Ok(()),
+            _ => unsupported("expected a (key, value) pair"),
+        }
+    }
+}
+
+macro_rules! pair_unsupported {
+    ($($method:ident($($arg:ty),*),)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<(), Error> {
+                unsupported("expected a (key, value) pair")
+            }
+        )*
+    }
+}
+
+impl<'a> ser::Serializer for PairSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = Impossible<(), Error>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    pair_unsupported! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_unit(),
+        serialize_unit_struct(&'static str),
+        serialize_unit_variant(&'static str, u32, &'static str),
+        serialize_none(),
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), Error> {
+        unsupported("expected a (key, value) pair")
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        unsupported("expected a (key, value) pair")
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self, Error> {
+        match len {
+            2 => Ok(self),
+            _ => unsupported("expected a (key, value) pair"),
+        }
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        unsupported("expected a (key, value) pair")
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        unsupported("expected a (key, value) pair")
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        unsupported("expected a (key, value) pair")
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        unsupported("expected a (key, value) pair")
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        unsupported("expected a (key, value) pair")
+    }
+}
+
+/// Serializes the value of a single field, flattening nested values into the
+/// surrounding pairs, or of a variant, nesting them under its key.
+struct ValueSerializer<'a> {
+    inner: Serializer<'a>,
+    key: Cow<'a, str>,
+    nest: bool,
+}
+
+impl<'a> ValueSerializer<'a> {
+    fn push(self, value: String) -> Result<(), Error> {
+        self.inner.pairs.push(&self.key, value)
+    }
+
+    /// The key of the content of `variant`, nested under this one.
+    fn variant_key(&self, variant: &str) -> String {
+        format!("{}.{}", self.key, variant)
+    }
+
+    /// The serializer of the fields of a struct or map value.
+    fn fields(self) -> Serializer<'a> {
+        match self.nest {
+            true => self.inner.nested(self.key.into_owned()),
+            false => self.inner,
+        }
+    }
+}
+
+macro_rules! serialize_display {
+    ($($method:ident($ty:ty),)*) => {
+        $(
+            fn $method(self, value: $ty) -> Result<(), Error> {
+                self.push(value.to_string())
+            }
+        )*
+    }
+}
+
+impl<'a> ser::Serializer for ValueSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = SeqSerializer<'a>;
+    type SerializeTuple = SeqSerializer<'a>;
+    type SerializeTupleStruct = SeqSerializer<'a>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = MapSerializer<'a>;
+    type SerializeStruct = MapSerializer<'a>;
+    type SerializeStructVariant = MapSerializer<'a>;
+
+    serialize_display! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<(), Error> {
+        match str::from_utf8(value) {
+            Ok(value) => self.push(value.to_owned()),
+            Err(e) => Err(ser::Error::custom(e)),
+        }
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        self.push(String::new())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        self.push(String::new())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.push(variant.to_owned())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(ValueSerializer {
+            key: Cow::Owned(self.variant_key(variant)),
+            inner: self.inner,
+            nest: true,
+        })
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        if self.inner.none_as_empty {
+            self.push(String::new())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqSerializer<'a>, Error> {
+        Ok(SeqSerializer { inner: self })
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<SeqSerializer<'a>, Error> {
+        Ok(SeqSerializer { inner: self })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<SeqSerializer<'a>, Error> {
+        Ok(SeqSerializer { inner: self })
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        unsupported("tuple variants cannot be serialized as key/value pairs")
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<MapSerializer<'a>, Error> {
+        ser::Serializer::serialize_map(self.fields(), len)
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<MapSerializer<'a>, Error> {
+        ser::Serializer::serialize_struct(self.fields(), name, len)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<MapSerializer<'a>, Error> {
+        let path = self.variant_key(variant);
+        Ok(MapSerializer::new(self.inner.nested(path)))
+    }
+}
+
+/// Serializes sequence values as one pair per element, all sharing a key.
+#[doc(hidden)]
+pub struct SeqSerializer<'a> {
+    inner: ValueSerializer<'a>,
+}
+
+impl<'a> SeqSerializer<'a> {
+    fn serialize_item<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(ValueSerializer {
+            inner: self.inner.inner.reborrow(),
+            key: Cow::Borrowed(&self.inner.key),
+            nest: self.inner.nest,
+        })
+    }
+}
+
+impl<'a> ser::SerializeSeq for SeqSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.serialize_item(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTuple for SeqSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.serialize_item(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for SeqSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.serialize_item(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Serializes a single value, such as a number or a unit variant, into its
+/// text.
+pub(crate) fn to_text<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    value.serialize(KeySerializer)
+}
+
+/// Serializes map keys into strings.
+struct KeySerializer;
+
+macro_rules! key_display {
+    ($($method:ident($ty:ty),)*) => {
+        $(
+            fn $method(self, value: $ty) -> Result<String, Error> {
+                Ok(value.to_string())
+            }
+        )*
+    }
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = Error;
+    type SerializeSeq = Impossible<String, Error>;
+    type SerializeTuple = Impossible<String, Error>;
+    type SerializeTupleStruct = Impossible<String, Error>;
+    type SerializeTupleVariant = Impossible<String, Error>;
+    type SerializeMap = Impossible<String, Error>;
+    type SerializeStruct = Impossible<String, Error>;
+    type SerializeStructVariant = Impossible<String, Error>;
+
+    key_display! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<String, Error> {
+        str::from_utf8(value)
+            .map(str::to_owned)
+            .map_err(ser::Error::custom)
+    }
+
+    fn serialize_unit(self) -> Result<String, Error> {
+        unsupported("keys must be strings")
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, Error> {
+        unsupported("keys must be strings")
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String, Error> {
+        Ok(variant.to_owned())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, Error> {
+        unsupported("keys must be strings")
+    }
+
+    fn serialize_none(self) -> Result<String, Error> {
+        unsupported("keys must be strings")
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<String, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        unsupported("keys must be strings")
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        unsupported("keys must be strings")
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        unsupported("keys must be strings")
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        unsupported("keys must be strings")
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        unsupported("keys must be strings")
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        unsupported("keys must be strings")
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        unsupported("keys must be strings")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::de;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum External {
+        Unit,
+        Newtype(u32),
+        Struct { x: u32, y: String },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Fields {
+        a: External,
+        b: External,
+    }
+
+    /// Serializes `value` and deserializes it back, with dot notation.
+    fn round_trip<T>(value: &T) -> (String, T)
+    where
+        T: Serialize + for<'de> Deserialize<'de>,
+    {
+        let opts = de::Options {
+            dot_notation: true,
+            ..Default::default()
+        };
+        let string = to_string(value).unwrap();
+        let parsed = de::from_pairs_with(de::parse_pairs(string.as_bytes()), opts).unwrap();
+        (string, parsed)
+    }
+
+    #[test]
+    fn external_enums_are_keyed_by_variant() {
+        assert_eq!(to_string(&External::Unit).unwrap(), "Unit=");
+        assert_eq!(de::from_str::<External>("Unit=").unwrap(), External::Unit);
+        assert_eq!(to_string(&External::Newtype(5)).unwrap(), "Newtype=5");
+        assert_eq!(
+            de::from_str::<External>("Newtype=5").unwrap(),
+            External::Newtype(5)
+        );
+
+        let value = External::Struct {
+            x: 1,
+            y: "a b".into(),
+        };
+        let (string, parsed) = round_trip(&value);
+        assert_eq!(string, "Struct.x=1&Struct.y=a+b");
+        assert_eq!(parsed, value);
+    }
+
+    #[test]
+    fn external_enum_fields_keep_their_keys() {
+        let value = Fields {
+            a: External::Newtype(1),
+            b: External::Struct {
+                x: 2,
+                y: "c".into(),
+            },
+        };
+        let (string, parsed) = round_trip(&value);
+        assert_eq!(string, "a.Newtype=1&b.Struct.x=2&b.Struct.y=c");
+        assert_eq!(parsed, value);
+
+        let value = Fields {
+            a: External::Unit,
+            b: External::Unit,
+        };
+        let (string, parsed) = round_trip(&value);
+        assert_eq!(string, "a=Unit&b=Unit");
+        assert_eq!(parsed, value);
+    }
+
+    #[test]
+    fn internal_enums_are_tagged() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        #[serde(tag = "type")]
+        enum Internal {
+            A { x: u32 },
+            B,
+        }
+
+        let (string, parsed) = round_trip(&Internal::A { x: 1 });
+        assert_eq!(string, "type=A&x=1");
+        assert_eq!(parsed, Internal::A { x: 1 });
+        let (string, parsed) = round_trip(&Internal::B);
+        assert_eq!(string, "type=B");
+        assert_eq!(parsed, Internal::B);
+    }
+
+    #[test]
+    fn options_apply_to_every_pair() {
+        #[derive(Serialize)]
+        struct Form {
+            first_name: &'static str,
+            tags: Vec<u32>,
+            note: Option<&'static str>,
+        }
+
+        let form = Form {
+            first_name: "a b",
+            tags: vec![2, 1],
+            note: None,
+        };
+        assert_eq!(to_string(&form).unwrap(), "first_name=a+b&tags=2&tags=1");
+
+        let opts = Options {
+            key_case: Some(crate::KeyCase::Camel),
+            key_prefix: Some("p.".into()),
+            percent_encode_space: true,
+            none_as_empty: true,
+            ..Default::default()
+        };
+        let body = "p.firstName=a%20b&p.tags=2&p.tags=1&p.note=";
+        assert_eq!(to_string_with(&form, opts.clone()).unwrap(), body);
+        let pairs = to_pairs_with(&form, opts.clone()).unwrap();
+        assert_eq!(encode_pairs(&pairs, &opts), body);
+
+        let opts = Options {
+            canonical: true,
+            ..opts
+        };
+        let body = "p.firstName=a%20b&p.note=&p.tags=2&p.tags=1";
+        assert_eq!(to_string_with(&form, opts).unwrap(), body);
+    }
+
+    #[test]
+    fn writer_failures_are_reported() {
+        struct Failing;
+
+        impl io::Write for Failing {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::other("closed"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let e = to_writer(Failing, &[("a", "1")]).unwrap_err();
+        assert_eq!(e, Error::Custom("closed".into()));
+    }
+}
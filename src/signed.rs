@@ -0,0 +1,311 @@
+// Copyright (c) 2021 Cognite AS
+//! Hidden form fields protected against tampering by an HMAC.
+//!
+//! This module is only available with the `secrets` feature enabled.
+
+use std::borrow::Cow;
+use std::ops::{Deref, DerefMut};
+use std::{fmt, io};
+
+use hmac::{Hmac, Mac};
+use rocket::data::{Data, FromData, Outcome};
+use rocket::fairing::{AdHoc, Fairing};
+use rocket::http::{ContentType, Status};
+use rocket::request::{self, FromRequest, Request};
+use rocket::{error, Build, Rocket};
+use serde::de::Error as _;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::{codec, de, ser, EnumFormConfig, Error, UrlEncodedCodec};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Signs values for hidden form fields with a key derived from Rocket's
+/// `secret_key`, for [`SignedForm`] to verify.
+///
+/// Managed by [`FormSigner::fairing()`], and available to handlers as a
+/// request guard. Without a `secret_key`, which Rocket only allows in debug,
+/// a random key is used, and forms signed before a restart fail to verify.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Serialize;
+/// use rocket_enumform::FormSigner;
+///
+/// #[derive(Serialize)]
+/// struct Quote {
+///     price: u32,
+/// }
+///
+/// #[get("/order")]
+/// fn order(signer: &FormSigner) -> String {
+///     let signed = signer.sign_for("/order", &Quote { price: 120 }).unwrap();
+///     format!(r#"<input type="hidden" name="{}" value="{}">"#, FormSigner::FIELD, signed)
+/// }
+///
+/// #[launch]
+/// fn rocket() -> _ {
+///     rocket::build()
+///         .attach(FormSigner::fairing())
+///         .mount("/", routes![order])
+/// }
+/// ```
+#[derive(Clone)]
+pub struct FormSigner {
+    key: [u8; 32],
+}
+
+impl FormSigner {
+    /// The name of the hidden field [`SignedForm`] reads the signed values
+    /// from.
+    pub const FIELD: &'static str = "_signed";
+
+    /// A signer with a key derived from the secret `material`.
+    pub fn new(material: &[u8]) -> Self {
+        let mut mac = HmacSha256::new_from_slice(material).expect("HMAC accepts any key length");
+        mac.update(b"rocket_enumform::FormSigner");
+        FormSigner {
+            key: mac.finalize().into_bytes().into(),
+        }
+    }
+
+    /// Returns a fairing which manages a signer keyed from the `secret_key`
+    /// of the configuration.
+    pub fn fairing() -> impl Fairing {
+        AdHoc::on_ignite("Form Signer", |rocket: Rocket<Build>| async move {
+            /// The `secret_key` as configured: base64 or hex text, or bytes.
+            #[derive(Deserialize)]
+            #[serde(untagged)]
+            enum Material {
+                Text(String),
+                Bytes(Vec<u8>),
+            }
+
+            let signer = match rocket.figment().extract_inner::<Material>("secret_key") {
+                Ok(Material::Text(text)) => FormSigner::new(text.as_bytes()),
+                Ok(Material::Bytes(bytes)) => FormSigner::new(&bytes),
+                Err(_) => FormSigner::new(&rand::random::<[u8; 32]>()),
+            };
+            rocket.manage(signer)
+        })
+    }
+
+    /// The value of a hidden [`FIELD`](FormSigner::FIELD) carrying `value`,
+    /// which any route with a [`SignedForm`] accepts: the value serialized
+    /// with [`ser::to_string`], followed by `.` and its HMAC in hexadecimal.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the value cannot be serialized.
+    pub fn sign<T: Serialize + ?Sized>(&self, value: &T) -> Result<String, ser::Error> {
+        self.sign_for("", value)
+    }
+
+    /// Like [`sign`](FormSigner::sign), but the HMAC covers `path` as well,
+    /// so that only a [`SignedForm`] submitted to that path, such as
+    /// `/order`, accepts the value.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the value cannot be serialized.
+    pub fn sign_for<T: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        value: &T,
+    ) -> Result<String, ser::Error> {
+        // Spaces are escaped as `%20`, which decodes the same with
+        // `literal_plus`, and `;` is always escaped.
+        let opts = ser::Options {
+            percent_encode_space: true,
+            ..Default::default()
+        };
+        let mut signed = ser::to_string_with(value, opts)?;
+        let tag = self.mac(path, &signed).finalize().into_bytes();
+        signed.push('.');
+        for byte in tag {
+            signed.push_str(&format!("{:02x}", byte));
+        }
+        Ok(signed)
+    }
+
+    /// The serialized value of `signed`, a value returned by
+    /// [`sign`](FormSigner::sign), if its HMAC is valid.
+    pub fn verify<'a>(&self, signed: &'a str) -> Option<&'a str> {
+        self.verify_for("", signed)
+    }
+
+    /// The serialized value of `signed`, a value returned by
+    /// [`sign_for`](FormSigner::sign_for) with `path`, if its HMAC is valid.
+    pub fn verify_for<'a>(&self, path: &str, signed: &'a str) -> Option<&'a str> {
+        let (value, hex) = signed.rsplit_once('.')?;
+        let tag = crate::decode_hex(hex)?;
+        self.mac(path, value)
+            .verify_slice(&tag)
+            .ok()
+            .map(|()| value)
+    }
+
+    fn mac(&self, path: &str, value: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts any key length");
+        mac.update(&(path.len() as u64).to_be_bytes());
+        mac.update(path.as_bytes());
+        mac.update(value.as_bytes());
+        mac
+    }
+
+    /// Moves the signed values of `pairs` into them, failing unless they are
+    /// signed by this signer, for any path or for `path`.
+    fn unsign(
+        &self,
+        pairs: &mut de::Pairs<'_>,
+        path: &str,
+        opts: &de::Options,
+    ) -> Result<(), de::Error> {
+        let mut signed = None;
+        for (key, value) in std::mem::take(pairs) {
+            match key == FormSigner::FIELD {
+                true if signed.is_some() => {
+                    return Err(de::Error::custom(format_args!(
+                        "duplicate field `{}`",
+                        FormSigner::FIELD
+                    )));
+                }
+                true => signed = Some(value),
+                false => pairs.push((key, value)),
+            }
+        }
+        let signed = signed.ok_or_else(|| de::Error::missing_field(FormSigner::FIELD))?;
+        let value = self
+            .verify_for(path, &signed)
+            .or_else(|| self.verify(&signed))
+            .ok_or_else(|| {
+                de::Error::custom(format_args!("invalid signature of `{}`", FormSigner::FIELD))
+            })?;
+
+        for (key, value) in codec::decode_pairs::<UrlEncodedCodec>(value, opts)? {
+            if pairs.iter().any(|(submitted, _)| *submitted == key) {
+                return Err(de::Error::custom(format_args!(
+                    "field `{}` is signed and cannot be submitted",
+                    key
+                )));
+            }
+            pairs.push((Cow::Owned(key.into_owned()), Cow::Owned(value.into_owned())));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for FormSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FormSigner").finish_non_exhaustive()
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for &'r FormSigner {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, ()> {
+        match req.rocket().state::<FormSigner>() {
+            Some(signer) => request::Outcome::Success(signer),
+            None => {
+                error!("FormSigner requires attaching `FormSigner::fairing()`.");
+                request::Outcome::Error((Status::InternalServerError, ()))
+            }
+        }
+    }
+}
+
+/// The SignedForm guard: [`UrlEncoded`](crate::UrlEncoded) with values the
+/// server signed with [`FormSigner::sign`], such as a price or a user id in
+/// hidden fields, which the client cannot change.
+///
+/// The signed values are read from the [`FormSigner::FIELD`] of the body and
+/// deserialized with the other fields submitted. The guard fails with
+/// `Status::UnprocessableEntity` if that field is missing, its signature is
+/// invalid, or a signed key is submitted as well. It requires
+/// [`FormSigner::fairing()`], and fails with `Status::InternalServerError`
+/// without it.
+///
+/// Values signed with [`FormSigner::sign`] can be submitted to any route of
+/// the application with a `SignedForm`. Sign them with
+/// [`FormSigner::sign_for`] and the path of the route instead to only accept
+/// them there.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket_enumform::SignedForm;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Order {
+///     price: u32,
+///     quantity: u32,
+/// }
+///
+/// #[post("/order", data = "<order>")]
+/// fn order(order: SignedForm<Order>) -> String {
+///     format!("{}", order.price * order.quantity)
+/// }
+/// ```
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SignedForm<T>(pub T);
+
+impl<T> SignedForm<T> {
+    /// Consumes the SignedForm wrapper and returns the wrapped item.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for SignedForm<T> {
+    fn from(value: T) -> Self {
+        SignedForm(value)
+    }
+}
+
+impl<T> Deref for SignedForm<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for SignedForm<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>> FromData<'r> for SignedForm<T> {
+    type Error = Error<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let signer = match req.rocket().state::<FormSigner>() {
+            Some(signer) => signer,
+            None => {
+                error!("SignedForm requires attaching `FormSigner::fairing()`.");
+                let e = io::Error::other("no FormSigner");
+                return Outcome::Error((Status::InternalServerError, Error::Io(e)));
+            }
+        };
+        let opts = EnumFormConfig::of(req).options();
+        let limit = crate::form_limit(req);
+        crate::parse_body(req, data, &ContentType::Form, limit, |body| {
+            let mut pairs = codec::decode_bytes(body, &opts);
+            signer
+                .unsign(&mut pairs, req.uri().path().as_str(), &opts)
+                .and_then(|()| de::from_pairs_with(pairs, opts))
+                .map(SignedForm)
+                .map_err(|e| Error::Parse(crate::raw_text(body), e))
+        })
+        .await
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright (c) 2021 Cognite AS
+//! Per-route statistics of the guards of this crate, in Prometheus' text
+//! format, and the time they spent during a request.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use rocket::fairing::{AdHoc, Fairing};
+use rocket::request::Request;
+
+/// The number of times the guards of this crate deserialized data per route,
+/// by outcome, and how long it took them.
+///
+/// Attach [`ParseStats::fairing()`] to collect them, and render them with
+/// [`ParseStats::render()`] in Prometheus' text format from a route of the
+/// application:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::State;
+/// use rocket_enumform::ParseStats;
+///
+/// #[get("/metrics")]
+/// fn metrics(stats: &State<ParseStats>) -> String {
+///     stats.render()
+/// }
+///
+/// #[launch]
+/// fn rocket() -> _ {
+///     rocket::build()
+///         .attach(ParseStats::fairing())
+///         .mount("/", routes![metrics])
+/// }
+/// ```
+///
+/// Data guards and [`QueryEncoded`](crate::QueryEncoded) are counted,
+/// including those which forward, while the value of a
+/// [`FromFormField`](rocket::form::FromFormField) is not.
+#[derive(Debug, Default)]
+pub struct ParseStats {
+    routes: Mutex<BTreeMap<RouteKey, RouteStats>>,
+}
+
+/// The time spent deserializing data during a request, in its local cache.
+#[derive(Default)]
+struct Elapsed(Mutex<Option<Duration>>);
+
+/// The method and the URI of a route.
+type RouteKey = (String, String);
+
+/// The statistics of a route.
+#[derive(Debug, Default, Clone, Copy)]
+struct RouteStats {
+    successes: u64,
+    failures: u64,
+    seconds: f64,
+}
+
+/// The counter of deserializations, labeled with the route and the outcome.
+const PARSE_TOTAL: &str = "enumform_route_parse_total";
+
+/// The summary of the durations of deserializations, labeled with the route.
+const DURATION_SECONDS: &str = "enumform_route_parse_duration_seconds";
+
+impl ParseStats {
+    /// Returns a fairing which manages an empty `ParseStats`, which the guards
+    /// of this crate then record in.
+    pub fn fairing() -> impl Fairing {
+        AdHoc::on_ignite("EnumForm Parse Stats", |rocket| async move {
+            rocket.manage(ParseStats::default())
+        })
+    }
+
+    /// The time the guards of this crate spent deserializing data during
+    /// `req`, if any did, for response fairings to include in access logs.
+    /// It is kept whether or not the fairing is attached.
+    ///
+    /// ```rust
+    /// use rocket::fairing::AdHoc;
+    /// use rocket_enumform::ParseStats;
+    ///
+    /// let access_log = AdHoc::on_response("Access Log", |req, res| {
+    ///     Box::pin(async move {
+    ///         let parse = ParseStats::elapsed(req).map(|d| d.as_micros());
+    ///         rocket::info!("{} {} {} parse={:?}us", req.method(), req.uri(), res.status(), parse);
+    ///     })
+    /// });
+    /// ```
+    pub fn elapsed(req: &Request<'_>) -> Option<Duration> {
+        *lock(&req.local_cache(Elapsed::default).0)
+    }
+
+    /// Records a deserialization during `req` which took `elapsed`, in `req`
+    /// and, if the application of `req` collects statistics, in them.
+    pub(crate) fn record(req: &Request<'_>, success: bool, elapsed: Duration) {
+        let total = &mut *lock(&req.local_cache(Elapsed::default).0);
+        *total = Some(total.unwrap_or_default() + elapsed);
+
+        let stats = match req.rocket().state::<ParseStats>() {
+            Some(stats) => stats,
+            None => return,
+        };
+        let route = match req.route() {
+            Some(route) => (route.method.to_string(), route.uri.to_string()),
+            None => (req.method().to_string(), req.uri().path().to_string()),
+        };
+        let mut routes = lock(&stats.routes);
+        let route = routes.entry(route).or_default();
+        match success {
+            true => route.successes += 1,
+            false => route.failures += 1,
+        }
+        route.seconds += elapsed.as_secs_f64();
+    }
+
+    /// The statistics in Prometheus' text exposition format:
+    ///
+    /// * `enumform_route_parse_total`, a counter labeled with the `method` and
+    ///   the `route` and the `outcome`, `success` or `failure`, and
+    /// * `enumform_route_parse_duration_seconds`, a summary of the durations
+    ///   labeled with the `method` and the `route`.
+    ///
+    /// Routes which did not deserialize data yet are left out.
+    pub fn render(&self) -> String {
+        let routes = self
+            .routes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let mut text = String::new();
+        let labels = |(method, route): &RouteKey| {
+            format!("method=\"{}\",route=\"{}\"", escape(method), escape(route))
+        };
+
+        let _ = writeln!(
+            text,
+            "# HELP {} Form data deserialized, by route and outcome.",
+            PARSE_TOTAL
+        );
+        let _ = writeln!(text, "# TYPE {} counter", PARSE_TOTAL);
+        for (key, stats) in &routes {
+            for (outcome, count) in [("success", stats.successes), ("failure", stats.failures)] {
+                let labels = labels(key);
+                let _ = writeln!(
+                    text,
+                    "{}{{{},outcome=\"{}\"}} {}",
+                    PARSE_TOTAL, labels, outcome, count
+                );
+            }
+        }
+
+        let _ = writeln!(
+            text,
+            "# HELP {} Time spent deserializing form data, by route.",
+            DURATION_SECONDS
+        );
+        let _ = writeln!(text, "# TYPE {} summary", DURATION_SECONDS);
+        for (key, stats) in &routes {
+            let (labels, count) = (labels(key), stats.successes + stats.failures);
+            let _ = writeln!(
+                text,
+                "{}_sum{{{}}} {}",
+                DURATION_SECONDS, labels, stats.seconds
+            );
+            let _ = writeln!(text, "{}_count{{{}}} {}", DURATION_SECONDS, labels, count);
+        }
+        text
+    }
+}
+
+/// Locks `mutex`, which a panic cannot leave inconsistent here.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Escapes a label value.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,119 @@
+// Copyright (c) 2021 Cognite AS
+//! Form fields for enums deriving [`strum`]'s string conversions.
+//!
+//! This module is only available with the `strum` feature enabled.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+
+use rocket::form::{self, FromFormField, ValueField};
+use rocket::http::uri::fmt::{Formatter as UriFormatter, FromUriParam, Query, UriDisplay};
+
+/// The StrumField form field: accept any enum deriving
+/// [`EnumString`](strum::EnumString) as a form or query value.
+///
+/// Values are parsed with the derived [`FromStr`] implementation and
+/// displayed in URIs with the one of [`IntoStaticStr`](strum::IntoStaticStr),
+/// so `#[strum(serialize = "...")]` and `#[strum(serialize_all = "...")]` are
+/// honored in both directions.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket_enumform::strum::StrumField;
+/// use strum::{EnumString, IntoStaticStr};
+///
+/// #[derive(Debug, EnumString, IntoStaticStr)]
+/// #[strum(serialize_all = "lowercase")]
+/// enum Sort {
+///     Asc,
+///     Desc,
+/// }
+///
+/// #[get("/items?<sort>")]
+/// fn items(sort: StrumField<Sort>) -> String {
+///     format!("{:?}", sort.into_inner())
+/// }
+///
+/// assert_eq!(uri!(items(Sort::Desc)).to_string(), "/items?sort=desc");
+/// ```
+///
+/// Values which do not name a variant fail with a validation error.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StrumField<T>(pub T);
+
+impl<T> StrumField<T> {
+    /// Consumes the StrumField wrapper and returns the wrapped item.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'v, T> FromFormField<'v> for StrumField<T>
+where
+    T: FromStr<Err = ::strum::ParseError> + Send,
+{
+    fn from_value(field: ValueField<'v>) -> form::Result<'v, Self> {
+        T::from_str(field.value)
+            .map(StrumField)
+            .map_err(|e| form::Error::validation(e.to_string()).into())
+    }
+}
+
+impl<T> UriDisplay<Query> for StrumField<T>
+where
+    for<'a> &'a T: Into<&'static str>,
+{
+    fn fmt(&self, f: &mut UriFormatter<'_, Query>) -> fmt::Result {
+        f.write_value((&self.0).into())
+    }
+}
+
+impl<T> FromUriParam<Query, T> for StrumField<T>
+where
+    for<'a> &'a T: Into<&'static str>,
+{
+    type Target = StrumField<T>;
+
+    #[inline(always)]
+    fn from_uri_param(param: T) -> Self::Target {
+        StrumField(param)
+    }
+}
+
+impl<T> FromUriParam<Query, StrumField<T>> for StrumField<T>
+where
+    for<'a> &'a T: Into<&'static str>,
+{
+    type Target = StrumField<T>;
+
+    #[inline(always)]
+    fn from_uri_param(param: StrumField<T>) -> Self::Target {
+        param
+    }
+}
+
+impl<T> From<T> for StrumField<T> {
+    fn from(value: T) -> Self {
+        StrumField(value)
+    }
+}
+
+impl<T> Deref for StrumField<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for StrumField<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
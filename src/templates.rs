@@ -0,0 +1,220 @@
+// Copyright (c) 2021 Cognite AS
+//! Re-rendering failed forms with
+//! [rocket_dyn_templates](https://docs.rs/rocket_dyn_templates).
+//!
+//! A [`StickyForm`], made from the [`FormContext`] of a
+//! [`ContextualUrlEncoded`](crate::ContextualUrlEncoded) form, holds the
+//! submitted values and the errors of each field. [`register()`] adds
+//! filters to Tera and helpers to Handlebars which look fields up in it, so
+//! that re-populating a field is one line of a template:
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! # use serde::Deserialize;
+//! use rocket::http::Status;
+//! use rocket_dyn_templates::{context, Template};
+//! use rocket_enumform::templates::{self, StickyForm};
+//! use rocket_enumform::ContextualUrlEncoded;
+//!
+//! #[derive(Debug, Deserialize)]
+//! struct Signup {
+//!     email: String,
+//!     age: u8,
+//! }
+//!
+//! // In `templates/signup.html.tera`:
+//! //
+//! // <input name="age" value="{{ form | field_value(name="age") }}">
+//! // {% for error in form | field_errors(name="age") %}<p>{{ error }}</p>{% endfor %}
+//! #[post("/signup", data = "<signup>")]
+//! fn signup(signup: ContextualUrlEncoded<Signup>) -> (Status, Template) {
+//!     let form = StickyForm::from(&signup.context);
+//!     (signup.context.status(), Template::render("signup", context! { form }))
+//! }
+//!
+//! #[launch]
+//! fn rocket() -> _ {
+//!     rocket::build()
+//!         .attach(Template::custom(|engines| templates::register(engines)))
+//!         .mount("/", routes![signup])
+//! }
+//! ```
+//!
+//! This module is only available with the `templates` feature enabled.
+
+use std::collections::{BTreeMap, HashMap};
+
+use rocket_dyn_templates::handlebars::{handlebars_helper, Handlebars};
+use rocket_dyn_templates::tera::{self, Tera};
+use rocket_dyn_templates::Engines;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::FormContext;
+
+/// The submitted values and errors of a form, by field, for templates.
+///
+/// It serializes as `{ "fields": { name: { "value": value, "values":
+/// [value, ...], "errors": [message, ...] } }, "errors": [message, ...] }`,
+/// where `value` is the first value submitted for the field, if any, and the
+/// outer `errors` concern the form as a whole.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StickyForm {
+    fields: BTreeMap<String, StickyField>,
+    errors: Vec<String>,
+}
+
+/// The submitted values and errors of a field of a [`StickyForm`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StickyField {
+    value: Option<String>,
+    values: Vec<String>,
+    errors: Vec<String>,
+}
+
+impl StickyForm {
+    /// The field `name`, if it was submitted or has errors.
+    pub fn field(&self, name: &str) -> Option<&StickyField> {
+        self.fields.get(name)
+    }
+
+    /// The errors concerning the form as a whole.
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+}
+
+impl StickyField {
+    /// The first value submitted, if any.
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    /// All values submitted, in submission order.
+    pub fn values(&self) -> &[String] {
+        &self.values
+    }
+
+    /// The messages of the errors of the field.
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+}
+
+impl From<&FormContext> for StickyForm {
+    fn from(context: &FormContext) -> Self {
+        let mut form = StickyForm::default();
+        for e in context.errors() {
+            match &e.name {
+                Some(name) => form
+                    .fields
+                    .entry(name.clone())
+                    .or_default()
+                    .errors
+                    .push(e.message.clone()),
+                None => form.errors.push(e.message.clone()),
+            }
+        }
+        for (name, values) in context.values() {
+            let field = form.fields.entry(name.to_string()).or_default();
+            field.values = values.to_vec();
+            field.value = values.first().cloned();
+        }
+        form
+    }
+}
+
+/// The field `name` of `form`, a serialized [`StickyForm`].
+fn field<'a>(form: &'a Value, name: &str) -> Option<&'a Value> {
+    form.get("fields")?.get(name)
+}
+
+/// The first value of the field `name` of `form`, or the empty string.
+fn field_value(form: &Value, name: &str) -> Value {
+    field(form, name)
+        .and_then(|field| field.get("value"))
+        .filter(|value| !value.is_null())
+        .cloned()
+        .unwrap_or_else(|| Value::String(String::new()))
+}
+
+/// The `key` list of the field `name` of `form`, or an empty one.
+fn field_list(form: &Value, name: &str, key: &str) -> Value {
+    field(form, name)
+        .and_then(|field| field.get(key))
+        .cloned()
+        .unwrap_or_else(|| Value::Array(Vec::new()))
+}
+
+/// Adds the filters `field_value`, `field_values` and `field_errors` to
+/// `tera`, which take a [`StickyForm`] and the `name` of a field:
+/// `{{ form | field_value(name="email") }}`.
+///
+/// ```rust
+/// use rocket_dyn_templates::tera::{Context, Tera};
+/// use rocket_enumform::templates;
+/// use serde_json::json;
+///
+/// let mut tera = Tera::default();
+/// templates::register_tera(&mut tera);
+/// let form = json!({ "fields": { "age": { "value": "x", "values": ["x"], "errors": ["invalid age"] } } });
+/// let context = Context::from_serialize(json!({ "form": form })).unwrap();
+/// let template = r#"<input value="{{ form | field_value(name="age") }}">{{ form | field_value(name="email") }}"#;
+/// let html = tera.render_str(template, &context).unwrap();
+/// assert_eq!(html, r#"<input value="x">"#);
+/// ```
+pub fn register_tera(tera: &mut Tera) {
+    fn name(args: &HashMap<String, Value>) -> tera::Result<&str> {
+        args.get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| tera::Error::msg("expected a `name` argument naming a field"))
+    }
+
+    tera.register_filter(
+        "field_value",
+        |form: &Value, args: &HashMap<String, Value>| Ok(field_value(form, name(args)?)),
+    );
+    tera.register_filter(
+        "field_values",
+        |form: &Value, args: &HashMap<String, Value>| Ok(field_list(form, name(args)?, "values")),
+    );
+    tera.register_filter(
+        "field_errors",
+        |form: &Value, args: &HashMap<String, Value>| Ok(field_list(form, name(args)?, "errors")),
+    );
+}
+
+handlebars_helper!(hbs_field_value: |form: Json, name: str| field_value(form, name));
+handlebars_helper!(hbs_field_values: |form: Json, name: str| field_list(form, name, "values"));
+handlebars_helper!(hbs_field_errors: |form: Json, name: str| field_list(form, name, "errors"));
+
+/// Adds the helpers `field_value`, `field_values` and `field_errors` to
+/// `handlebars`, which take a [`StickyForm`] and the name of a field:
+/// `{{field_value form "email"}}`, or
+/// `{{#each (field_errors form "email")}}...{{/each}}`.
+///
+/// ```rust
+/// use rocket_dyn_templates::handlebars::Handlebars;
+/// use rocket_enumform::templates;
+/// use serde_json::json;
+///
+/// let mut handlebars = Handlebars::new();
+/// templates::register_handlebars(&mut handlebars);
+/// let form = json!({ "fields": { "age": { "value": "x", "values": ["x"], "errors": ["invalid age"] } } });
+/// let template = r#"<input value="{{field_value form "age"}}">{{#each (field_errors form "age")}}<p>{{this}}</p>{{/each}}"#;
+/// let html = handlebars.render_template(template, &json!({ "form": form })).unwrap();
+/// assert_eq!(html, r#"<input value="x"><p>invalid age</p>"#);
+/// ```
+pub fn register_handlebars(handlebars: &mut Handlebars<'_>) {
+    handlebars.register_helper("field_value", Box::new(hbs_field_value));
+    handlebars.register_helper("field_values", Box::new(hbs_field_values));
+    handlebars.register_helper("field_errors", Box::new(hbs_field_errors));
+}
+
+/// Adds the filters of [`register_tera()`] and the helpers of
+/// [`register_handlebars()`] to `engines`, for
+/// [`Template::custom()`](rocket_dyn_templates::Template::custom).
+pub fn register(engines: &mut Engines) {
+    register_tera(&mut engines.tera);
+    register_handlebars(&mut engines.handlebars);
+}
@@ -0,0 +1,98 @@
+// Copyright (c) 2021 Cognite AS
+//! Assertions for the tests of types sent as forms.
+//!
+//! The assertions panic with the form and both values when they fail, for use
+//! in `#[test]` functions:
+//!
+//! ```rust
+//! use rocket_enumform::testing;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! #[serde(tag = "type", rename_all = "snake_case")]
+//! enum Body {
+//!     VariantOne { content_one: String },
+//!     VariantTwo { content_two: usize },
+//! }
+//!
+//! let form = testing::assert_roundtrip(&Body::VariantTwo { content_two: 5 });
+//! assert_eq!(form, "type=variant_two&content_two=5");
+//! testing::assert_parses("type=variant_one&content_one=a", &Body::VariantOne {
+//!     content_one: "a".to_string(),
+//! });
+//! ```
+//!
+//! With the `proptest` feature enabled, the [`proptest`] module generates
+//! forms to property-test parsing with.
+
+use std::fmt::Debug;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::FormExample;
+
+#[cfg(feature = "proptest")]
+pub mod proptest;
+
+/// Serializes `value` with [`to_string`](crate::to_string), deserializes the
+/// form with [`from_str`](crate::from_str) and asserts the result equals
+/// `value`, returning the form.
+///
+/// # Panics
+///
+/// Panics if `value` fails to serialize, if the form fails to deserialize or
+/// if it deserializes into another value.
+#[track_caller]
+pub fn assert_roundtrip<T>(value: &T) -> String
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let form = match crate::to_string(value) {
+        Ok(form) => form,
+        Err(e) => panic!("{:?} failed to serialize: {}", value, e),
+    };
+    assert_parses(&form, value);
+    form
+}
+
+/// Deserializes `form`, a body or query string without its `?`, with
+/// [`from_str`](crate::from_str) and asserts the result equals `expected`.
+///
+/// # Panics
+///
+/// Panics if `form` fails to deserialize or deserializes into another value.
+#[track_caller]
+pub fn assert_parses<'a, T>(form: &'a str, expected: &T)
+where
+    T: Deserialize<'a> + PartialEq + Debug,
+{
+    match crate::from_str::<T>(form) {
+        Ok(actual) if actual == *expected => {}
+        Ok(actual) => panic!(
+            "form `{}` parsed into another value\n  expected: {:?}\n    actual: {:?}",
+            form, expected, actual
+        ),
+        Err(e) => panic!(
+            "form `{}` failed to parse: {}\n  expected: {:?}",
+            form, e, expected
+        ),
+    }
+}
+
+/// Asserts each of the [`FormExample::examples()`] of `T` round-trips as
+/// [`assert_roundtrip`] does, covering every variant of an enum deriving
+/// [`FormExample`](trait@FormExample).
+///
+/// # Panics
+///
+/// Panics as [`assert_roundtrip`] does, for the first example which fails.
+#[track_caller]
+pub fn assert_examples_roundtrip<T>()
+where
+    T: FormExample + Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    for (_, value) in T::examples() {
+        assert_roundtrip(&value);
+    }
+}
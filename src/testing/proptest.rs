@@ -0,0 +1,172 @@
+// Copyright (c) 2021 Cognite AS
+//! [proptest](https://docs.rs/proptest) strategies generating forms, valid
+//! and adversarial, and a harness checking that parsing them never panics.
+//!
+//! [`check_form`] parses a form like the [`UrlEncoded`](crate::UrlEncoded)
+//! guard does, with the options of an [`EnumFormConfig`], and fails the test
+//! case if parsing panics, or fails without a message or with a server error
+//! status.
+//! [`assert_never_panics`] runs it over [`any_form()`]:
+//!
+//! ```rust
+//! use rocket_enumform::testing::proptest::{self as forms, check_form};
+//! use rocket_enumform::EnumFormConfig;
+//! use proptest::prelude::*;
+//! use serde::Deserialize;
+//!
+//! #[derive(Debug, Deserialize)]
+//! #[serde(tag = "type", rename_all = "snake_case")]
+//! enum Body {
+//!     VariantOne { content_one: String },
+//!     VariantTwo { content_two: usize },
+//! }
+//!
+//! forms::assert_never_panics::<Body>();
+//!
+//! proptest!(|(form in forms::adversarial_form())| {
+//!     check_form::<Body>(&form, &EnumFormConfig::default())?;
+//! });
+//! ```
+//!
+//! This module is only available with the `proptest` feature enabled.
+
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+
+use ::proptest::collection::vec;
+use ::proptest::prelude::*;
+use ::proptest::test_runner::{TestCaseError, TestRunner};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{codec, EnumFormConfig, Error, UrlEncodedCodec};
+
+/// Keys as forms name fields: `snake_case`, possibly dotted or bracketed.
+pub fn key() -> impl Strategy<Value = String> {
+    "[a-z_][a-z0-9_]{0,11}(\\.[a-z0-9_]{1,8}|\\[[a-z0-9_]{0,8}\\])?"
+}
+
+/// Any string, percent-encoded.
+pub fn value() -> impl Strategy<Value = String> {
+    any::<String>().prop_map(|value| form_urlencoded::byte_serialize(value.as_bytes()).collect())
+}
+
+/// Well-formed forms of up to 16 [`key()`] and [`value()`] pairs.
+pub fn form() -> impl Strategy<Value = String> {
+    vec((key(), value()), 0..16).prop_map(|pairs| {
+        let pairs: Vec<_> = pairs
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        pairs.join("&")
+    })
+}
+
+/// The forms of values of `values`, serialized with
+/// [`to_string`](crate::to_string). Values which fail to serialize are
+/// rejected.
+pub fn forms_of<T, S>(values: S) -> impl Strategy<Value = String>
+where
+    T: Serialize + Debug,
+    S: Strategy<Value = T>,
+{
+    values.prop_filter_map("value fails to serialize", |value| {
+        crate::to_string(&value).ok()
+    })
+}
+
+/// Percent escapes, valid and invalid: truncated, with non-hexadecimal
+/// digits, of NUL, and of invalid UTF-8.
+pub fn percent_escape() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "%[0-9a-fA-F]{2}",
+        "%[0-9a-fA-F]?",
+        "%[g-zG-Z%][0-9a-zA-Z]?",
+        Just("%00".to_string()),
+        Just("%c3%28".to_string()),
+        Just("%e2%82".to_string()),
+        Just("%ff%fe".to_string()),
+    ]
+}
+
+/// Keys of up to 64 KiB, beyond the default limits.
+pub fn huge_key() -> impl Strategy<Value = String> {
+    (1usize..=64 * 1024).prop_map(|len| "k".repeat(len))
+}
+
+/// A key repeated with up to 8 values.
+pub fn duplicate_keys() -> impl Strategy<Value = String> {
+    (key(), vec(value(), 2..8)).prop_map(|(key, values)| {
+        let pairs: Vec<_> = values
+            .into_iter()
+            .map(|value| format!("{}={}", key, value))
+            .collect();
+        pairs.join("&")
+    })
+}
+
+/// Forms assembled from the pieces of forms, unescaped text, separators and
+/// the strategies above, which are mostly malformed.
+pub fn adversarial_form() -> impl Strategy<Value = String> {
+    let piece = prop_oneof![
+        key(),
+        value(),
+        any::<String>(),
+        percent_escape(),
+        duplicate_keys(),
+        huge_key(),
+        Just("=".to_string()),
+        Just("&".to_string()),
+        Just(";".to_string()),
+        Just("+".to_string()),
+        Just("[]".to_string()),
+        Just(".".to_string()),
+    ];
+    vec(piece, 0..32).prop_map(|pieces| pieces.concat())
+}
+
+/// A [`form()`] or an [`adversarial_form()`].
+pub fn any_form() -> impl Strategy<Value = String> {
+    prop_oneof![form(), adversarial_form()]
+}
+
+/// Parses `form` into a `T` as the [`UrlEncoded`](crate::UrlEncoded) guard
+/// does with `config`, succeeding if it parses, or fails with a message and
+/// a client error status.
+///
+/// # Errors
+///
+/// Fails the test case if parsing panics, or fails without a message or
+/// with a server error status.
+pub fn check_form<T>(form: &str, config: &EnumFormConfig) -> Result<(), TestCaseError>
+where
+    T: DeserializeOwned + Debug,
+{
+    let opts = config.options();
+    let parse = || codec::decode_with::<UrlEncodedCodec, T>(form, opts).map(|_| ());
+    let e = match panic::catch_unwind(AssertUnwindSafe(parse)) {
+        Ok(Ok(())) => return Ok(()),
+        Ok(Err(e)) => Error::Parse(form, e),
+        Err(_) => return Err(TestCaseError::fail("parsing panicked")),
+    };
+    prop_assert!(!e.to_string().is_empty(), "error without a message");
+    prop_assert!(e.status().class().is_client_error(), "{:?}", e.status());
+    Ok(())
+}
+
+/// Runs [`check_form`] over [`any_form()`] with the default configuration.
+///
+/// # Panics
+///
+/// Panics with the shortest failing form found, if any.
+#[track_caller]
+pub fn assert_never_panics<T>()
+where
+    T: DeserializeOwned + Debug,
+{
+    let config = EnumFormConfig::default();
+    let result = TestRunner::default().run(&any_form(), |form| check_form::<T>(&form, &config));
+    if let Err(e) = result {
+        panic!("{}", e);
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright (c) 2021 Cognite AS
+//! Form fields for [`time`] dates and times in the formats of HTML inputs.
+//!
+//! This module is only available with the `time` feature enabled.
+
+use ::time::format_description;
+use ::time::{Date, PrimitiveDateTime};
+
+use crate::date::form_date;
+
+/// The format of a date or time field, in [`time`]'s
+/// [format description](time::format_description) syntax.
+///
+/// ```rust
+/// use rocket_enumform::time::{FormDate, Format};
+/// use serde::Deserialize;
+///
+/// struct Dotted;
+///
+/// impl Format for Dotted {
+///     const FORMAT: &'static str = "[day].[month].[year]";
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Invoice {
+///     due: FormDate<Dotted>,
+/// }
+///
+/// let invoice: Invoice = rocket_enumform::from_str("due=01.03.2024").unwrap();
+/// assert_eq!(invoice.due.to_string(), "2024-03-01");
+/// ```
+pub trait Format {
+    /// The format values are parsed in and written in.
+    const FORMAT: &'static str;
+
+    /// Further formats values are parsed in.
+    const ALSO_ACCEPTS: &'static [&'static str] = &[];
+}
+
+/// `2024-03-01`, as submitted by `<input type="date">`.
+#[derive(Debug, Clone, Copy)]
+pub struct HtmlDate;
+
+impl Format for HtmlDate {
+    const FORMAT: &'static str = "[year]-[month]-[day]";
+}
+
+/// `2024-03-01T09:30`, as submitted by `<input type="datetime-local">`, with
+/// seconds and fractions of them where the input's `step` allows them.
+/// Values are written with seconds, which the input accepts as well, but
+/// without fractions of them.
+#[derive(Debug, Clone, Copy)]
+pub struct HtmlDateTime;
+
+impl Format for HtmlDateTime {
+    const FORMAT: &'static str = "[year]-[month]-[day]T[hour]:[minute]:[second]";
+    const ALSO_ACCEPTS: &'static [&'static str] = &[
+        "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond]",
+        "[year]-[month]-[day]T[hour]:[minute]",
+    ];
+}
+
+fn parse_date(text: &str, format: &str) -> Option<Date> {
+    Date::parse(text, &format_description::parse_borrowed::<1>(format).ok()?).ok()
+}
+
+fn parse_date_time(text: &str, format: &str) -> Option<PrimitiveDateTime> {
+    PrimitiveDateTime::parse(text, &format_description::parse_borrowed::<1>(format).ok()?).ok()
+}
+
+fn format_date(date: &Date, format: &str) -> Option<String> {
+    date.format(&format_description::parse_borrowed::<1>(format).ok()?)
+        .ok()
+}
+
+fn format_date_time(date_time: &PrimitiveDateTime, format: &str) -> Option<String> {
+    date_time
+        .format(&format_description::parse_borrowed::<1>(format).ok()?)
+        .ok()
+}
+
+form_date! {
+    /// The FormDate field: a [`Date`] in the format `F`, by default the
+    /// one of `<input type="date">`.
+    ///
+    /// FormDate deserializes with serde and is a
+    /// [`FromFormField`](rocket::form::FromFormField); values are
+    /// serialized in the same format.
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// # use serde::Deserialize;
+    /// use rocket_enumform::time::FormDate;
+    /// use rocket_enumform::UrlEncoded;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Booking {
+    ///     arrival: FormDate,
+    /// }
+    ///
+    /// #[post("/book", format = "form", data = "<form>")]
+    /// fn book(form: UrlEncoded<Booking>) -> String {
+    ///     form.arrival.to_string()
+    /// }
+    /// ```
+    FormDate<HtmlDate>(Date),
+    "a date",
+    parse_date,
+    format_date
+}
+
+form_date! {
+    /// The FormDateTime field: a [`PrimitiveDateTime`] in the format `F`, by
+    /// default the one of `<input type="datetime-local">`.
+    ///
+    /// FormDateTime deserializes with serde and is a
+    /// [`FromFormField`](rocket::form::FromFormField); values are
+    /// serialized in the same format.
+    ///
+    /// ```rust
+    /// use rocket_enumform::time::FormDateTime;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Debug, Deserialize, Serialize)]
+    /// struct Meeting {
+    ///     start: FormDateTime,
+    /// }
+    ///
+    /// let meeting: Meeting = rocket_enumform::from_str("start=2024-03-01T09%3A30").unwrap();
+    /// assert_eq!(meeting.start.to_string(), "2024-03-01 9:30:00.0");
+    /// assert_eq!(
+    ///     rocket_enumform::to_string(&meeting).unwrap(),
+    ///     "start=2024-03-01T09%3A30%3A00"
+    /// );
+    /// ```
+    FormDateTime<HtmlDateTime>(PrimitiveDateTime),
+    "a date and time",
+    parse_date_time,
+    format_date_time
+}
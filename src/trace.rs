@@ -0,0 +1,49 @@
+// Copyright (c) 2021 Cognite AS
+//! Spans around the deserialization of form data.
+
+use rocket::request::Request;
+use tracing::field::Empty;
+
+use crate::Error;
+
+/// The name of the span form data is deserialized in.
+const SPAN: &str = "enumform.parse";
+
+/// Runs `parse` on `data`, the body or query of `req`, in an
+/// `enumform.parse` span with the fields:
+///
+/// * `body_size`: the length of `data` in bytes,
+/// * `content_type`: the Content-Type of `req`, if any,
+/// * `variant`: the tag of the internally tagged enum deserialized, if any,
+///   as submitted,
+/// * `outcome`: `success` or `failure`.
+pub(crate) fn traced<'r, D, S, F>(
+    req: &'r Request<'_>,
+    data: &'r D,
+    parse: F,
+) -> Result<S, Error<'r>>
+where
+    D: AsRef<[u8]> + ?Sized,
+    F: FnOnce(&'r D) -> Result<S, Error<'r>>,
+{
+    let content_type = req.content_type().map(ToString::to_string);
+    let span = tracing::info_span!(
+        SPAN,
+        body_size = data.as_ref().len(),
+        content_type = content_type.as_deref(),
+        variant = Empty,
+        outcome = Empty,
+    );
+    let result = span.in_scope(|| parse(data));
+    span.record(
+        "outcome",
+        if result.is_ok() { "success" } else { "failure" },
+    );
+    result
+}
+
+/// Records `tag` as the variant of the form data deserialized in the current
+/// span.
+pub(crate) fn record_variant(tag: &str) {
+    tracing::Span::current().record("variant", tag);
+}
@@ -0,0 +1,66 @@
+// Copyright (c) 2021 Cognite AS
+//! Documenting [`UrlEncoded`] responses in the OpenAPI documents of
+//! [`utoipa`](::utoipa).
+//!
+//! This module is only available with the `utoipa` feature enabled.
+
+use std::collections::BTreeMap;
+
+use ::utoipa::openapi::{Content, RefOr, Response};
+use ::utoipa::{IntoResponses, PartialSchema};
+
+use crate::UrlEncoded;
+
+/// The Content-Type of forms.
+const FORM: &str = "application/x-www-form-urlencoded";
+
+/// A `200 OK` response with an `application/x-www-form-urlencoded` body of
+/// the schema of `T`, as [`UrlEncoded`] responds.
+///
+/// The path macro takes the Content-Type of request bodies from their type,
+/// and would take a [`UrlEncoded`] body for JSON: document the body as `T`,
+/// with the Content-Type of forms:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket_enumform::UrlEncoded;
+/// use serde::{Deserialize, Serialize};
+/// use utoipa::{OpenApi, ToSchema};
+///
+/// #[derive(Debug, Deserialize, Serialize, ToSchema)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[utoipa::path(
+///     post,
+///     path = "/user",
+///     request_body(content = User, content_type = "application/x-www-form-urlencoded"),
+///     responses(UrlEncoded<User>)
+/// )]
+/// #[post("/user", data = "<user>")]
+/// fn echo(user: UrlEncoded<User>) -> UrlEncoded<User> {
+///     user
+/// }
+///
+/// #[derive(OpenApi)]
+/// #[openapi(paths(echo), components(schemas(User)))]
+/// struct ApiDoc;
+///
+/// let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+/// let post = &spec["paths"]["/user"]["post"];
+/// let form = "application/x-www-form-urlencoded";
+/// let body = &post["requestBody"]["content"][form]["schema"];
+/// assert_eq!(body["$ref"], "#/components/schemas/User");
+/// let response = &post["responses"]["200"]["content"][form]["schema"];
+/// assert_eq!(response["properties"]["name"]["type"], "string");
+/// ```
+impl<T: PartialSchema> IntoResponses for UrlEncoded<T> {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        let response = Response::builder()
+            .description("OK")
+            .content(FORM, Content::new(Some(T::schema())))
+            .build();
+        BTreeMap::from([("200".to_string(), response.into())])
+    }
+}
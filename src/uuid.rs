@@ -0,0 +1,142 @@
+// Copyright (c) 2021 Cognite AS
+//! A form field for [`uuid`] identifiers.
+//!
+//! This module is only available with the `uuid` feature enabled.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use ::uuid::Uuid;
+use rocket::form::{self, FromFormField, ValueField};
+use rocket::http::uri::fmt::{Formatter as UriFormatter, FromUriParam, Query, UriDisplay};
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+/// The FormUuid form field: a [`Uuid`] in its hyphenated form,
+/// `67e55044-10b1-426f-9247-bb680e5fe0c8`, or its simple one,
+/// `67e5504410b1426f9247bb680e5fe0c8`.
+///
+/// FormUuid deserializes with serde and is a [`FromFormField`]. It is
+/// serialized and displayed in URIs hyphenated, so that it can be a field of
+/// structs deriving [`UriDisplayQuery`](rocket::http::uri::fmt::UriDisplay)
+/// for `uri!`.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket_enumform::uuid::FormUuid;
+/// use uuid::Uuid;
+///
+/// #[derive(FromForm, UriDisplayQuery)]
+/// struct Lookup {
+///     id: FormUuid,
+/// }
+///
+/// #[get("/items?<lookup..>")]
+/// fn item(lookup: Lookup) -> String {
+///     lookup.id.to_string()
+/// }
+///
+/// let id = Uuid::parse_str("67e5504410b1426f9247bb680e5fe0c8").unwrap();
+/// assert_eq!(
+///     uri!(item(Lookup { id: id.into() })).to_string(),
+///     "/items?id=67e55044-10b1-426f-9247-bb680e5fe0c8"
+/// );
+/// ```
+///
+/// Other forms, such as URNs or braced UUIDs, fail with a validation error.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FormUuid(pub Uuid);
+
+impl FormUuid {
+    /// Consumes the FormUuid wrapper and returns the wrapped item.
+    #[inline(always)]
+    pub fn into_inner(self) -> Uuid {
+        self.0
+    }
+
+    /// Parses a hyphenated or simple UUID.
+    fn parse(text: &str) -> Option<Self> {
+        match text.len() {
+            32 | 36 => Uuid::try_parse(text).ok().map(FormUuid),
+            _ => None,
+        }
+    }
+}
+
+const EXPECTED: &str = "a hyphenated or simple UUID";
+
+impl<'de> Deserialize<'de> for FormUuid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = <Cow<'de, str>>::deserialize(deserializer)?;
+        FormUuid::parse(&text)
+            .ok_or_else(|| de::Error::invalid_value(de::Unexpected::Str(&text), &EXPECTED))
+    }
+}
+
+impl Serialize for FormUuid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0.hyphenated())
+    }
+}
+
+#[rocket::async_trait]
+impl<'v> FromFormField<'v> for FormUuid {
+    fn from_value(field: ValueField<'v>) -> form::Result<'v, Self> {
+        FormUuid::parse(field.value)
+            .ok_or_else(|| form::Error::validation(format!("expected {}", EXPECTED)).into())
+    }
+}
+
+impl UriDisplay<Query> for FormUuid {
+    fn fmt(&self, f: &mut UriFormatter<'_, Query>) -> fmt::Result {
+        f.write_value(self.0.hyphenated().to_string())
+    }
+}
+
+impl FromUriParam<Query, Uuid> for FormUuid {
+    type Target = FormUuid;
+
+    #[inline(always)]
+    fn from_uri_param(param: Uuid) -> Self::Target {
+        FormUuid(param)
+    }
+}
+
+impl FromUriParam<Query, FormUuid> for FormUuid {
+    type Target = FormUuid;
+
+    #[inline(always)]
+    fn from_uri_param(param: FormUuid) -> Self::Target {
+        param
+    }
+}
+
+impl fmt::Display for FormUuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.hyphenated().fmt(f)
+    }
+}
+
+impl From<Uuid> for FormUuid {
+    fn from(value: Uuid) -> Self {
+        FormUuid(value)
+    }
+}
+
+impl Deref for FormUuid {
+    type Target = Uuid;
+
+    #[inline(always)]
+    fn deref(&self) -> &Uuid {
+        &self.0
+    }
+}
+
+impl DerefMut for FormUuid {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Uuid {
+        &mut self.0
+    }
+}
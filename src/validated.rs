@@ -0,0 +1,190 @@
+// Copyright (c) 2021 Cognite AS
+//! Validation of deserialized forms with
+//! [validator](https://docs.rs/validator).
+
+use std::ops::{Deref, DerefMut};
+
+use rocket::data::{Data, FromData, Outcome};
+use rocket::form::prelude as form;
+use rocket::request::Request;
+use validator::{Validate, ValidationErrors, ValidationErrorsKind};
+
+use crate::{Error, FieldError};
+
+/// The Validated guard: a guard of this crate, such as
+/// [`UrlEncoded`](crate::UrlEncoded), whose value is validated with
+/// [`Validate::validate()`](validator::Validate::validate) once deserialized.
+///
+/// Constraints declared with `#[derive(Validate)]` on the type of the form
+/// are checked after its guard succeeds. A form which fails them fails the
+/// guard with [`Error::Validation`] and `Status::UnprocessableEntity`, whose
+/// problem details list the failing fields as `invalidFields`. As a form
+/// field, the errors are named after the fields, nested in the name of the
+/// form field.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket::http::{ContentType, Status};
+/// use rocket::local::blocking::Client;
+/// use rocket_enumform::{Error, UrlEncoded, Validated};
+/// use validator::Validate;
+///
+/// #[derive(Debug, Deserialize, Validate)]
+/// struct Signup {
+///     #[validate(email)]
+///     email: String,
+///     #[validate(range(min = 18, message = "must be an adult"))]
+///     age: u8,
+/// }
+///
+/// #[post("/signup", data = "<signup>")]
+/// fn signup(signup: Result<Validated<UrlEncoded<Signup>>, Error<'_>>) -> Result<String, Error<'_>> {
+///     Ok(signup?.into_inner().into_inner().email)
+/// }
+///
+/// let client = Client::untracked(rocket::build().mount("/", routes![signup])).unwrap();
+/// let request = client.post("/signup").header(ContentType::Form);
+/// let response = request.body("email=a%40b.c&age=12").dispatch();
+/// assert_eq!(response.status(), Status::UnprocessableEntity);
+/// let problem: serde_json::Value = response.into_json().unwrap();
+/// assert_eq!(problem["code"], "validation_failed");
+/// assert_eq!(problem["invalidFields"][0]["name"], "age");
+/// assert_eq!(problem["invalidFields"][0]["message"], "must be an adult");
+///
+/// let request = client.post("/signup").header(ContentType::Form);
+/// let response = request.body("email=a%40b.c&age=21").dispatch();
+/// assert_eq!(response.into_string().unwrap(), "a@b.c");
+/// ```
+///
+/// This guard is only available with the `validator` feature enabled.
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Validated<G>(pub G);
+
+impl<G> Validated<G> {
+    /// Consumes the Validated wrapper and returns the wrapped guard.
+    #[inline(always)]
+    pub fn into_inner(self) -> G {
+        self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, G> FromData<'r> for Validated<G>
+where
+    G: FromData<'r, Error = Error<'r>> + Deref,
+    G::Target: Validate,
+{
+    type Error = Error<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let guard = match G::from_data(req, data).await {
+            Outcome::Success(guard) => guard,
+            Outcome::Error(e) => return Outcome::Error(e),
+            Outcome::Forward(f) => return Outcome::Forward(f),
+        };
+        let result = guard.validate().map(|()| Validated(guard));
+        crate::data_outcome(req, result.map_err(Error::Validation))
+    }
+}
+
+#[rocket::async_trait]
+impl<'v, G> form::FromFormField<'v> for Validated<G>
+where
+    G: form::FromFormField<'v> + Deref,
+    G::Target: Validate,
+{
+    fn from_value(field: form::ValueField<'v>) -> Result<Self, form::Errors<'v>> {
+        let name = field.name.source();
+        let guard = G::from_value(field)?;
+        validate(guard, name)
+    }
+
+    async fn from_data(field: form::DataField<'v, '_>) -> Result<Self, form::Errors<'v>> {
+        let name = field.name.source();
+        let guard = G::from_data(field).await?;
+        validate(guard, name)
+    }
+}
+
+/// Validates the value of `guard`, the form field `parent`.
+fn validate<'v, G>(guard: G, parent: &'v form::Name) -> Result<Validated<G>, form::Errors<'v>>
+where
+    G: Deref,
+    G::Target: Validate,
+{
+    match guard.validate() {
+        Ok(()) => Ok(Validated(guard)),
+        Err(errors) => Err(field_errors(&errors)
+            .into_iter()
+            .map(|e| {
+                let mut error = form::Error::validation(e.message);
+                error.set_name((Some(parent), e.name.unwrap_or_default()));
+                error
+            })
+            .collect::<Vec<_>>()
+            .into()),
+    }
+}
+
+/// The errors of `errors`, named after their fields, with nested fields as
+/// `outer.inner` and items as `list[0]`, in order of name. Errors of the
+/// struct as a whole have no name.
+pub(crate) fn field_errors(errors: &ValidationErrors) -> Vec<FieldError> {
+    fn collect(errors: &ValidationErrors, prefix: Option<&str>, out: &mut Vec<FieldError>) {
+        for (field, kind) in errors.errors() {
+            let name = match (prefix, &**field) {
+                (prefix, "__all__") => prefix.map(String::from),
+                (Some(prefix), field) => Some(format!("{}.{}", prefix, field)),
+                (None, field) => Some(field.to_string()),
+            };
+            match kind {
+                ValidationErrorsKind::Field(errors) => {
+                    out.extend(errors.iter().map(|e| FieldError {
+                        name: name.clone(),
+                        message: match &e.message {
+                            Some(message) => message.to_string(),
+                            None => format!("failed `{}` validation", e.code),
+                        },
+                    }))
+                }
+                ValidationErrorsKind::Struct(errors) => collect(errors, name.as_deref(), out),
+                ValidationErrorsKind::List(items) => {
+                    for (i, errors) in items {
+                        let name = format!("{}[{}]", name.as_deref().unwrap_or_default(), i);
+                        collect(errors, Some(&name), out);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    collect(errors, None, &mut out);
+    // The fields of `ValidationErrors` are in no particular order.
+    out.sort_by(|a, b| a.name.cmp(&b.name));
+    out
+}
+
+impl<G> From<G> for Validated<G> {
+    fn from(guard: G) -> Self {
+        Validated(guard)
+    }
+}
+
+impl<G> Deref for Validated<G> {
+    type Target = G;
+
+    #[inline(always)]
+    fn deref(&self) -> &G {
+        &self.0
+    }
+}
+
+impl<G> DerefMut for Validated<G> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut G {
+        &mut self.0
+    }
+}
@@ -0,0 +1,445 @@
+// Copyright (c) 2021 Cognite AS
+//! A dynamic value for form data of unknown shape.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::de::value::{MapAccessDeserializer, MapDeserializer, SeqDeserializer};
+use serde::de::{self, DeserializeOwned, IntoDeserializer};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use serde::{forward_to_deserialize_any, Deserialize};
+use serde_json::{Map, Value as Json};
+
+use crate::{de as form_de, ser};
+
+/// Form data of any shape, for handlers which inspect it before, or instead
+/// of, converting it into a type with [`from_value()`].
+///
+/// `UrlEncoded<Value>` deserializes a body into a [`Value::Map`] of its keys.
+/// A key submitted more than once keeps its last value, unless
+/// [`Duplicates::Collect`](crate::de::Duplicates::Collect) collects all of
+/// them into a [`Value::Seq`]; nested keys, such as with
+/// [`dot_notation`](crate::de::Options::dot_notation), are nested maps.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket_enumform::{UrlEncoded, Value};
+///
+/// #[post("/debug", format = "form", data = "<form>")]
+/// fn debug(form: UrlEncoded<Value>) -> String {
+///     format!("{:?}", form.get("name").and_then(Value::as_str))
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Value {
+    /// A single value.
+    String(String),
+
+    /// The values of a key submitted more than once.
+    Seq(Vec<Value>),
+
+    /// Keys and their values.
+    Map(BTreeMap<String, Value>),
+}
+
+impl Value {
+    /// The string, if this is a single value.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The value of `key`, if this is a map which has it.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Map(map) => map.get(key),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Value {
+    fn default() -> Self {
+        Value::Map(BTreeMap::new())
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::String(s) => serializer.serialize_str(s),
+            Value::Seq(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            }
+            Value::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> de::Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("form data")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::String(String::new()))
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::String(String::new()))
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(Value::Seq(values))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut entries = BTreeMap::new();
+        while let Some((key, value)) = map.next_entry()? {
+            entries.insert(key, value);
+        }
+        Ok(Value::Map(entries))
+    }
+}
+
+/// Converts a [`Value`] into a `T`, parsing single values as the types of the
+/// fields they fill, like [`UrlEncoded`](crate::UrlEncoded) does.
+///
+/// ```rust
+/// use rocket_enumform::{from_value, to_value};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Deserialize, Serialize)]
+/// struct Page {
+///     offset: usize,
+///     tags: Vec<String>,
+/// }
+///
+/// let page = Page { offset: 20, tags: vec!["a".into(), "b".into()] };
+/// let value = to_value(&page).unwrap();
+/// assert_eq!(from_value::<Page>(value).unwrap(), page);
+/// ```
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, form_de::Error> {
+    T::deserialize(ValueDeserializer::new(&value, false))
+        .or_else(|e| T::deserialize(ValueDeserializer::new(&value, true)).map_err(|_| e))
+}
+
+/// Converts a `T` into a [`Value::Map`] of the pairs it serializes into, with
+/// the values of repeated keys in a [`Value::Seq`].
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, ser::Error> {
+    let mut entries = BTreeMap::new();
+    for (key, value) in ser::to_pairs(value)? {
+        let value = Value::String(value);
+        match entries.remove(&key) {
+            None => entries.insert(key, value),
+            Some(Value::Seq(mut values)) => {
+                values.push(value);
+                entries.insert(key, Value::Seq(values))
+            }
+            Some(first) => entries.insert(key, Value::Seq(vec![first, value])),
+        };
+    }
+    Ok(Value::Map(entries))
+}
+
+/// Converts urlencoded pairs into JSON, for logging or transforming payloads
+/// as JSON.
+///
+/// Keys are nested into objects at their dots and the values of a key
+/// submitted more than once are collected into an array, in submission order.
+/// Values are kept as strings. A key which is both a value and has nested
+/// keys, such as `a=1&a.b=2`, fails with a conflicting field error.
+///
+/// [`from_json_value()`] converts the JSON back into the same pairs, although
+/// ordered by key rather than by submission.
+///
+/// ```rust
+/// use rocket_enumform::{from_json_value, to_json_value};
+/// use serde_json::json;
+///
+/// let pairs = vec![("user.name", "Ann"), ("tags", "a"), ("tags", "b")];
+/// let json = to_json_value(pairs).unwrap();
+/// assert_eq!(json, json!({ "user": { "name": "Ann" }, "tags": ["a", "b"] }));
+///
+/// let pairs = from_json_value(&json).unwrap();
+/// assert_eq!(pairs[0], ("tags".to_string(), "a".to_string()));
+/// assert_eq!(pairs[2], ("user.name".to_string(), "Ann".to_string()));
+/// ```
+pub fn to_json_value<I, K, V>(pairs: I) -> Result<Json, form_de::Error>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let mut object = Map::new();
+    for (key, value) in pairs {
+        let key = key.as_ref();
+        insert_json(&mut object, key, key, value.as_ref().to_string())?;
+    }
+    Ok(Json::Object(object))
+}
+
+/// Inserts `value` at the dotted `key` of `object`, where `key` is the rest of
+/// the full key `path`.
+fn insert_json(
+    object: &mut Map<String, Json>,
+    path: &str,
+    key: &str,
+    value: String,
+) -> Result<(), form_de::Error> {
+    if let Some((head, tail)) = key.split_once('.') {
+        let nested = object
+            .entry(head)
+            .or_insert_with(|| Json::Object(Map::new()));
+        return match nested {
+            Json::Object(nested) => insert_json(nested, path, tail, value),
+            _ => Err(form_de::Error::conflict(path)),
+        };
+    }
+    match object.get_mut(key) {
+        None => {
+            object.insert(key.to_string(), Json::String(value));
+        }
+        Some(Json::Array(values)) => values.push(Json::String(value)),
+        Some(Json::Object(_)) => return Err(form_de::Error::conflict(path)),
+        Some(first) => {
+            let first = first.take();
+            object.insert(
+                key.to_string(),
+                Json::Array(vec![first, Json::String(value)]),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Converts JSON into urlencoded pairs, the reverse of [`to_json_value()`].
+///
+/// `value` must be an object. Nested objects become dotted keys and arrays
+/// become a key repeated for each element. Numbers and booleans are written
+/// as text and `null` values are skipped, like `None` fields are when
+/// serializing. Arrays of arrays or of objects have no such encoding and
+/// fail.
+pub fn from_json_value(value: &Json) -> Result<Vec<(String, String)>, ser::Error> {
+    let mut pairs = Vec::new();
+    match value {
+        Json::Object(object) => flatten_json(&mut pairs, None, object)?,
+        _ => {
+            return Err(ser::Error::Custom(
+                "top-level value is not an object".into(),
+            ))
+        }
+    }
+    Ok(pairs)
+}
+
+/// Appends the pairs of `object` below `prefix` to `pairs`.
+fn flatten_json(
+    pairs: &mut Vec<(String, String)>,
+    prefix: Option<&str>,
+    object: &Map<String, Json>,
+) -> Result<(), ser::Error> {
+    for (key, value) in object {
+        let key = match prefix {
+            Some(prefix) => format!("{}.{}", prefix, key),
+            None => key.clone(),
+        };
+        match value {
+            Json::Object(nested) => flatten_json(pairs, Some(&key), nested)?,
+            Json::Array(values) => {
+                for value in values {
+                    match json_text(value) {
+                        Some(text) => pairs.push((key.clone(), text)),
+                        None if value.is_null() => {}
+                        None => {
+                            let what = format!("field `{}` is an array of arrays or objects", key);
+                            return Err(ser::Error::Custom(what.into()));
+                        }
+                    }
+                }
+            }
+            Json::Null => {}
+            _ => pairs.extend(json_text(value).map(|text| (key, text))),
+        }
+    }
+    Ok(())
+}
+
+/// The text of a JSON string, number or boolean.
+fn json_text(value: &Json) -> Option<String> {
+    match value {
+        Json::String(s) => Some(s.clone()),
+        Json::Number(n) => Some(n.to_string()),
+        Json::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Deserializes a borrowed [`Value`], inferring booleans and numbers of
+/// single values in `deserialize_any` with `infer`.
+struct ValueDeserializer<'de> {
+    value: &'de Value,
+    infer: bool,
+}
+
+impl<'de> ValueDeserializer<'de> {
+    fn new(value: &'de Value, infer: bool) -> Self {
+        ValueDeserializer { value, infer }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, form_de::Error> for ValueDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! forward_to_text {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+                where V: de::Visitor<'de>
+            {
+                match self.value {
+                    Value::String(s) => form_de::text(s, self.infer).$method(visitor),
+                    _ => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = form_de::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let infer = self.infer;
+        match self.value {
+            Value::String(s) => form_de::text(s, infer).deserialize_any(visitor),
+            Value::Seq(values) => {
+                let values = values.iter().map(|v| ValueDeserializer::new(v, infer));
+                let mut seq = SeqDeserializer::new(values);
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Value::Map(entries) => {
+                let entries = entries
+                    .iter()
+                    .map(|(k, v)| (k.as_str(), ValueDeserializer::new(v, infer)));
+                visitor.visit_map(MapDeserializer::new(entries))
+            }
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    /// Externally tagged enums are maps with the variant as the only key.
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let infer = self.infer;
+        match self.value {
+            Value::String(s) => form_de::text(s, infer).deserialize_enum(name, variants, visitor),
+            Value::Map(entries) => {
+                let entries = entries
+                    .iter()
+                    .map(|(k, v)| (k.as_str(), ValueDeserializer::new(v, infer)));
+                visitor.visit_enum(MapAccessDeserializer::new(MapDeserializer::new(entries)))
+            }
+            Value::Seq(_) => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_text! {
+        deserialize_bool deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_f32
+        deserialize_f64 deserialize_char deserialize_str deserialize_string deserialize_seq
+        deserialize_identifier
+    }
+
+    forward_to_deserialize_any! {
+        bytes byte_buf unit unit_struct tuple tuple_struct map struct ignored_any
+    }
+}
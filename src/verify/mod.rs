@@ -0,0 +1,296 @@
+// Copyright (c) 2021 Cognite AS
+//! Guards verifying the signatures of webhooks which send forms, such as
+//! Slack's slash commands and Twilio's webhooks, before parsing them.
+//!
+//! Other schemes are supported by implementing [`SignatureVerifier`] for use
+//! with the [`Verified`] guard. PayPal's notifications, which are not signed
+//! but confirmed by PayPal, are verified with the
+//! [`PaypalIpn`](paypal::PaypalIpn) guard instead.
+//!
+//! Signatures are computed over the body as the guards read it: as received,
+//! unless the `compression` or `charset` features decompress or transcode it
+//! where the request asks for it. A request with a missing, invalid or
+//! expired signature fails with [`Error::Signature`],
+//! `Status::Unauthorized`.
+//!
+//! A signature is expired if its timestamp differs from the current time by
+//! more than [`EnumFormConfig::max_clock_skew`]. Replays of a signed request
+//! within that time are rejected with the same error by the [`NonceStore`]
+//! attached with [`replay_protection()`], if any.
+//!
+//! This module is only available with the `verify` feature enabled.
+
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rocket::data::{Data, FromData, Outcome};
+use rocket::error;
+use rocket::fairing::{AdHoc, Fairing};
+use rocket::http::{ContentType, Status};
+use rocket::request::Request;
+use serde::Deserialize;
+
+use crate::{codec, EnumFormConfig, Error, UrlEncodedCodec};
+
+pub mod paypal;
+pub mod slack;
+pub mod twilio;
+
+/// A scheme verifying the signatures of forms sent to the application, such
+/// as webhooks signed with a shared secret.
+///
+/// The verifier is managed by the application, and [`Verified`] calls it with
+/// the request, for its headers and URI, and the body before parsing it.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use hmac::{Hmac, Mac};
+/// use rocket::request::Request;
+/// use rocket_enumform::verify::{SignatureVerifier, Verified};
+/// use sha2::Sha256;
+///
+/// /// GitHub's `X-Hub-Signature-256`: `sha256=` and the hexadecimal
+/// /// HMAC-SHA256 of the body.
+/// struct GitHubSecret(Vec<u8>);
+///
+/// impl SignatureVerifier for GitHubSecret {
+///     fn verify(&self, req: &Request<'_>, body: &str) -> bool {
+///         let signature = req.headers().get_one("X-Hub-Signature-256");
+///         let hex = match signature.and_then(|s| s.strip_prefix("sha256=")) {
+///             Some(hex) if hex.len() % 2 == 0 && hex.is_ascii() => hex,
+///             _ => return false,
+///         };
+///         let tag: Option<Vec<u8>> = (0..hex.len())
+///             .step_by(2)
+///             .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+///             .collect();
+///         let mut mac = Hmac::<Sha256>::new_from_slice(&self.0).unwrap();
+///         mac.update(body.as_bytes());
+///         tag.is_some_and(|tag| mac.verify_slice(&tag).is_ok())
+///     }
+/// }
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Event {
+///     payload: String,
+/// }
+///
+/// #[post("/github", data = "<event>")]
+/// fn github(event: Verified<Event, GitHubSecret>) -> String {
+///     event.into_inner().payload
+/// }
+///
+/// #[launch]
+/// fn rocket() -> _ {
+///     rocket::build()
+///         .manage(GitHubSecret(b"secret".to_vec()))
+///         .mount("/", routes![github])
+/// }
+/// ```
+pub trait SignatureVerifier: Send + Sync + 'static {
+    /// Whether `req`, with the body `body`, carries a valid signature.
+    fn verify(&self, req: &Request<'_>, body: &str) -> bool;
+
+    /// The time `req` was signed at, in seconds since the Unix epoch, if the
+    /// signature covers one. Requests without a valid timestamp where one is
+    /// expected should fail to [`verify`](SignatureVerifier::verify).
+    /// Defaults to none.
+    fn timestamp(&self, req: &Request<'_>) -> Option<u64> {
+        let _ = req;
+        None
+    }
+
+    /// A value unique to each request signed and covered by its signature,
+    /// such as the signature itself, for the [`NonceStore`] to reject replays
+    /// with. Defaults to none.
+    fn nonce(&self, req: &Request<'_>, body: &str) -> Option<String> {
+        let _ = (req, body);
+        None
+    }
+}
+
+/// The nonces of the signed requests an application received, for
+/// [`replay_protection()`].
+///
+/// Stores shared by several instances of an application, such as ones backed
+/// by a database, detect replays to any of them.
+pub trait NonceStore: Send + Sync + 'static {
+    /// Records `nonce`, which may be forgotten after `expires`, in seconds
+    /// since the Unix epoch, returning whether it is new.
+    fn insert(&self, nonce: &str, expires: u64) -> bool;
+}
+
+/// A [`NonceStore`] in the memory of the process, which forgets nonces as
+/// they expire.
+#[derive(Debug, Default)]
+pub struct MemoryNonceStore {
+    nonces: Mutex<HashMap<String, u64>>,
+}
+
+impl MemoryNonceStore {
+    /// An empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl NonceStore for MemoryNonceStore {
+    fn insert(&self, nonce: &str, expires: u64) -> bool {
+        let now = now();
+        let mut nonces = self.nonces.lock().unwrap_or_else(|e| e.into_inner());
+        nonces.retain(|_, expires| *expires >= now);
+        match nonces.contains_key(nonce) {
+            true => false,
+            false => {
+                nonces.insert(nonce.to_string(), expires);
+                true
+            }
+        }
+    }
+}
+
+/// The store attached with [`replay_protection()`].
+struct Nonces(Box<dyn NonceStore>);
+
+/// Returns a fairing which has the guards of this module reject requests
+/// whose [`SignatureVerifier::nonce`] is in `store`, as replays, and record
+/// the others. A nonce is kept for [`EnumFormConfig::max_clock_skew`] past
+/// the timestamp of its request, or past its receipt without one.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket_enumform::verify::{self, MemoryNonceStore};
+///
+/// #[launch]
+/// fn rocket() -> _ {
+///     rocket::build().attach(verify::replay_protection(MemoryNonceStore::new()))
+/// }
+/// ```
+pub fn replay_protection<S: NonceStore>(store: S) -> impl Fairing {
+    let nonces = Nonces(Box::new(store));
+    AdHoc::on_ignite("Replay Protection", |rocket| async move {
+        rocket.manage(nonces)
+    })
+}
+
+/// The Verified guard: [`UrlEncoded`](crate::UrlEncoded) for forms whose
+/// signature the managed verifier `V` accepts, checked before parsing.
+///
+/// The guard fails with [`Error::Signature`], `Status::Unauthorized`, if the
+/// verifier rejects the request, and with `Status::InternalServerError` if
+/// no `V` is managed. See [`SignatureVerifier`] for an example.
+pub struct Verified<T, V>(pub T, PhantomData<V>);
+
+impl<T, V> Verified<T, V> {
+    /// Wraps `value`, as verified by `V`.
+    #[inline(always)]
+    pub fn new(value: T) -> Self {
+        Verified(value, PhantomData)
+    }
+
+    /// Consumes the Verified wrapper and returns the wrapped item.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T, V> From<T> for Verified<T, V> {
+    fn from(value: T) -> Self {
+        Verified::new(value)
+    }
+}
+
+impl<T, V> Deref for Verified<T, V> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T, V> DerefMut for Verified<T, V> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+// Implemented by hand, as derives would require them of `V`.
+impl<T: Clone, V> Clone for Verified<T, V> {
+    fn clone(&self) -> Self {
+        Verified::new(self.0.clone())
+    }
+}
+
+impl<T: std::fmt::Debug, V> std::fmt::Debug for Verified<T, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Verified").field(&self.0).finish()
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>, V: SignatureVerifier> FromData<'r> for Verified<T, V> {
+    type Error = Error<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        verified::<T, V>(req, data).await.map(Verified::new)
+    }
+}
+
+/// Reads the body of `req`, verifies it with the managed `V` and parses it.
+pub(crate) async fn verified<'r, T, V>(
+    req: &'r Request<'_>,
+    data: Data<'r>,
+) -> Outcome<'r, T, Error<'r>>
+where
+    T: Deserialize<'r>,
+    V: SignatureVerifier,
+{
+    let verifier = match req.rocket().state::<V>() {
+        Some(verifier) => verifier,
+        None => {
+            let name = std::any::type_name::<V>();
+            error!("verifying signatures requires a managed `{}`.", name);
+            let e = std::io::Error::other(format!("no {}", name));
+            return Outcome::Error((Status::InternalServerError, Error::Io(e)));
+        }
+    };
+    let config = EnumFormConfig::of(req);
+    let opts = config.options();
+    let limit = crate::form_limit(req);
+    crate::parse_body(req, data, &ContentType::Form, limit, |body| {
+        let body = crate::utf8(body)?;
+        if !verifier.verify(req, body) {
+            return Err(Error::Signature);
+        }
+        let (now, timestamp) = (now(), verifier.timestamp(req));
+        if timestamp.is_some_and(|timestamp| now.abs_diff(timestamp) > config.max_clock_skew) {
+            return Err(Error::Signature);
+        }
+        if let (Some(Nonces(store)), Some(nonce)) =
+            (req.rocket().state::<Nonces>(), verifier.nonce(req, body))
+        {
+            let expires = timestamp
+                .unwrap_or(now)
+                .saturating_add(config.max_clock_skew);
+            if !store.insert(&nonce, expires) {
+                return Err(Error::Signature);
+            }
+        }
+        codec::decode_with::<UrlEncodedCodec, T>(body, opts).map_err(|e| Error::Parse(body, e))
+    })
+    .await
+}
+
+/// The current time, in seconds since the Unix epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs())
+}
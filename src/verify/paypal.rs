@@ -0,0 +1,183 @@
+// Copyright (c) 2021 Cognite AS
+//! Verification of PayPal's Instant Payment Notifications, which PayPal
+//! confirms when the body is posted back to it verbatim.
+
+use std::{error, fmt, io};
+
+use rocket::data::{Capped, Data, FromData, Outcome};
+use rocket::http::ContentType;
+use rocket::request::Request;
+use serde::Deserialize;
+
+use crate::{cache, EnumFormConfig, Error};
+
+/// The endpoint validating notifications of live payments.
+pub const LIVE: &str = "https://ipnpb.paypal.com/cgi-bin/webscr";
+
+/// The endpoint validating notifications of the sandbox.
+pub const SANDBOX: &str = "https://ipnpb.sandbox.paypal.com/cgi-bin/webscr";
+
+/// The HTTP client [`PaypalIpn::verify`] posts notifications back to PayPal
+/// with, left to the application.
+#[rocket::async_trait]
+pub trait IpnClient: Send + Sync {
+    /// Posts `body`, with the Content-Type
+    /// `application/x-www-form-urlencoded`, to `url`, returning the body of
+    /// the response.
+    async fn post(&self, url: &str, body: Vec<u8>) -> io::Result<String>;
+}
+
+/// The error of [`PaypalIpn::verify`].
+#[derive(Debug)]
+pub enum IpnError {
+    /// PayPal did not send the notification, answering `INVALID`.
+    Invalid,
+
+    /// Posting the notification back failed, or PayPal answered neither
+    /// `VERIFIED` nor `INVALID`.
+    Io(io::Error),
+}
+
+impl fmt::Display for IpnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid => write!(f, "invalid notification"),
+            Self::Io(err) => write!(f, "i/o error: {}", err),
+        }
+    }
+}
+
+impl error::Error for IpnError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Invalid => None,
+            Self::Io(err) => Some(err),
+        }
+    }
+}
+
+/// The PaypalIpn guard: an Instant Payment Notification, parsed like
+/// [`UrlEncoded`](crate::UrlEncoded), with the exact body it was parsed from.
+///
+/// Anyone can post a notification, which is therefore only to be trusted
+/// once [`verify`](PaypalIpn::verify) returns its value, after PayPal
+/// confirmed sending it. The value can be inspected before, for instance to
+/// log it, with [`unverified`](PaypalIpn::unverified).
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use std::io;
+///
+/// use rocket::http::{ContentType, Status};
+/// use rocket::local::blocking::Client;
+/// use rocket::State;
+/// use rocket_enumform::verify::paypal::{self, IpnClient, PaypalIpn};
+///
+/// struct Echo;
+///
+/// #[rocket::async_trait]
+/// impl IpnClient for Echo {
+///     async fn post(&self, url: &str, body: Vec<u8>) -> io::Result<String> {
+///         // An HTTP client posting to PayPal, here answering for it.
+///         assert_eq!(url, paypal::SANDBOX);
+///         assert_eq!(body, b"cmd=_notify-validate&txn_id=61E67681CH3238416&mc_gross=19.95");
+///         Ok("VERIFIED".to_string())
+///     }
+/// }
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Payment {
+///     txn_id: String,
+///     mc_gross: String,
+/// }
+///
+/// #[post("/ipn", data = "<ipn>")]
+/// async fn ipn(ipn: PaypalIpn<Payment>, client: &State<Echo>) -> Status {
+///     match ipn.verify(client.inner(), paypal::SANDBOX).await {
+///         Ok(payment) => Status::Ok,
+///         Err(_) => Status::BadRequest,
+///     }
+/// }
+///
+/// let rocket = rocket::build().manage(Echo).mount("/", routes![ipn]);
+/// let client = Client::untracked(rocket).unwrap();
+/// let request = client.post("/ipn").header(ContentType::Form);
+/// let response = request.body("txn_id=61E67681CH3238416&mc_gross=19.95").dispatch();
+/// assert_eq!(response.status(), Status::Ok);
+/// ```
+pub struct PaypalIpn<T> {
+    value: T,
+    raw: Vec<u8>,
+}
+
+impl<T> PaypalIpn<T> {
+    /// The body, exactly as received.
+    pub fn raw(&self) -> &[u8] {
+        &self.raw
+    }
+
+    /// The value, which may not come from PayPal.
+    pub fn unverified(&self) -> &T {
+        &self.value
+    }
+
+    /// Posts the notification back to the endpoint `url`, [`LIVE`] or
+    /// [`SANDBOX`], with `client`, and returns its value if PayPal confirms
+    /// sending it.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`IpnError::Invalid`] if PayPal denies it, and with
+    /// [`IpnError::Io`] if it cannot tell.
+    pub async fn verify<C: IpnClient + ?Sized>(self, client: &C, url: &str) -> Result<T, IpnError> {
+        let mut body = b"cmd=_notify-validate&".to_vec();
+        body.extend_from_slice(&self.raw);
+        let response = client.post(url, body).await.map_err(IpnError::Io)?;
+        match response.trim() {
+            "VERIFIED" => Ok(self.value),
+            "INVALID" => Err(IpnError::Invalid),
+            response => Err(IpnError::Io(io::Error::other(format!(
+                "unexpected response: {}",
+                response
+            )))),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PaypalIpn<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PaypalIpn")
+            .field("value", &self.value)
+            .field("raw", &String::from_utf8_lossy(&self.raw))
+            .finish()
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>> FromData<'r> for PaypalIpn<T> {
+    type Error = Error<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        if let Err(e) = crate::check_content_type(req, &ContentType::Form) {
+            return crate::data_outcome(req, Err(e));
+        }
+        let limit = crate::form_limit(req);
+        let capped = match crate::read_bytes(req, data, limit).await {
+            Ok(capped) if capped.is_complete() => capped,
+            Ok(capped) => {
+                let read = capped.n.written.into();
+                return crate::data_outcome(req, Err(Error::TooLarge { limit, read }));
+            }
+            Err(e) => return crate::data_outcome(req, Err(Error::Io(e))),
+        };
+
+        let (raw, n) = (capped.value, capped.n);
+        let result = crate::decode_body(req, raw.clone()).and_then(|body| {
+            let body = cache::store_body(req, Capped::new(body, n)).into_inner();
+            let opts = EnumFormConfig::of(req).options();
+            crate::instrumented(req, body, |body| crate::decode_form(req, body, opts))
+        });
+        crate::data_outcome(req, result.map(|value| PaypalIpn { value, raw }))
+    }
+}
@@ -0,0 +1,212 @@
+// Copyright (c) 2021 Cognite AS
+//! Verification of requests from Slack, such as slash commands, signed with
+//! the signing secret of a Slack app.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use hmac::{Hmac, Mac};
+use rocket::data::{Data, FromData, Outcome};
+use rocket::error;
+use rocket::fairing::{AdHoc, Fairing};
+use rocket::request::Request;
+use serde::Deserialize;
+use sha2::Sha256;
+
+use super::SignatureVerifier;
+use crate::{Error, UrlEncoded};
+
+/// The header carrying the signature, `v0=` followed by its hexadecimal.
+const SIGNATURE: &str = "X-Slack-Signature";
+
+/// The header carrying the time of the request, in seconds since the Unix
+/// epoch.
+const TIMESTAMP: &str = "X-Slack-Request-Timestamp";
+
+/// The signing secret of a Slack app, which [`SlackVerified`] verifies
+/// requests with, as a [`SignatureVerifier`].
+///
+/// Managed by [`SigningSecret::fairing()`], or by the application itself.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket_enumform::verify::slack::SigningSecret;
+///
+/// #[launch]
+/// fn rocket() -> _ {
+///     rocket::build().manage(SigningSecret::new("8f742231b10e8888abcd99yyyzzz85a5"))
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SigningSecret(Vec<u8>);
+
+impl SigningSecret {
+    /// The signing secret `secret`, as shown in the settings of the app.
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        SigningSecret(secret.as_ref().to_vec())
+    }
+
+    /// Returns a fairing which manages the secret of the
+    /// `slack_signing_secret` key of the configuration. Ignition fails
+    /// without it.
+    pub fn fairing() -> impl Fairing {
+        AdHoc::try_on_ignite("Slack Signing Secret", |rocket| async move {
+            match rocket
+                .figment()
+                .extract_inner::<String>("slack_signing_secret")
+            {
+                Ok(secret) => Ok(rocket.manage(SigningSecret::new(secret))),
+                Err(e) => {
+                    error!("invalid slack_signing_secret: {}", e);
+                    Err(rocket)
+                }
+            }
+        })
+    }
+
+    /// Whether `signature` is the signature of `body` sent at `timestamp`.
+    fn verify_signature(&self, timestamp: &str, body: &str, signature: &str) -> bool {
+        let tag = match signature.strip_prefix("v0=").and_then(crate::decode_hex) {
+            Some(tag) => tag,
+            None => return false,
+        };
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.0).expect("HMAC accepts any key length");
+        mac.update(b"v0:");
+        mac.update(timestamp.as_bytes());
+        mac.update(b":");
+        mac.update(body.as_bytes());
+        mac.verify_slice(&tag).is_ok()
+    }
+}
+
+impl SignatureVerifier for SigningSecret {
+    fn verify(&self, req: &Request<'_>, body: &str) -> bool {
+        let headers = req.headers();
+        match (headers.get_one(TIMESTAMP), headers.get_one(SIGNATURE)) {
+            (Some(timestamp), Some(signature)) => {
+                self.timestamp(req).is_some() && self.verify_signature(timestamp, body, signature)
+            }
+            _ => false,
+        }
+    }
+
+    fn timestamp(&self, req: &Request<'_>) -> Option<u64> {
+        req.headers().get_one(TIMESTAMP)?.parse().ok()
+    }
+
+    /// The signature, which covers the timestamp.
+    fn nonce(&self, req: &Request<'_>, _: &str) -> Option<String> {
+        req.headers().get_one(SIGNATURE).map(String::from)
+    }
+}
+
+impl fmt::Debug for SigningSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningSecret").finish_non_exhaustive()
+    }
+}
+
+/// The SlackVerified guard: wraps a [`UrlEncoded`] guard, handing over its
+/// value only if the request carries a valid `X-Slack-Signature` of the body,
+/// made within [`EnumFormConfig::max_clock_skew`](crate::EnumFormConfig::max_clock_skew)
+/// as per its `X-Slack-Request-Timestamp`.
+///
+/// The signature is checked against the managed [`SigningSecret`] before
+/// the body is parsed. The guard fails with
+/// [`Error::Signature`], `Status::Unauthorized`, if
+/// it is missing, invalid or expired, and with
+/// `Status::InternalServerError` without a signing secret.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket_enumform::verify::slack::SlackVerified;
+/// use rocket_enumform::UrlEncoded;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct SlashCommand {
+///     command: String,
+///     text: String,
+///     user_id: String,
+/// }
+///
+/// #[post("/slack/commands", data = "<command>")]
+/// fn command(command: SlackVerified<UrlEncoded<SlashCommand>>) -> String {
+///     format!("{} {}", command.command, command.text)
+/// }
+///
+/// # use hmac::{Hmac, Mac};
+/// # use rocket::http::{ContentType, Header, Status};
+/// # use rocket::local::blocking::Client;
+/// # use rocket_enumform::verify::slack::SigningSecret;
+/// # use rocket_enumform::verify::{self, MemoryNonceStore};
+/// # use sha2::Sha256;
+/// let rocket = rocket::build()
+///     .manage(SigningSecret::new("secret"))
+///     .attach(verify::replay_protection(MemoryNonceStore::new()))
+///     .mount("/", routes![command]);
+/// let client = Client::untracked(rocket).unwrap();
+///
+/// let body = "command=%2Fweather&text=oslo&user_id=U1";
+/// let timestamp = std::time::SystemTime::now()
+///     .duration_since(std::time::UNIX_EPOCH)
+///     .unwrap()
+///     .as_secs()
+///     .to_string();
+/// let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+/// mac.update(format!("v0:{}:{}", timestamp, body).as_bytes());
+/// let signature: String = mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect();
+///
+/// let post = |signature: String| {
+///     let request = client.post("/slack/commands").header(ContentType::Form);
+///     let request = request.header(Header::new("X-Slack-Request-Timestamp", timestamp.clone()));
+///     request.header(Header::new("X-Slack-Signature", signature)).body(body).dispatch()
+/// };
+/// assert_eq!(post(format!("v0={}", signature)).into_string().unwrap(), "/weather oslo");
+/// assert_eq!(post(format!("v0={}", signature)).status(), Status::Unauthorized);
+/// assert_eq!(post(format!("v0={}", "0".repeat(64))).status(), Status::Unauthorized);
+/// ```
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SlackVerified<G>(pub G);
+
+impl<G> SlackVerified<G> {
+    /// Consumes the SlackVerified wrapper and returns the wrapped item.
+    #[inline(always)]
+    pub fn into_inner(self) -> G {
+        self.0
+    }
+}
+
+impl<G> From<G> for SlackVerified<G> {
+    fn from(value: G) -> Self {
+        SlackVerified(value)
+    }
+}
+
+impl<G> Deref for SlackVerified<G> {
+    type Target = G;
+
+    #[inline(always)]
+    fn deref(&self) -> &G {
+        &self.0
+    }
+}
+
+impl<G> DerefMut for SlackVerified<G> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut G {
+        &mut self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>> FromData<'r> for SlackVerified<UrlEncoded<T>> {
+    type Error = Error<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        super::verified::<T, SigningSecret>(req, data)
+            .await
+            .map(|value| SlackVerified(UrlEncoded(value)))
+    }
+}
@@ -0,0 +1,239 @@
+// Copyright (c) 2021 Cognite AS
+//! Verification of webhooks from Twilio, signed with the auth token of a
+//! Twilio account.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use hmac::{Hmac, Mac};
+use rocket::data::{Data, FromData, Outcome};
+use rocket::error;
+use rocket::fairing::{AdHoc, Fairing};
+use rocket::request::Request;
+use serde::Deserialize;
+use sha1::Sha1;
+
+use super::SignatureVerifier;
+use crate::{de, ser, Error, UrlEncoded};
+
+/// The header carrying the signature, in base64.
+const SIGNATURE: &str = "X-Twilio-Signature";
+
+/// The auth token of a Twilio account, which [`TwilioVerified`] verifies
+/// webhooks with as a [`SignatureVerifier`], and the base of the URLs Twilio
+/// requests.
+///
+/// Twilio signs the URL it requests, which the application sees only in
+/// part. By default, the URL of a request is taken to be `https://` followed
+/// by its `Host` header, path and query; set a base URL, such as
+/// `https://example.com`, where a proxy changes them.
+///
+/// Managed by [`AuthToken::fairing()`], or by the application itself.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket_enumform::verify::twilio::AuthToken;
+///
+/// #[launch]
+/// fn rocket() -> _ {
+///     let token = AuthToken::new("12345").base_url("https://example.com");
+///     rocket::build().manage(token)
+/// }
+/// ```
+#[derive(Clone)]
+pub struct AuthToken {
+    token: Vec<u8>,
+    base_url: Option<String>,
+}
+
+impl AuthToken {
+    /// The auth token `token`, as shown in the console of the account.
+    pub fn new(token: impl AsRef<[u8]>) -> Self {
+        AuthToken {
+            token: token.as_ref().to_vec(),
+            base_url: None,
+        }
+    }
+
+    /// Sets the scheme and authority of the URLs Twilio requests, without a
+    /// trailing slash.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Returns a fairing which manages the token of the `twilio_auth_token`
+    /// key of the configuration, with the base URL of the optional
+    /// `twilio_base_url` key. Ignition fails without a token.
+    pub fn fairing() -> impl Fairing {
+        AdHoc::try_on_ignite("Twilio Auth Token", |rocket| async move {
+            let figment = rocket.figment();
+            let token = match figment.extract_inner::<String>("twilio_auth_token") {
+                Ok(token) => AuthToken::new(token),
+                Err(e) => {
+                    error!("invalid twilio_auth_token: {}", e);
+                    return Err(rocket);
+                }
+            };
+            let token = match figment.extract_inner::<String>("twilio_base_url") {
+                Ok(base_url) => token.base_url(base_url),
+                Err(_) => token,
+            };
+            Ok(rocket.manage(token))
+        })
+    }
+
+    /// The URL Twilio requested for `req`, if known.
+    fn url(&self, req: &Request<'_>) -> Option<String> {
+        let base = match &self.base_url {
+            Some(base_url) => base_url.clone(),
+            None => format!("https://{}", req.host()?),
+        };
+        Some(format!("{}{}", base, req.uri()))
+    }
+
+    /// Whether `signature` is the signature of `pairs` posted to `url`: the
+    /// URL followed by each key and value, sorted by key.
+    fn verify_signature(&self, url: &str, pairs: &de::Pairs<'_>, signature: &str) -> bool {
+        let tag = match BASE64.decode(signature) {
+            Ok(tag) => tag,
+            Err(_) => return false,
+        };
+        let pairs: Vec<_> = pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        let opts = ser::Options {
+            canonical: true,
+            ..ser::Options::default()
+        };
+        let mut mac =
+            Hmac::<Sha1>::new_from_slice(&self.token).expect("HMAC accepts any key length");
+        mac.update(url.as_bytes());
+        for (key, value) in ser::ordered(&pairs, &opts) {
+            mac.update(key.as_bytes());
+            mac.update(value.as_bytes());
+        }
+        mac.verify_slice(&tag).is_ok()
+    }
+}
+
+impl SignatureVerifier for AuthToken {
+    fn verify(&self, req: &Request<'_>, body: &str) -> bool {
+        match (self.url(req), req.headers().get_one(SIGNATURE)) {
+            (Some(url), Some(signature)) => {
+                self.verify_signature(&url, &de::parse_pairs(body.as_bytes()), signature)
+            }
+            _ => false,
+        }
+    }
+
+    /// The signature, which covers the SID of the resource of the webhook.
+    /// Twilio signs no timestamp, so a replay is only detected within
+    /// [`EnumFormConfig::max_clock_skew`](crate::EnumFormConfig::max_clock_skew)
+    /// of the request.
+    fn nonce(&self, req: &Request<'_>, _: &str) -> Option<String> {
+        req.headers().get_one(SIGNATURE).map(String::from)
+    }
+}
+
+impl fmt::Debug for AuthToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuthToken")
+            .field("base_url", &self.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The TwilioVerified guard: wraps a [`UrlEncoded`] guard, handing over its
+/// value only if the request carries a valid `X-Twilio-Signature` of its URL
+/// and parameters.
+///
+/// The signature is checked against the managed [`AuthToken`], over the URL
+/// followed by the decoded keys and values, sorted by key as
+/// [`ser::Options::canonical`] sorts them, before the parameters are
+/// deserialized. The guard fails with [`Error::Signature`],
+/// `Status::Unauthorized`, if it is missing or invalid, and with
+/// `Status::InternalServerError` without an auth token.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket::http::{ContentType, Header, Status};
+/// use rocket::local::blocking::Client;
+/// use rocket_enumform::verify::twilio::{AuthToken, TwilioVerified};
+/// use rocket_enumform::UrlEncoded;
+///
+/// #[derive(Debug, Deserialize)]
+/// #[serde(rename_all = "PascalCase")]
+/// struct Call {
+///     call_sid: String,
+///     digits: String,
+/// }
+///
+/// #[post("/myapp.php?<foo>&<bar>", data = "<call>")]
+/// fn call(foo: u32, bar: u32, call: TwilioVerified<UrlEncoded<Call>>) -> String {
+///     format!("{} pressed {}", call.call_sid, call.digits)
+/// }
+///
+/// let token = AuthToken::new("12345").base_url("https://mycompany.com");
+/// let rocket = rocket::build().manage(token).mount("/", routes![call]);
+/// let client = Client::untracked(rocket).unwrap();
+///
+/// let post = |signature: &'static str| {
+///     let request = client.post("/myapp.php?foo=1&bar=2").header(ContentType::Form);
+///     let request = request.header(Header::new("X-Twilio-Signature", signature));
+///     let body = "CallSid=CA1234567890ABCDE&Caller=%2B12349013030&Digits=1234\
+///         &From=%2B12349013030&To=%2B18005551212";
+///     request.body(body).dispatch()
+/// };
+/// let response = post("0/KCTR6DLpKmkAf8muzZqo1nDgQ=");
+/// assert_eq!(response.into_string().unwrap(), "CA1234567890ABCDE pressed 1234");
+/// assert_eq!(post("AAAAAAAAAAAAAAAAAAAAAAAAAAA=").status(), Status::Unauthorized);
+/// ```
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TwilioVerified<G>(pub G);
+
+impl<G> TwilioVerified<G> {
+    /// Consumes the TwilioVerified wrapper and returns the wrapped item.
+    #[inline(always)]
+    pub fn into_inner(self) -> G {
+        self.0
+    }
+}
+
+impl<G> From<G> for TwilioVerified<G> {
+    fn from(value: G) -> Self {
+        TwilioVerified(value)
+    }
+}
+
+impl<G> Deref for TwilioVerified<G> {
+    type Target = G;
+
+    #[inline(always)]
+    fn deref(&self) -> &G {
+        &self.0
+    }
+}
+
+impl<G> DerefMut for TwilioVerified<G> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut G {
+        &mut self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>> FromData<'r> for TwilioVerified<UrlEncoded<T>> {
+    type Error = Error<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        super::verified::<T, AuthToken>(req, data)
+            .await
+            .map(|value| TwilioVerified(UrlEncoded(value)))
+    }
+}